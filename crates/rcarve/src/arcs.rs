@@ -0,0 +1,328 @@
+use crate::types::{PathArc, Toolpath};
+use std::f64::consts::PI;
+
+/// Arcs stop at a half turn, which every controller reads the same way.
+const MAX_SWEEP: f64 = PI;
+
+/// Flatter runs than this radius stay lines; the centre gets too far away to round
+/// cleanly in the G-code (mm).
+const MAX_RADIUS: f64 = 1000.0;
+
+/// Points tried past the end of an arc before settling for it.
+const MAX_MISSES: usize = 16;
+
+/// Z changes smaller than this count as level (mm).
+const LEVEL_TOLERANCE: f64 = 1e-9;
+
+type Point = (f64, f64, f64);
+
+/// An arc fitted to a run of points.
+struct Fit {
+    center: (f64, f64),
+    clockwise: bool,
+    /// Unit direction of travel where the arc ends.
+    end_tangent: (f64, f64),
+    /// Furthest the arc strays from the straight line between its ends.
+    bulge: f64,
+}
+
+/// Fit circular arcs to the paths of `toolpath`, so postprocessors can cut one G2/G3 move
+/// in place of a run of short lines. Returns the number of arcs fitted.
+///
+/// Every point an arc replaces lies within `tolerance` of it, and the arc bulges no more
+/// than `chord_tolerance` from the lines between them. Pass the tolerance the curves were
+/// flattened with, so curves come back as arcs while real corners stay lines. An arc that
+/// follows another starts on its tangent where it can, and Z may change along an arc at a
+/// steady rate, as on a helix. Drill cycles are left alone.
+pub fn fit_arcs(toolpath: &mut Toolpath, tolerance: f64, chord_tolerance: f64) -> usize {
+    toolpath.arcs.clear();
+    if toolpath.drill.is_some() {
+        return 0;
+    }
+    let arcs: Vec<Vec<PathArc>> = toolpath
+        .paths
+        .iter()
+        .map(|path| fit_path(path, tolerance, chord_tolerance))
+        .collect();
+    let count = arcs.iter().map(Vec::len).sum();
+    if count > 0 {
+        toolpath.arcs = arcs;
+    }
+    count
+}
+
+/// Greedily take the longest arc from each point along the path, preferring one that
+/// carries on along the tangent of the arc before it.
+fn fit_path(path: &[Point], tolerance: f64, chord_tolerance: f64) -> Vec<PathArc> {
+    let mut arcs = Vec::new();
+    let mut start = 0;
+    // Where the last arc ended at `start`, the direction it left in.
+    let mut tangent = None;
+    while start + 2 < path.len() {
+        let free = longest_fit(path, start, None, tolerance, chord_tolerance);
+        let held = tangent.and_then(|tangent| {
+            longest_fit(path, start, Some(tangent), tolerance, chord_tolerance)
+        });
+        let fitted = match (held, free) {
+            (Some(held), Some(free)) if held.0 >= free.0 => Some(held),
+            (held, free) => free.or(held),
+        };
+        match fitted {
+            // Runs that barely curve are cut as the lines they are.
+            Some((end, fit)) if fit.bulge >= tolerance => {
+                arcs.push(PathArc {
+                    start,
+                    end,
+                    center: fit.center,
+                    clockwise: fit.clockwise,
+                });
+                tangent = Some(fit.end_tangent);
+                start = end;
+            }
+            _ => {
+                tangent = None;
+                start += 1;
+            }
+        }
+    }
+    arcs
+}
+
+/// The furthest point along `path` an arc from `start` reaches while fitting.
+///
+/// A few misses are allowed before giving up, since the centre found for a short run of
+/// points wanders with their rounding.
+fn longest_fit(
+    path: &[Point],
+    start: usize,
+    tangent: Option<(f64, f64)>,
+    tolerance: f64,
+    chord_tolerance: f64,
+) -> Option<(usize, Fit)> {
+    let mut fitted = None;
+    let mut misses = 0;
+    for end in start + 2..path.len() {
+        match fit_span(&path[start..=end], tangent, tolerance, chord_tolerance) {
+            Some(fit) => {
+                fitted = Some((end, fit));
+                misses = 0;
+            }
+            None if misses < MAX_MISSES => misses += 1,
+            None => break,
+        }
+    }
+    fitted
+}
+
+/// Fit one arc through `points`, starting along `tangent` if given.
+fn fit_span(
+    points: &[Point],
+    tangent: Option<(f64, f64)>,
+    tolerance: f64,
+    chord_tolerance: f64,
+) -> Option<Fit> {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let center = match tangent {
+        Some(tangent) => tangent_center(first, tangent, last)?,
+        None => bisector_center(points)?,
+    };
+    let radius = distance(first, center);
+    // Circles smaller than the flattening tolerance are rounding, not curves.
+    if radius <= tolerance.max(chord_tolerance) || radius > MAX_RADIUS {
+        return None;
+    }
+
+    // The points must turn steadily one way around the centre, each on the circle and
+    // each line between them close to the arc.
+    let mut turned = Vec::with_capacity(points.len());
+    let mut sweep = 0.0;
+    for pair in points.windows(2) {
+        let step = signed_angle(
+            (pair[0].0 - center.0, pair[0].1 - center.1),
+            (pair[1].0 - center.0, pair[1].1 - center.1),
+        );
+        if step == 0.0 || step.signum() * sweep < 0.0 {
+            return None;
+        }
+        if (distance(pair[1], center) - radius).abs() > tolerance
+            || radius * (1.0 - (step / 2.0).cos()) > chord_tolerance
+        {
+            return None;
+        }
+        sweep += step;
+        turned.push(sweep);
+    }
+    if sweep.abs() > MAX_SWEEP {
+        return None;
+    }
+    let clockwise = sweep < 0.0;
+
+    let start_tangent = travel_direction(first, center, clockwise);
+    if tangent.is_some_and(|t| t.0 * start_tangent.0 + t.1 * start_tangent.1 <= 0.0) {
+        return None;
+    }
+
+    // Z must be level or move one way at a steady rate, so the whole arc cuts at one feed.
+    let rise = last.2 - first.2;
+    for (pair, turned) in points.windows(2).zip(&turned) {
+        let step = pair[1].2 - pair[0].2;
+        let steady = if rise.abs() <= LEVEL_TOLERANCE {
+            (pair[1].2 - first.2).abs() <= LEVEL_TOLERANCE
+        } else {
+            step.abs() > LEVEL_TOLERANCE
+                && step.signum() == rise.signum()
+                && (first.2 + rise * turned / sweep - pair[1].2).abs() <= tolerance
+        };
+        if !steady {
+            return None;
+        }
+    }
+
+    Some(Fit {
+        center,
+        clockwise,
+        end_tangent: travel_direction(last, center, clockwise),
+        bulge: radius * (1.0 - (sweep / 2.0).cos()),
+    })
+}
+
+/// Centre on the perpendicular bisector of the run's ends that best fits the points in
+/// between, so the arc starts and ends at the same radius. `None` if they lie in a line.
+fn bisector_center(points: &[Point]) -> Option<(f64, f64)> {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let (dx, dy) = (last.0 - first.0, last.1 - first.1);
+    let chord = dx.hypot(dy);
+    if chord < 1e-9 {
+        return None;
+    }
+    // Centres `midpoint + t * across`; each point p asks for
+    // 2t across·(p - first) = (p - first)·(p - last).
+    let across = (-dy / chord, dx / chord);
+    let (mut aa, mut ab) = (0.0, 0.0);
+    for p in &points[1..points.len() - 1] {
+        let a = 2.0 * (across.0 * (p.0 - first.0) + across.1 * (p.1 - first.1));
+        let b = (p.0 - first.0) * (p.0 - last.0) + (p.1 - first.1) * (p.1 - last.1);
+        aa += a * a;
+        ab += a * b;
+    }
+    if aa < 1e-12 {
+        return None;
+    }
+    let t = ab / aa;
+    Some((
+        (first.0 + last.0) / 2.0 + across.0 * t,
+        (first.1 + last.1) / 2.0 + across.1 * t,
+    ))
+}
+
+/// Centre of the circle leaving `start` along `tangent` that passes through `end`.
+fn tangent_center(start: Point, tangent: (f64, f64), end: Point) -> Option<(f64, f64)> {
+    let normal = (-tangent.1, tangent.0);
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let along_normal = normal.0 * dx + normal.1 * dy;
+    if along_normal.abs() < 1e-12 {
+        return None;
+    }
+    let offset = (dx * dx + dy * dy) / (2.0 * along_normal);
+    Some((start.0 + normal.0 * offset, start.1 + normal.1 * offset))
+}
+
+/// Unit direction of travel at `point` going round `center`.
+fn travel_direction(point: Point, center: (f64, f64), clockwise: bool) -> (f64, f64) {
+    let radius = distance(point, center);
+    let (rx, ry) = ((point.0 - center.0) / radius, (point.1 - center.1) / radius);
+    if clockwise {
+        (ry, -rx)
+    } else {
+        (-ry, rx)
+    }
+}
+
+/// Angle turned from `a` to `b`, anticlockwise positive.
+fn signed_angle(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 * b.1 - a.1 * b.0).atan2(a.0 * b.0 + a.1 * b.1)
+}
+
+fn distance(point: Point, center: (f64, f64)) -> f64 {
+    (point.0 - center.0).hypot(point.1 - center.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurbo::{Circle, PathEl, Shape};
+
+    fn flattened_circle(radius: f64, z: f64) -> Vec<Point> {
+        let mut points = Vec::new();
+        kurbo::flatten(
+            Circle::new((0.0, 0.0), radius).path_elements(0.1),
+            0.25,
+            |el| {
+                if let PathEl::MoveTo(p) | PathEl::LineTo(p) = el {
+                    points.push((p.x, p.y, z));
+                }
+            },
+        );
+        points
+    }
+
+    #[test]
+    fn test_flattened_circle_becomes_tangent_arcs() {
+        let path = flattened_circle(20.0, -1.0);
+        let mut toolpath = Toolpath::new(vec![path.clone()]);
+        let count = fit_arcs(&mut toolpath, 0.02, 0.25);
+        assert!((2..=4).contains(&count), "{count} arcs");
+
+        let arcs = &toolpath.arcs[0];
+        assert_eq!(arcs[0].start, 0);
+        // Half turns rarely land on a point, so a last line may close the loop.
+        assert!(arcs.last().unwrap().end >= path.len() - 2);
+        for pair in arcs.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        for arc in arcs {
+            assert!(!arc.clockwise);
+            assert!(arc.center.0.hypot(arc.center.1) < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_corners_and_lines_stay_lines() {
+        let hexagon: Vec<Point> = (0..=6)
+            .map(|i| {
+                let angle = i as f64 * PI / 3.0;
+                (10.0 * angle.cos(), 10.0 * angle.sin(), 0.0)
+            })
+            .collect();
+        let line: Vec<Point> = (0..10).map(|i| (i as f64, 0.0, 0.0)).collect();
+        let mut toolpath = Toolpath::new(vec![hexagon, line]);
+        assert_eq!(fit_arcs(&mut toolpath, 0.02, 0.25), 0);
+        assert!(toolpath.arcs.is_empty());
+    }
+
+    #[test]
+    fn test_helix_fits_with_steady_descent() {
+        let helix: Vec<Point> = (0..=64)
+            .map(|i| {
+                let angle = -(i as f64) * PI / 32.0;
+                (5.0 * angle.cos(), 5.0 * angle.sin(), -(i as f64) * 0.05)
+            })
+            .collect();
+        let mut toolpath = Toolpath::new(vec![helix]);
+        assert!((2..=3).contains(&fit_arcs(&mut toolpath, 0.01, 0.25)));
+        assert!(toolpath.arcs[0].iter().all(|arc| arc.clockwise));
+
+        // A step down partway breaks the steady descent.
+        let mut stepped = toolpath.paths[0].clone();
+        for point in &mut stepped[10..] {
+            point.2 -= 1.0;
+        }
+        let mut toolpath = Toolpath::new(vec![stepped]);
+        fit_arcs(&mut toolpath, 0.01, 0.25);
+        assert!(toolpath.arcs[0]
+            .iter()
+            .all(|arc| arc.end <= 9 || arc.start >= 10));
+    }
+}
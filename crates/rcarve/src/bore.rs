@@ -0,0 +1,255 @@
+use crate::types::{CutDirection, CutSide, Tool, Toolpath};
+use anyhow::{anyhow, Result};
+use std::f64::consts::TAU;
+
+/// Largest gap allowed between a straight helix segment and the true arc (mm).
+const ARC_TOLERANCE: f64 = 0.01;
+
+/// Generate a helical bore around the circle of `radius` centred on `center`.
+///
+/// The tool centre spirals down from the stock top to `target_depth`, dropping at most
+/// `pitch` per turn, then makes one full circle at depth to clean up the wall. An inside
+/// bore too wide to clear in one ring helixes near the centre instead and spirals out to
+/// the wall after every turn, stepping over by the tool's stepover. `cut_side`
+/// compensates for the tool: inside bores a hole of the circle's size, outside leaves a
+/// boss of it, and on line keeps the tool centre on the circle.
+pub fn generate_helical_bore_toolpath(
+    center: (f64, f64),
+    radius: f64,
+    tool: &Tool,
+    cut_side: &CutSide,
+    direction: CutDirection,
+    pitch: f64,
+    target_depth: f64,
+) -> Result<Toolpath> {
+    if target_depth <= 0.0 {
+        return Err(anyhow!("Bore depth must be positive"));
+    }
+    if pitch <= 0.0 {
+        return Err(anyhow!("Helix pitch must be positive"));
+    }
+
+    let tool_radius = tool.diameter / 2.0;
+    let path_radius = match cut_side {
+        CutSide::Inside => radius - tool_radius,
+        CutSide::Outside => radius + tool_radius,
+        CutSide::OnLine => radius,
+    };
+    if path_radius <= 0.0 {
+        return Err(anyhow!(
+            "The {:.2}mm hole is not larger than the {:.2}mm tool; drill it instead",
+            radius * 2.0,
+            tool.diameter
+        ));
+    }
+
+    // Climb milling runs counter-clockwise inside a hole and clockwise around a boss.
+    let outside = matches!(cut_side, CutSide::Outside);
+    let sign = if (direction == CutDirection::Climb) != outside {
+        1.0
+    } else {
+        -1.0
+    };
+
+    let turns = ((target_depth / pitch) - 1e-9).ceil().max(1.0) as usize;
+    let segments = if path_radius > ARC_TOLERANCE {
+        (TAU / (2.0 * (1.0 - ARC_TOLERANCE / path_radius).acos())).ceil() as usize
+    } else {
+        0
+    }
+    .max(8);
+
+    // A hole more than twice the tool's width would leave a core standing in the middle,
+    // so the helix runs at the tool radius and every turn spirals out to the wall.
+    let helix_radius = match cut_side {
+        CutSide::Inside => path_radius.min(tool_radius),
+        _ => path_radius,
+    };
+    let spiral_turns = ((path_radius - helix_radius) / (tool.diameter * tool.stepover) - 1e-9)
+        .ceil()
+        .max(0.0) as usize;
+
+    let point = |radius: f64, step: usize, z: f64| {
+        let angle = sign * TAU * step as f64 / segments as f64;
+        (
+            center.0 + radius * angle.cos(),
+            center.1 + radius * angle.sin(),
+            z,
+        )
+    };
+    let helix_steps = turns * segments;
+    let helix_z = |helix_step: usize| -target_depth * helix_step as f64 / helix_steps as f64;
+    if spiral_turns == 0 {
+        let mut path: Vec<(f64, f64, f64)> = (0..=helix_steps)
+            .map(|step| point(path_radius, step, helix_z(step)))
+            .collect();
+        // One more turn at full depth.
+        path.extend((1..=segments).map(|step| point(path_radius, step, -target_depth)));
+        return Ok(Toolpath::new(vec![path]));
+    }
+
+    let spiral_steps = spiral_turns * segments;
+    let mut path = vec![point(helix_radius, 0, 0.0)];
+    let mut step = 0;
+    for turn in 0..turns {
+        if turn > 0 {
+            // Back in across the floor just cleared.
+            path.push(point(helix_radius, step, helix_z(turn * segments)));
+        }
+        for helix_step in turn * segments + 1..=(turn + 1) * segments {
+            step += 1;
+            path.push(point(helix_radius, step, helix_z(helix_step)));
+        }
+        let z = helix_z((turn + 1) * segments);
+        for spiral_step in 1..=spiral_steps {
+            step += 1;
+            let t = spiral_step as f64 / spiral_steps as f64;
+            path.push(point(
+                helix_radius + (path_radius - helix_radius) * t,
+                step,
+                z,
+            ));
+        }
+        // A full turn at the wall.
+        for _ in 0..segments {
+            step += 1;
+            path.push(point(path_radius, step, z));
+        }
+    }
+
+    Ok(Toolpath::new(vec![path]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FeedSettings, ToolType, Units};
+
+    fn endmill() -> Tool {
+        Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    #[test]
+    fn test_helical_bore_descends_at_pitch_then_finishes() {
+        let toolpath = generate_helical_bore_toolpath(
+            (10.0, 20.0),
+            5.0,
+            &endmill(),
+            &CutSide::Inside,
+            CutDirection::Climb,
+            1.5,
+            5.0,
+        )
+        .unwrap();
+        assert_eq!(toolpath.paths.len(), 1);
+        let path = &toolpath.paths[0];
+
+        // The tool edge touches the 10mm hole wall throughout.
+        for p in path {
+            assert!(((p.0 - 10.0).hypot(p.1 - 20.0) - 2.0).abs() < 1e-9);
+        }
+        assert_eq!(path[0].2, 0.0);
+
+        // Four turns of 1.25mm, then a full turn at depth.
+        let at_depth = path.iter().position(|p| p.2 == -5.0).unwrap();
+        let per_turn = (path.len() - 1 - at_depth) as f64;
+        assert_eq!(at_depth as f64 / per_turn, 4.0);
+        assert!(path[at_depth..].iter().all(|p| p.2 == -5.0));
+        assert_eq!(path.last().unwrap().0, path[at_depth].0);
+        for pair in path[..=at_depth].windows(2) {
+            let drop = pair[0].2 - pair[1].2;
+            assert!(drop > 0.0 && drop * per_turn <= 1.5);
+        }
+
+        // Climb milling inside a hole runs counter-clockwise.
+        let cross =
+            (path[1].0 - 10.0) * (path[2].1 - 20.0) - (path[1].1 - 20.0) * (path[2].0 - 10.0);
+        assert!(cross > 0.0);
+    }
+
+    #[test]
+    fn test_wide_bore_spirals_out_from_the_centre() {
+        // A 24mm hole is four times the 6mm tool: bored at the wall alone, it would leave
+        // a 6mm core.
+        let tool = endmill();
+        let toolpath = generate_helical_bore_toolpath(
+            (0.0, 0.0),
+            12.0,
+            &tool,
+            &CutSide::Inside,
+            CutDirection::Climb,
+            2.0,
+            4.0,
+        )
+        .unwrap();
+        let path = &toolpath.paths[0];
+        assert_eq!(path[0], (3.0, 0.0, 0.0));
+        assert!(path.iter().all(|p| p.0.hypot(p.1) <= 9.0 + 1e-9));
+
+        // Where each level passes its start direction, the tool steps out at most one
+        // stepover at a time, from cutting over the centre to the wall.
+        for z in [-2.0, -4.0] {
+            let mut radii: Vec<f64> = path
+                .iter()
+                .filter(|p| p.2 == z && p.1.abs() < 1e-9 && p.0 > 0.0)
+                .map(|p| p.0)
+                .collect();
+            radii.sort_by(f64::total_cmp);
+            radii.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+            assert!(radii[0] <= tool.diameter / 2.0);
+            assert!((radii.last().unwrap() - 9.0).abs() < 1e-9);
+            for pair in radii.windows(2) {
+                assert!(pair[1] - pair[0] <= tool.diameter * tool.stepover + 1e-9);
+            }
+        }
+        assert_eq!(path.last().unwrap().2, -4.0);
+        assert!((path.last().unwrap().0.hypot(path.last().unwrap().1) - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_helical_bore_compensation() {
+        let radius_of = |side: CutSide| {
+            let toolpath = generate_helical_bore_toolpath(
+                (0.0, 0.0),
+                8.0,
+                &endmill(),
+                &side,
+                CutDirection::Climb,
+                2.0,
+                2.0,
+            )
+            .unwrap();
+            // The widest the tool centre goes: the wall.
+            toolpath.paths[0]
+                .iter()
+                .map(|p| p.0.hypot(p.1))
+                .fold(0.0, f64::max)
+        };
+        assert!((radius_of(CutSide::Inside) - 5.0).abs() < 1e-9);
+        assert!((radius_of(CutSide::Outside) - 11.0).abs() < 1e-9);
+        assert!((radius_of(CutSide::OnLine) - 8.0).abs() < 1e-9);
+
+        let too_small = generate_helical_bore_toolpath(
+            (0.0, 0.0),
+            3.0,
+            &endmill(),
+            &CutSide::Inside,
+            CutDirection::Climb,
+            1.0,
+            2.0,
+        );
+        assert!(too_small
+            .unwrap_err()
+            .to_string()
+            .contains("drill it instead"));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::types::{DrillCycle, Toolpath};
+use anyhow::{anyhow, Result};
+
+/// Height above the stock top where each hole's feed starts: the R plane of a canned cycle.
+pub const DRILL_RETRACT_HEIGHT: f64 = 1.0;
+
+/// Gap above the bottom of the previous peck where an expanded peck cycle stops its rapid
+/// and starts feeding again, as G83 does.
+pub const PECK_CLEARANCE: f64 = 0.5;
+
+/// Generate a drilling toolpath with one hole at each of `holes`.
+///
+/// Each path is a vertical feed from the retract plane down to `target_depth`. The G-code
+/// writer turns it into a canned cycle, or expanded moves, according to `cycle`.
+pub fn generate_drill_toolpath(
+    holes: &[(f64, f64)],
+    target_depth: f64,
+    cycle: DrillCycle,
+) -> Result<Toolpath> {
+    if target_depth <= 0.0 {
+        return Err(anyhow!("Drill depth must be positive"));
+    }
+    if holes.is_empty() {
+        return Err(anyhow!(
+            "Drill requires at least one circle or point marker"
+        ));
+    }
+    if cycle.peck_depth.is_some_and(|peck| peck <= 0.0) {
+        return Err(anyhow!("Peck depth must be positive"));
+    }
+    if cycle.dwell.is_some_and(|dwell| dwell < 0.0) {
+        return Err(anyhow!("Dwell must not be negative"));
+    }
+
+    let paths = holes
+        .iter()
+        .map(|&(x, y)| vec![(x, y, DRILL_RETRACT_HEIGHT), (x, y, -target_depth)])
+        .collect();
+    Ok(Toolpath {
+        drill: Some(cycle),
+        ..Toolpath::new(paths)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drill_toolpath_has_one_plunge_per_hole() {
+        let cycle = DrillCycle {
+            peck_depth: Some(3.0),
+            dwell: None,
+        };
+        let toolpath = generate_drill_toolpath(&[(10.0, 5.0), (42.0, 5.0)], 12.0, cycle).unwrap();
+
+        assert_eq!(toolpath.drill, Some(cycle));
+        assert_eq!(
+            toolpath.paths,
+            vec![
+                vec![(10.0, 5.0, DRILL_RETRACT_HEIGHT), (10.0, 5.0, -12.0)],
+                vec![(42.0, 5.0, DRILL_RETRACT_HEIGHT), (42.0, 5.0, -12.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drill_rejects_bad_cycles() {
+        let holes = [(0.0, 0.0)];
+        let peck = DrillCycle {
+            peck_depth: Some(0.0),
+            dwell: None,
+        };
+        let dwell = DrillCycle {
+            peck_depth: None,
+            dwell: Some(-1.0),
+        };
+        assert!(generate_drill_toolpath(&holes, 0.0, DrillCycle::default()).is_err());
+        assert!(generate_drill_toolpath(&[], 5.0, DrillCycle::default()).is_err());
+        assert!(generate_drill_toolpath(&holes, 5.0, peck).is_err());
+        assert!(generate_drill_toolpath(&holes, 5.0, dwell).is_err());
+    }
+}
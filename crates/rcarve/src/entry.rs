@@ -0,0 +1,536 @@
+use crate::types::{EntryStrategy, SegmentFeed, Tool, Toolpath};
+use anyhow::{anyhow, Result};
+use std::f64::consts::PI;
+
+/// Segments used to approximate one revolution of a helical entry.
+const HELIX_SEGMENTS: usize = 32;
+
+/// Replace the straight plunge at the start of each path with the configured entry move.
+///
+/// Only paths starting over uncut stock get one, descending from the level above (or the
+/// stock top) to their own start depth; a path starting inside the area its level has
+/// already cut feeds straight down. `boundary` holds the loops bounding the machined area;
+/// a helix is only placed where it stays inside them and falls back to a ramp elsewhere.
+/// Returns the number of fallbacks.
+pub fn apply_entry(
+    toolpath: &mut Toolpath,
+    entry: &EntryStrategy,
+    tool: &Tool,
+    boundary: &[Vec<(f64, f64)>],
+) -> Result<usize> {
+    let (radius, max_angle_deg) = match entry {
+        EntryStrategy::Plunge => return Ok(0),
+        EntryStrategy::Ramp { max_angle_deg } => (None, *max_angle_deg),
+        EntryStrategy::Helix {
+            radius,
+            max_angle_deg,
+        } => {
+            if *radius <= 0.0 {
+                return Err(anyhow!("Helix radius must be positive"));
+            }
+            (Some(*radius), *max_angle_deg)
+        }
+    };
+    if max_angle_deg <= 0.0 || max_angle_deg >= 90.0 {
+        return Err(anyhow!("Entry angle must be between 0 and 90 degrees"));
+    }
+    let slope = max_angle_deg.to_radians().tan();
+
+    let tool_radius = tool.diameter / 2.0;
+    let mut fallbacks = 0;
+    for index in 0..toolpath.paths.len() {
+        let top_z = level_top(toolpath, index);
+        let path = &toolpath.paths[index];
+        let Some(start) = path.first() else {
+            continue;
+        };
+        if start.2 >= top_z - 1e-9 || is_cleared(toolpath, index, tool_radius) {
+            continue;
+        }
+
+        let helix = radius
+            .and_then(|radius| helix_entry(path, top_z, radius, slope, tool_radius, boundary));
+        let new_path = match helix {
+            Some(path) => path,
+            None => {
+                if radius.is_some() {
+                    fallbacks += 1;
+                }
+                ramp_entry(path, top_z, slope)
+            }
+        };
+        // The descent ramps; what follows keeps the rates it had, or takes them from its
+        // direction where the entry re-walked the path.
+        let descent = new_path
+            .iter()
+            .position(|point| point.2 <= start.2 + 1e-9)
+            .unwrap_or(0);
+        let mut feeds = vec![SegmentFeed::Ramp; descent];
+        if new_path[descent..] == path[..] {
+            feeds.extend(toolpath.path_segment_feeds(index));
+        } else {
+            feeds.resize(new_path.len() - 1, SegmentFeed::Auto);
+        }
+        toolpath.paths[index] = new_path;
+        toolpath.set_segment_feeds(index, feeds);
+    }
+
+    Ok(fallbacks)
+}
+
+/// The Z already cleared above a path: the next level up, or the stock top.
+fn level_top(toolpath: &Toolpath, index: usize) -> f64 {
+    let Some(level) = toolpath.level_of(index) else {
+        return 0.0;
+    };
+    toolpath
+        .levels
+        .iter()
+        .map(|other| other.z)
+        .filter(|z| *z > level.z + 1e-9)
+        .fold(0.0, f64::min)
+}
+
+/// Whether the start of path `index` lies inside the area swept by the paths before it on
+/// the same level.
+fn is_cleared(toolpath: &Toolpath, index: usize, tool_radius: f64) -> bool {
+    let start = toolpath.paths[index][0];
+    let level = toolpath.path_levels.get(index);
+    (0..index)
+        .filter(|&other| toolpath.path_levels.get(other) == level)
+        .flat_map(|other| toolpath.paths[other].windows(2))
+        .any(|w| {
+            segment_distance((start.0, start.1), (w[0].0, w[0].1), (w[1].0, w[1].1)) < tool_radius
+        })
+}
+
+/// Descend along the path itself at no more than `slope` (rise over run).
+///
+/// Closed loops ramp forward and then cut one full lap at depth. Open paths ramp backwards
+/// towards their start, zig-zagging if they are too short, and are then cut as usual.
+fn ramp_entry(path: &[(f64, f64, f64)], top_z: f64, slope: f64) -> Vec<(f64, f64, f64)> {
+    let cumulative = cumulative_lengths(path);
+    let perimeter = *cumulative.last().unwrap_or(&0.0);
+    if perimeter <= 1e-9 {
+        return path.to_vec();
+    }
+
+    let depth = top_z - path[0].2;
+    let ramp_length = depth / slope;
+
+    if is_closed(path) {
+        let ramp = walk_cyclic(path, &cumulative, 0.0, ramp_length);
+        let mut result = ramp_profile(&ramp, top_z, depth);
+        let lap = walk_cyclic(path, &cumulative, ramp_length % perimeter, perimeter);
+        result.extend(lap.into_iter().skip(1));
+        return result;
+    }
+
+    let leg = ramp_length.min(perimeter);
+    let legs = ((ramp_length / leg) - 1e-9).ceil().max(1.0) as usize;
+    let mut ramp = Vec::new();
+    for i in 0..legs {
+        // Legs alternate direction so that the last one ends at the path start.
+        let toward_start = (legs - i) % 2 == 1;
+        let (from, to) = if toward_start { (leg, 0.0) } else { (0.0, leg) };
+        let points = sample(path, &cumulative, from, to);
+        let skip = usize::from(!ramp.is_empty());
+        ramp.extend(points.into_iter().skip(skip));
+    }
+    let mut result = ramp_profile(&ramp, top_z, depth);
+    result.extend(path.iter().skip(1).copied());
+    result
+}
+
+/// Lower a walked sequence of points linearly from `top_z` by `depth` over its length.
+fn ramp_profile(points: &[(f64, f64, f64)], top_z: f64, depth: f64) -> Vec<(f64, f64, f64)> {
+    let total: f64 = points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum();
+    let mut travelled = 0.0;
+    let mut result = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            let prev = points[i - 1];
+            travelled += (point.0 - prev.0).hypot(point.1 - prev.1);
+        }
+        let fraction = if total > 0.0 { travelled / total } else { 1.0 };
+        let z = (top_z - depth * fraction).max(point.2);
+        result.push((point.0, point.1, z));
+    }
+    result
+}
+
+/// Spiral down beside a closed path, ending on it, where the helix fits inside `boundary`.
+fn helix_entry(
+    path: &[(f64, f64, f64)],
+    top_z: f64,
+    radius: f64,
+    slope: f64,
+    tool_radius: f64,
+    boundary: &[Vec<(f64, f64)>],
+) -> Option<Vec<(f64, f64, f64)>> {
+    if boundary.is_empty() || !is_closed(path) {
+        return None;
+    }
+
+    // The helix circle touches the path at a vertex or an edge midpoint, on whichever side
+    // has room for it.
+    let needed = radius + tool_radius - 1e-9;
+    let vertex_count = path.len() - 1;
+    let fits = |point: (f64, f64, f64), tangent: (f64, f64)| {
+        let length = tangent.0.hypot(tangent.1);
+        if length <= 1e-9 {
+            return None;
+        }
+        let (nx, ny) = (-tangent.1 / length * radius, tangent.0 / length * radius);
+        [(point.0 + nx, point.1 + ny), (point.0 - nx, point.1 - ny)]
+            .into_iter()
+            .find(|&centre| boundary_distance(centre, boundary) >= needed)
+    };
+    let (rotated, (cx, cy)) = (0..vertex_count).find_map(|i| {
+        let (before, vertex, after) = (
+            path[(i + vertex_count - 1) % vertex_count],
+            path[i],
+            path[i + 1],
+        );
+        if let Some(centre) = fits(vertex, (after.0 - before.0, after.1 - before.1)) {
+            // Rotate the loop so it starts at the vertex.
+            let mut rotated: Vec<(f64, f64, f64)> = path[i..vertex_count].to_vec();
+            rotated.extend_from_slice(&path[..=i]);
+            return Some((rotated, centre));
+        }
+        let middle = (
+            (vertex.0 + after.0) / 2.0,
+            (vertex.1 + after.1) / 2.0,
+            (vertex.2 + after.2) / 2.0,
+        );
+        let centre = fits(middle, (after.0 - vertex.0, after.1 - vertex.1))?;
+        // Split the edge and start the loop from its middle.
+        let mut rotated = vec![middle];
+        rotated.extend_from_slice(&path[i + 1..vertex_count]);
+        rotated.extend_from_slice(&path[..=i]);
+        rotated.push(middle);
+        Some((rotated, centre))
+    })?;
+
+    let (vx, vy, bottom_z) = rotated[0];
+    let depth = top_z - bottom_z;
+    let pitch = 2.0 * PI * radius * slope;
+    let turns = depth / pitch;
+    let steps = ((turns * HELIX_SEGMENTS as f64).ceil() as usize).max(1);
+
+    // Wind back from the loop start so the descent and the flat revolution finish on it.
+    let end_angle = (vy - cy).atan2(vx - cx);
+    let start_angle = end_angle - 2.0 * PI * turns;
+    let mut result = Vec::with_capacity(steps + HELIX_SEGMENTS + rotated.len() + 1);
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let angle = start_angle + 2.0 * PI * turns * t;
+        result.push((
+            cx + radius * angle.cos(),
+            cy + radius * angle.sin(),
+            top_z - depth * t,
+        ));
+    }
+
+    // One flat revolution at depth to clear the floor of the entry hole.
+    for step in 1..HELIX_SEGMENTS {
+        let angle = end_angle + 2.0 * PI * step as f64 / HELIX_SEGMENTS as f64;
+        result.push((
+            cx + radius * angle.cos(),
+            cy + radius * angle.sin(),
+            bottom_z,
+        ));
+    }
+
+    result.extend(rotated);
+    Some(result)
+}
+
+fn is_closed(path: &[(f64, f64, f64)]) -> bool {
+    match (path.first(), path.last()) {
+        (Some(first), Some(last)) if path.len() > 2 => {
+            (first.0 - last.0).abs() < 1e-9 && (first.1 - last.1).abs() < 1e-9
+        }
+        _ => false,
+    }
+}
+
+fn cumulative_lengths(path: &[(f64, f64, f64)]) -> Vec<f64> {
+    let mut cumulative = Vec::with_capacity(path.len());
+    let mut total = 0.0;
+    for (i, point) in path.iter().enumerate() {
+        if i > 0 {
+            let prev = path[i - 1];
+            total += (point.0 - prev.0).hypot(point.1 - prev.1);
+        }
+        cumulative.push(total);
+    }
+    cumulative
+}
+
+fn point_at(path: &[(f64, f64, f64)], cumulative: &[f64], distance: f64) -> (f64, f64, f64) {
+    for i in 1..path.len() {
+        if distance <= cumulative[i] {
+            let span = cumulative[i] - cumulative[i - 1];
+            let t = if span > 0.0 {
+                (distance - cumulative[i - 1]) / span
+            } else {
+                0.0
+            };
+            let (a, b) = (path[i - 1], path[i]);
+            return (
+                a.0 + (b.0 - a.0) * t,
+                a.1 + (b.1 - a.1) * t,
+                a.2 + (b.2 - a.2) * t,
+            );
+        }
+    }
+    path[path.len() - 1]
+}
+
+/// Points along the path between two distances, in travel order, including vertices.
+fn sample(
+    path: &[(f64, f64, f64)],
+    cumulative: &[f64],
+    from: f64,
+    to: f64,
+) -> Vec<(f64, f64, f64)> {
+    let (lo, hi) = (from.min(to), from.max(to));
+    let mut points = vec![point_at(path, cumulative, lo)];
+    for (i, point) in path.iter().enumerate() {
+        if cumulative[i] > lo + 1e-9 && cumulative[i] < hi - 1e-9 {
+            points.push(*point);
+        }
+    }
+    points.push(point_at(path, cumulative, hi));
+    if from > to {
+        points.reverse();
+    }
+    points
+}
+
+/// Walk `length` forward around a closed loop starting at distance `start`.
+fn walk_cyclic(
+    path: &[(f64, f64, f64)],
+    cumulative: &[f64],
+    start: f64,
+    length: f64,
+) -> Vec<(f64, f64, f64)> {
+    let perimeter = cumulative[cumulative.len() - 1];
+    let mut points: Vec<(f64, f64, f64)> = Vec::new();
+    let mut position = start;
+    let mut remaining = length;
+    while remaining > 1e-9 {
+        let end = (position + remaining).min(perimeter);
+        let skip = usize::from(!points.is_empty());
+        points.extend(
+            sample(path, cumulative, position, end)
+                .into_iter()
+                .skip(skip),
+        );
+        remaining -= end - position;
+        position = if end >= perimeter { 0.0 } else { end };
+    }
+    if points.is_empty() {
+        points.push(point_at(path, cumulative, start));
+    }
+    points
+}
+
+/// Distance from a point to the nearest edge of any boundary loop.
+fn boundary_distance(point: (f64, f64), boundary: &[Vec<(f64, f64)>]) -> f64 {
+    let mut best = f64::INFINITY;
+    for ring in boundary {
+        for i in 0..ring.len() {
+            let distance = segment_distance(point, ring[i], ring[(i + 1) % ring.len()]);
+            best = best.min(distance);
+        }
+    }
+    best
+}
+
+/// Distance from a point to the segment `a`-`b`.
+fn segment_distance(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point.0 - (a.0 + dx * t)).hypot(point.1 - (a.1 + dy * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocket::generate_pocket_toolpath;
+    use crate::profile::generate_profile_toolpath;
+    use crate::types::{CutSide, FeedSettings, ToolType, Units};
+
+    fn tool() -> Tool {
+        Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    fn square(size: f64) -> Vec<(f64, f64)> {
+        vec![
+            (0.0, 0.0),
+            (size, 0.0),
+            (size, size),
+            (0.0, size),
+            (0.0, 0.0),
+        ]
+    }
+
+    fn max_descent_angle(path: &[(f64, f64, f64)]) -> f64 {
+        path.windows(2)
+            .filter(|w| w[1].2 < w[0].2)
+            .map(|w| {
+                let run = (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1);
+                (w[0].2 - w[1].2).atan2(run).to_degrees()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_ramp_entry_on_profile_levels() {
+        let mut toolpath =
+            generate_profile_toolpath(&square(50.0), &tool(), &CutSide::Outside, 4.0).unwrap();
+        let entry = EntryStrategy::Ramp { max_angle_deg: 5.0 };
+        apply_entry(&mut toolpath, &entry, &tool(), &[]).unwrap();
+
+        // First level ramps from the stock top, second from the first level.
+        assert_eq!(toolpath.paths[0][0].2, 0.0);
+        assert_eq!(toolpath.paths[1][0].2, -2.0);
+        for path in &toolpath.paths {
+            assert!(max_descent_angle(path) <= 5.0 + 1e-6);
+        }
+
+        // After the ramp a full lap is cut at the level depth.
+        let level_z = toolpath.paths[1].last().unwrap().2;
+        assert_eq!(level_z, -4.0);
+        let at_depth: f64 = toolpath.paths[1]
+            .windows(2)
+            .filter(|w| w[0].2 == level_z && w[1].2 == level_z)
+            .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+            .sum();
+        let perimeter = 4.0 * 50.0 + 2.0 * PI * 3.0;
+        assert!(at_depth >= perimeter - 0.5, "lap at depth {at_depth}");
+    }
+
+    #[test]
+    fn test_ramp_entry_on_short_open_path_zigzags() {
+        let mut toolpath = Toolpath::new(vec![vec![(0.0, 0.0, -3.0), (10.0, 0.0, -3.0)]]);
+        let entry = EntryStrategy::Ramp {
+            max_angle_deg: 10.0,
+        };
+        apply_entry(&mut toolpath, &entry, &tool(), &[]).unwrap();
+
+        let path = &toolpath.paths[0];
+        assert_eq!(path[0].2, 0.0);
+        assert!(max_descent_angle(path) <= 10.0 + 1e-6);
+        // The ramp finishes at the path start, which is then cut in full.
+        assert_eq!(path[path.len() - 2], (0.0, 0.0, -3.0));
+        assert_eq!(path[path.len() - 1], (10.0, 0.0, -3.0));
+        // The descent is cut at the ramp rate and the path itself at the feed rate.
+        let last = path.len() - 1;
+        assert!((1..last).all(|point| toolpath.segment_feed(0, point) == SegmentFeed::Ramp));
+        assert_eq!(toolpath.segment_feed(0, last), SegmentFeed::Cut);
+    }
+
+    #[test]
+    fn test_helix_entry_in_pocket() {
+        let outer = square(60.0);
+        let original = generate_pocket_toolpath(&outer, &[], &tool(), 2.0).unwrap();
+        let mut toolpath = original.clone();
+        let entry = EntryStrategy::Helix {
+            radius: 2.0,
+            max_angle_deg: 3.0,
+        };
+        let boundary = vec![outer];
+        let fallbacks = apply_entry(&mut toolpath, &entry, &tool(), &boundary).unwrap();
+
+        // The helix fits beside the outermost ring, on the side away from the wall.
+        assert_eq!(fallbacks, 0);
+
+        let clearance = |path: &[(f64, f64, f64)]| {
+            path.iter()
+                .map(|p| boundary_distance((p.0, p.1), &boundary))
+                .fold(f64::INFINITY, f64::min)
+        };
+        assert_eq!(toolpath.paths[0][0].2, 0.0);
+        assert!(max_descent_angle(&toolpath.paths[0]) <= 3.0 + 0.1);
+        // The entry stays a tool radius off the wall.
+        assert!(clearance(&toolpath.paths[0]) >= 3.0 - 1e-6);
+        // The rings inside it start over the area it cleared and feed straight down.
+        assert_eq!(toolpath.paths[1..], original.paths[1..]);
+    }
+
+    #[test]
+    fn test_helix_ends_where_the_ring_starts() {
+        let outer = square(60.0);
+        let pocket = generate_pocket_toolpath(&outer, &[], &tool(), 2.0).unwrap();
+        let ring = pocket.paths[0].clone();
+        let slope = 3.0_f64.to_radians().tan();
+
+        let path = helix_entry(&ring, 0.0, 2.0, slope, 3.0, &[outer]).expect("helix fits");
+
+        // The descent and then the flat revolution end where the ring starts.
+        let end = path.last().unwrap();
+        let ends: Vec<usize> = (0..path.len()).filter(|&i| path[i] == *end).collect();
+        let (descended, revolved) = (ends[0], ends[1]);
+        assert_eq!(revolved - descended, HELIX_SEGMENTS);
+        let revolution = &path[descended + 1..=revolved];
+        assert!(revolution.iter().all(|p| p.2 == -2.0));
+        let centre = revolution.iter().fold((0.0, 0.0), |sum, p| {
+            (
+                sum.0 + p.0 / HELIX_SEGMENTS as f64,
+                sum.1 + p.1 / HELIX_SEGMENTS as f64,
+            )
+        });
+        assert!(path[..descended].len() > HELIX_SEGMENTS);
+        for point in &path[..=revolved] {
+            let radius = (point.0 - centre.0).hypot(point.1 - centre.1);
+            assert!((radius - 2.0).abs() < 1e-6);
+        }
+        // The helix sits inside the ring, clear of the wall.
+        assert!(boundary_distance(centre, &[square(60.0)]) >= 5.0 - 1e-6);
+    }
+
+    #[test]
+    fn test_pocket_enters_each_level_once() {
+        let mut toolpath = generate_pocket_toolpath(&square(60.0), &[], &tool(), 4.0).unwrap();
+        let rings = toolpath.paths.len();
+        let entry = EntryStrategy::Ramp { max_angle_deg: 5.0 };
+        apply_entry(&mut toolpath, &entry, &tool(), &[]).unwrap();
+
+        let entries = (0..rings)
+            .filter(|&index| toolpath.paths[index][0].2 > toolpath.level_of(index).unwrap().z)
+            .count();
+        assert!(rings > 4);
+        assert_eq!(entries, 2);
+
+        // Every ring links to the next at depth, except where a ramp's closing lap ends
+        // across the pocket from the next ring's start.
+        assert_eq!(crate::link_paths(&mut toolpath, 3.0), rings - 4);
+    }
+
+    #[test]
+    fn test_entry_rejects_bad_angle() {
+        let mut toolpath = Toolpath::new(vec![vec![(0.0, 0.0, -3.0), (10.0, 0.0, -3.0)]]);
+        let entry = EntryStrategy::Ramp { max_angle_deg: 0.0 };
+        assert!(apply_entry(&mut toolpath, &entry, &tool(), &[]).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::drill::PECK_CLEARANCE;
+use crate::project::{Project, ToolpathStatus};
+use crate::toolpath_generation::operation_cut_order;
+use crate::types::{DrillCycle, SegmentFeed, Toolpath};
+use crate::verification::artifact_passes;
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+/// Feed rate (mm/min) assumed for toolpaths that set none, as the postprocessors do.
+const DEFAULT_FEED: f64 = 100.0;
+
+/// Feed moves turning more sharply than this stop the machine at the corner (degrees).
+const CORNER_ANGLE_DEG: f64 = 30.0;
+
+/// How fast the machine moves, for estimating how long a job takes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MachineProfile {
+    /// Rapid traverse rate (mm/min).
+    pub rapid_rate: f64,
+    /// Acceleration and deceleration of every axis (mm/s²).
+    pub acceleration: f64,
+    /// Height the tool retracts to between paths; match the postprocessor's (mm).
+    pub safe_z: f64,
+    /// Time to change tools, including spinning the new one up (s).
+    pub tool_change_seconds: f64,
+    /// Optional: What an hour of machine time costs, for quoting.
+    pub hourly_rate: Option<f64>,
+}
+
+impl Default for MachineProfile {
+    fn default() -> Self {
+        Self {
+            rapid_rate: 5000.0,
+            acceleration: 500.0,
+            safe_z: 10.0,
+            tool_change_seconds: 60.0,
+            hourly_rate: None,
+        }
+    }
+}
+
+/// Machining time and the motion behind it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MachiningEstimate {
+    pub seconds: f64,
+    /// Length of the feed moves, plunges included (mm).
+    pub cut_length: f64,
+    /// Length of the rapid moves (mm).
+    pub rapid_length: f64,
+    /// Times the tool feeds down from safe height into the work.
+    pub plunges: usize,
+    /// Tool changes, loading the first tool included.
+    pub tool_changes: usize,
+}
+
+impl MachiningEstimate {
+    /// Cost of the machine time at `machine`'s hourly rate, if it has one.
+    pub fn cost(&self, machine: &MachineProfile) -> Option<f64> {
+        machine.hourly_rate.map(|rate| rate * self.seconds / 3600.0)
+    }
+}
+
+impl AddAssign for MachiningEstimate {
+    fn add_assign(&mut self, other: Self) {
+        self.seconds += other.seconds;
+        self.cut_length += other.cut_length;
+        self.rapid_length += other.rapid_length;
+        self.plunges += other.plunges;
+        self.tool_changes += other.tool_changes;
+    }
+}
+
+/// Machining time for a whole project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobEstimate {
+    /// One entry per operation; `None` where the operation has no toolpath yet.
+    pub operations: Vec<Option<MachiningEstimate>>,
+    pub total: MachiningEstimate,
+    /// Total cost at the machine's hourly rate, if it has one.
+    pub cost: Option<f64>,
+}
+
+/// Estimate running one toolpath on its own, starting above its first point.
+pub fn estimate_toolpath(toolpath: &Toolpath, machine: &MachineProfile) -> MachiningEstimate {
+    let mut motion = Motion::new(machine, None);
+    motion.toolpath(toolpath);
+    motion.estimate
+}
+
+/// Estimate running every ready toolpath of `project` as exported: in cutting order, from
+/// the stock origin, changing tools between passes that use different ones. Dirty and
+/// invalid toolpaths are left out, since they cannot be exported. An inlay plug is cut as
+/// its own program, so its board starts over from the origin with no tool loaded.
+///
+/// Each move accelerates from rest and decelerates to a stop, except that feed moves
+/// running on in much the same direction at the same rate blend into one.
+pub fn estimate_project(project: &Project) -> JobEstimate {
+    let machine = &project.machine;
+    let (x, y, _) = project.stock.origin.unwrap_or_default();
+    let mut operations = vec![None; project.operations.len()];
+    let mut total = MachiningEstimate::default();
+
+    for board in project.boards() {
+        let mut motion = Motion::new(machine, Some((x, y, machine.safe_z)));
+        let mut current_tool = None;
+        for index in operation_cut_order(project) {
+            if project.operations[index].board() != board {
+                continue;
+            }
+            let ready = project
+                .operation_states
+                .get(index)
+                .is_some_and(|state| matches!(state.status(), ToolpathStatus::Ready { .. }));
+            let Some(artifact) = project.toolpath_for_operation(index).filter(|_| ready) else {
+                continue;
+            };
+            for (toolpath, tool_index) in artifact_passes(artifact, project, index) {
+                if current_tool != Some(tool_index) {
+                    motion.tool_change();
+                    current_tool = Some(tool_index);
+                }
+                motion.toolpath(toolpath);
+            }
+            let estimate = std::mem::take(&mut motion.estimate);
+            total += estimate;
+            operations[index] = Some(estimate);
+        }
+    }
+
+    JobEstimate {
+        operations,
+        total,
+        cost: total.cost(machine),
+    }
+}
+
+/// The machine's motion as the G-code writer drives it, timed as it goes.
+struct Motion<'a> {
+    machine: &'a MachineProfile,
+    position: Option<(f64, f64, f64)>,
+    estimate: MachiningEstimate,
+}
+
+impl<'a> Motion<'a> {
+    fn new(machine: &'a MachineProfile, position: Option<(f64, f64, f64)>) -> Self {
+        Self {
+            machine,
+            position,
+            estimate: MachiningEstimate::default(),
+        }
+    }
+
+    fn tool_change(&mut self) {
+        self.retract();
+        self.estimate.seconds += self.machine.tool_change_seconds;
+        self.estimate.tool_changes += 1;
+    }
+
+    fn toolpath(&mut self, toolpath: &Toolpath) {
+        let cut_rate = toolpath.feeds.feed_rate.unwrap_or(DEFAULT_FEED);
+        let plunge_rate = toolpath.feeds.plunge_rate.unwrap_or(cut_rate);
+        let ramp_rate = toolpath.feeds.ramp_rate.unwrap_or(plunge_rate);
+
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            if path.is_empty() {
+                continue;
+            }
+            let start = path[0];
+            self.retract();
+            self.rapid((start.0, start.1, self.machine.safe_z));
+            if let Some(cycle) = &toolpath.drill {
+                self.drill(start, path[path.len() - 1], cycle, plunge_rate);
+                self.retract();
+                continue;
+            }
+
+            self.feed(&[start], plunge_rate);
+            self.estimate.plunges += 1;
+
+            // Moves at one rate run together until a sharp corner.
+            let mut run = vec![start];
+            let mut run_rate = cut_rate;
+            for (next, &point) in path.iter().enumerate().skip(1) {
+                let previous = run[run.len() - 1];
+                let rate = match toolpath.segment_feed(index, next) {
+                    SegmentFeed::Ramp => ramp_rate,
+                    SegmentFeed::Plunge => plunge_rate,
+                    _ => cut_rate,
+                };
+                let turns = run.len() > 1 && is_corner(run[run.len() - 2], previous, point);
+                if run.len() > 1 && (rate != run_rate || turns) {
+                    self.feed(&run[1..], run_rate);
+                    run = vec![previous];
+                }
+                run.push(point);
+                run_rate = rate;
+            }
+            if run.len() > 1 {
+                self.feed(&run[1..], run_rate);
+            }
+            self.retract();
+        }
+    }
+
+    /// Peck down from the retract plane at `top` to `bottom`, clearing chips between pecks.
+    fn drill(
+        &mut self,
+        top: (f64, f64, f64),
+        bottom: (f64, f64, f64),
+        cycle: &DrillCycle,
+        rate: f64,
+    ) {
+        self.rapid(top);
+        self.estimate.plunges += 1;
+        let mut depth = top.2;
+        loop {
+            depth = match cycle.peck_depth {
+                Some(peck) => (depth - peck).max(bottom.2),
+                None => bottom.2,
+            };
+            self.feed(&[(top.0, top.1, depth)], rate);
+            if depth <= bottom.2 {
+                break;
+            }
+            self.rapid(top);
+            self.rapid((top.0, top.1, (depth + PECK_CLEARANCE).min(top.2)));
+        }
+        self.estimate.seconds += cycle.dwell.unwrap_or(0.0);
+    }
+
+    fn retract(&mut self) {
+        if let Some((x, y, z)) = self.position {
+            if z < self.machine.safe_z {
+                self.rapid((x, y, self.machine.safe_z));
+            }
+        }
+    }
+
+    fn rapid(&mut self, to: (f64, f64, f64)) {
+        let length = self.position.map_or(0.0, |from| distance(from, to));
+        self.estimate.rapid_length += length;
+        self.estimate.seconds += move_seconds(length, self.machine.rapid_rate, self.machine);
+        self.position = Some(to);
+    }
+
+    /// Feed through `points` as one move that only stops at its end.
+    fn feed(&mut self, points: &[(f64, f64, f64)], rate: f64) {
+        let mut length = 0.0;
+        for &point in points {
+            length += self.position.map_or(0.0, |from| distance(from, point));
+            self.position = Some(point);
+        }
+        self.estimate.cut_length += length;
+        self.estimate.seconds += move_seconds(length, rate, self.machine);
+    }
+}
+
+/// Whether the path turns too sharply at `b` to keep its speed.
+fn is_corner(a: (f64, f64, f64), b: (f64, f64, f64), c: (f64, f64, f64)) -> bool {
+    let (u, v) = (
+        (b.0 - a.0, b.1 - a.1, b.2 - a.2),
+        (c.0 - b.0, c.1 - b.1, c.2 - b.2),
+    );
+    let lengths = distance(a, b) * distance(b, c);
+    if lengths == 0.0 {
+        return false;
+    }
+    let cos = (u.0 * v.0 + u.1 * v.1 + u.2 * v.2) / lengths;
+    cos < CORNER_ANGLE_DEG.to_radians().cos()
+}
+
+fn distance(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2) + (b.2 - a.2).powi(2)).sqrt()
+}
+
+/// Time to move `length` mm from rest to rest at `rate` mm/min, accelerating as the
+/// machine allows; short moves never reach full speed.
+fn move_seconds(length: f64, rate: f64, machine: &MachineProfile) -> f64 {
+    if length <= 0.0 || rate <= 0.0 {
+        return 0.0;
+    }
+    let speed = rate / 60.0;
+    let acceleration = machine.acceleration;
+    if acceleration <= 0.0 {
+        return length / speed;
+    }
+    if length >= speed * speed / acceleration {
+        length / speed + speed / acceleration
+    } else {
+        2.0 * (length / acceleration).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FeedSettings;
+
+    fn machine() -> MachineProfile {
+        MachineProfile {
+            rapid_rate: 6000.0,
+            acceleration: 100.0,
+            safe_z: 5.0,
+            tool_change_seconds: 30.0,
+            hourly_rate: Some(60.0),
+        }
+    }
+
+    #[test]
+    fn test_move_time_follows_the_acceleration_profile() {
+        let machine = machine();
+        // 100mm/s takes 1s and 50mm to reach; a 150mm move cruises for the middle 50mm.
+        assert!((move_seconds(150.0, 6000.0, &machine) - 2.5).abs() < 1e-9);
+        // A 50mm move only reaches 70.7mm/s before braking.
+        assert!((move_seconds(50.0, 6000.0, &machine) - 2.0 * 0.5f64.sqrt()).abs() < 1e-9);
+        let unlimited = MachineProfile {
+            acceleration: 0.0,
+            ..machine
+        };
+        assert_eq!(move_seconds(150.0, 6000.0, &unlimited), 1.5);
+    }
+
+    #[test]
+    fn test_estimate_toolpath_breakdown() {
+        let machine = MachineProfile {
+            acceleration: 0.0,
+            ..machine()
+        };
+        let mut toolpath = Toolpath::new(vec![
+            vec![(0.0, 0.0, -1.0), (60.0, 0.0, -1.0), (60.0, 30.0, -1.0)],
+            vec![(100.0, 30.0, -1.0), (100.0, 0.0, -1.0)],
+        ]);
+        toolpath.feeds = FeedSettings {
+            feed_rate: Some(600.0),
+            plunge_rate: Some(60.0),
+            ..FeedSettings::default()
+        };
+
+        let estimate = estimate_toolpath(&toolpath, &machine);
+        assert_eq!(estimate.plunges, 2);
+        assert_eq!(estimate.tool_changes, 0);
+        assert!((estimate.cut_length - (6.0 + 90.0 + 6.0 + 30.0)).abs() < 1e-9);
+        // Out of the first path, across to the second, and out of that.
+        assert!((estimate.rapid_length - (6.0 + 40.0 + 6.0)).abs() < 1e-9);
+        // Plunges take 6s each, 120mm of cutting 12s, rapids 0.52s.
+        assert!((estimate.seconds - (12.0 + 12.0 + 0.52)).abs() < 1e-9);
+        assert!((estimate.cost(&machine).unwrap() - estimate.seconds / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_smooth_paths_run_on_and_corners_stop() {
+        let machine = machine();
+        let straight: Vec<_> = (0..=10).map(|i| (i as f64 * 15.0, 0.0, -1.0)).collect();
+        let zigzag: Vec<_> = (0..=10)
+            .map(|i| (i as f64 * 15.0, if i % 2 == 0 { 0.0 } else { 15.0 }, -1.0))
+            .collect();
+        let time = |path: Vec<(f64, f64, f64)>| {
+            let mut toolpath = Toolpath::new(vec![path]);
+            toolpath.feeds.feed_rate = Some(6000.0);
+            estimate_toolpath(&toolpath, &machine).seconds
+        };
+        let straight = time(straight);
+        let zigzag = time(zigzag);
+        // Ten short segments in a line take as long as one 150mm move, plus the plunge.
+        assert!((straight - (2.5 + move_seconds(6.0, 6000.0, &machine) * 2.0)).abs() < 1e-9);
+        assert!(zigzag > straight + 5.0);
+    }
+}
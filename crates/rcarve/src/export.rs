@@ -0,0 +1,142 @@
+use crate::{
+    operation_cut_order, Board, CutSide, GCode, GCodeWriter, Operation, PostProcessor, Project,
+    ToolLibrary, ToolpathPass, ToolpathPassKind, ToolpathStatus,
+};
+use anyhow::{anyhow, Result};
+
+/// Write the whole project as one G-code program.
+///
+/// A project that also carves an inlay plug cuts two boards, which cannot be one program;
+/// export each with [`export_board_gcode`] instead.
+pub fn export_gcode(
+    project: &Project,
+    tools: &ToolLibrary,
+    post: &dyn PostProcessor,
+) -> Result<GCode> {
+    match project.boards()[..] {
+        [] => Err(anyhow!("Project has no operations to export")),
+        [board] => export_board_gcode(project, tools, post, board),
+        _ => Err(anyhow!(
+            "The project carves an inlay plug from a second board; export the stock and the \
+             plug as separate programs"
+        )),
+    }
+}
+
+/// Write the operations that cut `board` as one G-code program.
+///
+/// Operations are written in [`operation_cut_order`], each pass in sequence, with a tool
+/// change whenever a pass uses a different tool from the one before it, in the project's
+/// units. Every operation needs an up-to-date, valid toolpath; generate toolpaths first.
+pub fn export_board_gcode(
+    project: &Project,
+    tools: &ToolLibrary,
+    post: &dyn PostProcessor,
+    board: Board,
+) -> Result<GCode> {
+    if !project.boards().contains(&board) {
+        return Err(anyhow!(
+            "Project has no operations on the {board} to export"
+        ));
+    }
+
+    let mut writer = GCodeWriter::with_units(post, project.units);
+    writer.comment(&project.meta.name);
+    let mut current_tool = None;
+
+    for index in operation_cut_order(project) {
+        let operation = &project.operations[index];
+        if operation.board() != board {
+            continue;
+        }
+        let name = format!("Operation {} - {}", index + 1, operation_name(operation));
+        let state = project
+            .operation_states
+            .get(index)
+            .map(|state| state.status())
+            .unwrap_or(ToolpathStatus::Dirty);
+        let artifact = match (state, project.toolpath_for_operation(index)) {
+            (ToolpathStatus::Ready { .. }, Some(artifact)) => artifact,
+            (ToolpathStatus::Invalid { warnings }, _) => {
+                return Err(anyhow!(
+                    "{name} has an invalid toolpath: {}",
+                    warnings.join("; ")
+                ));
+            }
+            _ => {
+                return Err(anyhow!(
+                    "{name} has no up-to-date toolpath; generate toolpaths before exporting"
+                ));
+            }
+        };
+
+        // Artifacts saved before passes were recorded only carry the finish toolpath.
+        let fallback;
+        let passes = if artifact.passes.is_empty() {
+            fallback = [ToolpathPass::new(
+                primary_tool_index(operation),
+                ToolpathPassKind::Finish,
+                artifact.toolpath.clone(),
+            )];
+            &fallback[..]
+        } else {
+            &artifact.passes[..]
+        };
+
+        writer.comment(&name);
+        for pass in passes {
+            if current_tool != Some(pass.tool_index) {
+                let tool = tools.tools.get(pass.tool_index).ok_or_else(|| {
+                    anyhow!(
+                        "{name} uses tool #{} which is not in the library",
+                        pass.tool_index
+                    )
+                })?;
+                writer.tool_change(pass.tool_index + 1, tool, &pass.toolpath.feeds);
+                current_tool = Some(pass.tool_index);
+            }
+            if passes.len() > 1 {
+                writer.comment(match pass.kind {
+                    ToolpathPassKind::Clearance => "Clearance pass",
+                    ToolpathPassKind::Finish => "Finish pass",
+                });
+            }
+            writer.toolpath(&pass.toolpath);
+        }
+    }
+
+    Ok(writer.finish())
+}
+
+fn operation_name(operation: &Operation) -> String {
+    match operation {
+        Operation::Profile { cut_side, .. } => {
+            let side = match cut_side {
+                CutSide::Inside => "inside",
+                CutSide::Outside => "outside",
+                CutSide::OnLine => "on line",
+            };
+            format!("Profile ({side})")
+        }
+        Operation::Pocket { .. } => "Pocket".to_string(),
+        Operation::VCarve {
+            inlay_plug: None, ..
+        } => "V-Carve".to_string(),
+        Operation::VCarve { .. } => "V-Carve inlay plug".to_string(),
+        Operation::Drill { .. } => "Drill".to_string(),
+        Operation::Bore { cut_side, .. } => match cut_side {
+            CutSide::Outside => "Bore (boss)".to_string(),
+            _ => "Bore".to_string(),
+        },
+    }
+}
+
+fn primary_tool_index(operation: &Operation) -> usize {
+    match operation {
+        Operation::Profile { tool_index, .. }
+        | Operation::Pocket { tool_index, .. }
+        | Operation::VCarve { tool_index, .. }
+        | Operation::Drill { tool_index, .. }
+        | Operation::Bore { tool_index, .. } => *tool_index,
+    }
+}
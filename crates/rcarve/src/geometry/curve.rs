@@ -1,9 +1,6 @@
-use kurbo::{BezPath, Circle, Line, PathEl, Rect, Shape as KurboShape};
+use kurbo::{BezPath, Circle, Line, PathEl, Point, Rect, Shape as KurboShape};
 use serde::{Deserialize, Serialize};
 
-#[cfg(test)]
-use kurbo::Point;
-
 /// A curve that can be used in shapes and operations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Curve {
@@ -13,6 +10,8 @@ pub enum Curve {
     Circle(Circle),
     /// A Bézier path (can contain multiple segments).
     BezPath(BezPath),
+    /// A point marker, such as a hole centre.
+    Point(Point),
 }
 
 impl Curve {
@@ -22,6 +21,7 @@ impl Curve {
             Curve::Line(line) => line.bounding_box(),
             Curve::Circle(circle) => circle.bounding_box(),
             Curve::BezPath(path) => path.bounding_box(),
+            Curve::Point(point) => Rect::from_points(*point, *point),
         }
     }
 
@@ -69,6 +69,7 @@ impl Curve {
 
                 points
             }
+            Curve::Point(point) => vec![(point.x, point.y)],
         }
     }
 
@@ -77,6 +78,7 @@ impl Curve {
         match self {
             Curve::Line(_) => false,
             Curve::Circle(_) => true,
+            Curve::Point(_) => false,
             Curve::BezPath(path) => path
                 .elements()
                 .last()
@@ -85,6 +87,32 @@ impl Curve {
         }
     }
 
+    /// The circle this curve traces, if it is one.
+    ///
+    /// Closed paths count when every point lies within `tolerance` of the circle through
+    /// their bounding box centre, so SVG circles imported as Béziers are recognised.
+    pub fn as_circle(&self, tolerance: f64) -> Option<Circle> {
+        match self {
+            Curve::Circle(circle) => Some(*circle),
+            Curve::BezPath(path) if self.is_closed() => {
+                let center = path.bounding_box().center();
+                // Points are sampled on the curve itself, so coarse spacing is enough.
+                let distances: Vec<f64> = self
+                    .flatten(0.5)
+                    .into_iter()
+                    .map(|(x, y)| (x - center.x).hypot(y - center.y))
+                    .collect();
+                if distances.len() < 4 {
+                    return None;
+                }
+                let radius = distances.iter().sum::<f64>() / distances.len() as f64;
+                let round = distances.iter().all(|d| (d - radius).abs() <= tolerance);
+                (round && radius > tolerance).then(|| Circle::new(center, radius))
+            }
+            _ => None,
+        }
+    }
+
     /// Apply an affine transformation to the curve.
     pub fn apply_affine(&mut self, affine: kurbo::Affine) {
         match self {
@@ -106,6 +134,9 @@ impl Curve {
             Curve::BezPath(path) => {
                 path.apply_affine(affine);
             }
+            Curve::Point(point) => {
+                *point = affine * *point;
+            }
         }
     }
 }
@@ -184,4 +215,34 @@ mod tests {
         let points = curve.flatten(0.1);
         assert!(points.len() >= 3);
     }
+
+    #[test]
+    fn test_point_marker() {
+        let mut curve = Curve::Point(Point::new(2.0, 3.0));
+        assert!(!curve.is_closed());
+        assert_eq!(curve.flatten(0.1), vec![(2.0, 3.0)]);
+
+        curve.apply_affine(kurbo::Affine::translate((10.0, 0.0)) * kurbo::Affine::scale(2.0));
+        assert_eq!(curve.flatten(0.1), vec![(14.0, 6.0)]);
+        assert_eq!(curve.bounding_box().area(), 0.0);
+    }
+
+    #[test]
+    fn test_as_circle_fits_bezier_circles() {
+        let circle = Circle::new(Point::new(12.0, -4.0), 7.5);
+        let mut curve = Curve::BezPath(circle.to_path(0.01));
+        curve.apply_affine(kurbo::Affine::translate((3.0, 0.0)));
+        let fitted = curve.as_circle(0.05).unwrap();
+        assert!((fitted.center - Point::new(15.0, -4.0)).hypot() < 1e-6);
+        assert!((fitted.radius - 7.5).abs() < 0.01);
+
+        // Squares, ellipses and open paths are not circles.
+        let square = Curve::BezPath(Rect::new(0.0, 0.0, 10.0, 10.0).to_path(0.1));
+        assert!(square.as_circle(0.05).is_none());
+        let mut ellipse = Curve::Circle(circle);
+        ellipse.apply_affine(kurbo::Affine::scale_non_uniform(1.0, 1.1));
+        assert!(ellipse.as_circle(0.05).is_none());
+        let line = Curve::Line(Line::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0)));
+        assert!(line.as_circle(0.05).is_none());
+    }
 }
@@ -24,7 +24,7 @@ use region::Region as RegionType;
 use shape::Shape as ShapeType;
 
 /// Registry of all shapes, curves, and regions in a project.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ShapeRegistry {
     /// All shapes in the project, indexed by their ID.
     pub shapes: HashMap<ShapeIdType, ShapeType>,
@@ -42,16 +42,6 @@ pub struct ImportedBatch {
     pub region_ids: Vec<RegionId>,
 }
 
-impl Default for ShapeRegistry {
-    fn default() -> Self {
-        Self {
-            shapes: HashMap::new(),
-            curves: HashMap::new(),
-            regions: HashMap::new(),
-        }
-    }
-}
-
 impl ShapeRegistry {
     /// Create a new empty registry.
     pub fn new() -> Self {
@@ -140,6 +130,11 @@ impl ShapeRegistry {
         self.add_curve(Curve::Circle(circle))
     }
 
+    /// Create a point marker.
+    pub fn create_point(&mut self, position: (f64, f64)) -> CurveId {
+        self.add_curve(Curve::Point(Point::new(position.0, position.1)))
+    }
+
     /// Create a Bézier path curve from a kurbo BezPath.
     pub fn create_bezpath(&mut self, path: BezPath) -> CurveId {
         self.add_curve(Curve::BezPath(path))
@@ -220,7 +215,7 @@ impl ShapeRegistry {
         for node in group.children() {
             match node {
                 usvg::Node::Group(g) => {
-                    self.import_usvg_group(&g, source, path_count);
+                    self.import_usvg_group(g, source, path_count);
                 }
                 usvg::Node::Path(path) => {
                     if !path.is_visible() {
@@ -102,9 +102,8 @@ fn create_polyline(points: &[(f64, f64)], closed: bool) -> Polyline {
     if !effective_points.is_empty() {
         let mut clean_points = Vec::new();
         clean_points.push(effective_points[0]);
-        for i in 1..effective_points.len() {
+        for &curr in effective_points.iter().skip(1) {
             let prev = clean_points.last().unwrap();
-            let curr = effective_points[i];
             if (prev.0 - curr.0).abs() > 1e-9 || (prev.1 - curr.1).abs() > 1e-9 {
                 clean_points.push(curr);
             }
@@ -173,7 +172,7 @@ fn tessellate_arc(x1: f64, y1: f64, x2: f64, y2: f64, bulge: f64) -> Vec<(f64, f
     vec![(px, py)]
 }
 
-fn is_point_inside(point: &(f64, f64), polygon: &[(f64, f64)]) -> bool {
+pub(crate) fn is_point_inside(point: &(f64, f64), polygon: &[(f64, f64)]) -> bool {
     let x = point.0;
     let y = point.1;
     let mut inside = false;
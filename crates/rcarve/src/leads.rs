@@ -0,0 +1,425 @@
+use crate::types::{CutSide, LeadKind, LeadSettings, SegmentFeed, Tool, Toolpath};
+use anyhow::{anyhow, Result};
+use std::f64::consts::FRAC_PI_2;
+
+/// Segments used for a quarter-circle lead arc.
+const ARC_SEGMENTS: usize = 9;
+/// Times a lead is halved before it is dropped for lack of room.
+const MAX_SHRINK_STEPS: usize = 4;
+/// Slack allowed on the tool-radius clearance, covering offset approximation error.
+const CLEARANCE_TOLERANCE: f64 = 0.05;
+
+/// Add lead-in and lead-out moves to every closed loop of a profile toolpath.
+///
+/// Leads sit on the waste side of the cut (outside the loop for `Outside`, inside for
+/// `Inside`). A lead that would bring the tool closer than its radius to any of the
+/// `obstacles` is halved until it fits, and dropped if it still does not. Each loop is
+/// restarted mid-way along its longest straight run, and the cut overlaps its own start by
+/// `overlap` before leading out. Returns the number of dropped leads.
+pub fn apply_leads(
+    toolpath: &mut Toolpath,
+    cut_side: &CutSide,
+    leads: &LeadSettings,
+    tool: &Tool,
+    obstacles: &[Vec<(f64, f64)>],
+) -> Result<usize> {
+    if leads.length <= 0.0 || leads.overlap < 0.0 {
+        return Err(anyhow!(
+            "Lead length must be positive and overlap must not be negative"
+        ));
+    }
+    let outward = match cut_side {
+        CutSide::Outside => true,
+        CutSide::Inside => false,
+        CutSide::OnLine => return Err(anyhow!("Leads need an inside or outside cut side")),
+    };
+    let clearance = tool.diameter / 2.0 - CLEARANCE_TOLERANCE;
+
+    let mut dropped = 0;
+    for index in 0..toolpath.paths.len() {
+        let path = &mut toolpath.paths[index];
+        if !is_closed(path) {
+            continue;
+        }
+
+        // Waste lies to the right of travel on a CCW loop cut outside, and flips with
+        // either the winding or the cut side.
+        let waste_right = (signed_area(path) > 0.0) == outward;
+        let waste_normal = |t: (f64, f64)| {
+            if waste_right {
+                (t.1, -t.0)
+            } else {
+                (-t.1, t.0)
+            }
+        };
+
+        start_mid_segment(path);
+        let start = path[0];
+        let Some(start_tangent) = tangent_at(path, 0) else {
+            continue;
+        };
+
+        let lead_in = fit_lead(leads, clearance, obstacles, |size| {
+            lead_points(
+                leads.kind,
+                start,
+                start_tangent,
+                waste_normal(start_tangent),
+                size,
+                true,
+            )
+        });
+
+        let overlap = walk_from_start(path, leads.overlap);
+        let end = *overlap.last().unwrap_or(&start);
+        let end_tangent = overlap_tangent(path, &overlap).unwrap_or(start_tangent);
+        let lead_out = fit_lead(leads, clearance, obstacles, |size| {
+            lead_points(
+                leads.kind,
+                end,
+                end_tangent,
+                waste_normal(end_tangent),
+                size,
+                false,
+            )
+        });
+
+        // Leads are cut at the feed rate; the loop keeps taking its rates from direction.
+        let mut new_path = Vec::with_capacity(path.len() + overlap.len() + 2 * ARC_SEGMENTS);
+        match lead_in {
+            Some(points) => new_path.extend(points),
+            None => {
+                dropped += 1;
+                new_path.push(start);
+            }
+        }
+        let mut feeds = vec![SegmentFeed::Cut; new_path.len() - 1];
+        new_path.extend(path.iter().skip(1).copied());
+        new_path.extend(overlap.into_iter().skip(1));
+        feeds.resize(new_path.len() - 1, SegmentFeed::Auto);
+        match lead_out {
+            Some(points) => new_path.extend(points.into_iter().skip(1)),
+            None => dropped += 1,
+        }
+        feeds.resize(new_path.len() - 1, SegmentFeed::Cut);
+        *path = new_path;
+        toolpath.set_segment_feeds(index, feeds);
+    }
+
+    Ok(dropped)
+}
+
+/// Restart a closed loop at the middle of its longest segment at cutting depth, so the
+/// leads meet a straight run instead of a corner or a tab.
+fn start_mid_segment(path: &mut Vec<(f64, f64, f64)>) {
+    let bottom = path.iter().map(|p| p.2).fold(f64::INFINITY, f64::min);
+    let vertices = &path[..path.len() - 1];
+    let count = vertices.len();
+    let longest = (0..count)
+        .filter(|&i| vertices[i].2 == bottom && vertices[(i + 1) % count].2 == bottom)
+        .max_by(|&a, &b| {
+            let length = |i: usize| {
+                let (p, q) = (vertices[i], vertices[(i + 1) % count]);
+                (q.0 - p.0).hypot(q.1 - p.1)
+            };
+            length(a).total_cmp(&length(b))
+        });
+    let Some(i) = longest else {
+        return;
+    };
+    let (a, b) = (vertices[i], vertices[(i + 1) % count]);
+    let mid = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0, a.2);
+
+    let mut rotated = Vec::with_capacity(count + 2);
+    rotated.push(mid);
+    rotated.extend_from_slice(&vertices[i + 1..]);
+    rotated.extend_from_slice(&vertices[..=i]);
+    rotated.push(mid);
+    *path = rotated;
+}
+
+/// Try the full lead size, halving it until the lead keeps clear of every obstacle.
+fn fit_lead(
+    leads: &LeadSettings,
+    clearance: f64,
+    obstacles: &[Vec<(f64, f64)>],
+    build: impl Fn(f64) -> Vec<(f64, f64, f64)>,
+) -> Option<Vec<(f64, f64, f64)>> {
+    let mut size = leads.length;
+    for _ in 0..=MAX_SHRINK_STEPS {
+        let points = build(size);
+        let clear = points
+            .iter()
+            .all(|p| obstacle_distance((p.0, p.1), obstacles) >= clearance);
+        if clear {
+            return Some(points);
+        }
+        size /= 2.0;
+    }
+    None
+}
+
+/// Lead geometry attached at `anchor`, travelling along `tangent` there.
+///
+/// Lead-ins end at the anchor and lead-outs start at it; both bulge towards `normal`.
+fn lead_points(
+    kind: LeadKind,
+    anchor: (f64, f64, f64),
+    tangent: (f64, f64),
+    normal: (f64, f64),
+    size: f64,
+    lead_in: bool,
+) -> Vec<(f64, f64, f64)> {
+    let direction = if lead_in { -1.0 } else { 1.0 };
+    let mut points: Vec<(f64, f64, f64)> = match kind {
+        LeadKind::Arc => {
+            let center = (anchor.0 + normal.0 * size, anchor.1 + normal.1 * size);
+            (0..=ARC_SEGMENTS)
+                .map(|i| {
+                    let phi = FRAC_PI_2 * i as f64 / ARC_SEGMENTS as f64;
+                    let (c, s) = (phi.cos(), phi.sin() * direction);
+                    (
+                        center.0 - normal.0 * size * c + tangent.0 * size * s,
+                        center.1 - normal.1 * size * c + tangent.1 * size * s,
+                        anchor.2,
+                    )
+                })
+                .collect()
+        }
+        LeadKind::Line => {
+            let scale = size / std::f64::consts::SQRT_2;
+            let far = (
+                anchor.0 + (normal.0 + tangent.0 * direction) * scale,
+                anchor.1 + (normal.1 + tangent.1 * direction) * scale,
+            );
+            (0..=ARC_SEGMENTS)
+                .map(|i| {
+                    let t = i as f64 / ARC_SEGMENTS as f64;
+                    (
+                        anchor.0 + (far.0 - anchor.0) * t,
+                        anchor.1 + (far.1 - anchor.1) * t,
+                        anchor.2,
+                    )
+                })
+                .collect()
+        }
+    };
+    // Points run outward from the anchor; a lead-in travels the other way.
+    if lead_in {
+        points.reverse();
+    }
+    points
+}
+
+/// Points from the loop start, continuing `distance` along it past the closing vertex.
+fn walk_from_start(path: &[(f64, f64, f64)], distance: f64) -> Vec<(f64, f64, f64)> {
+    let mut points = vec![path[0]];
+    let mut remaining = distance;
+    let mut i = 1;
+    while remaining > 1e-9 {
+        let (a, b) = (path[(i - 1) % path.len()], path[i % path.len()]);
+        let length = (b.0 - a.0).hypot(b.1 - a.1);
+        if length >= remaining {
+            let t = remaining / length;
+            points.push((
+                a.0 + (b.0 - a.0) * t,
+                a.1 + (b.1 - a.1) * t,
+                a.2 + (b.2 - a.2) * t,
+            ));
+            break;
+        }
+        if length > 0.0 {
+            points.push(b);
+        }
+        remaining -= length;
+        i += 1;
+        if i > path.len() * 4 {
+            break;
+        }
+    }
+    points
+}
+
+fn overlap_tangent(path: &[(f64, f64, f64)], overlap: &[(f64, f64, f64)]) -> Option<(f64, f64)> {
+    if overlap.len() < 2 {
+        return tangent_at(path, 0);
+    }
+    let (a, b) = (overlap[overlap.len() - 2], overlap[overlap.len() - 1]);
+    normalize((b.0 - a.0, b.1 - a.1))
+}
+
+fn tangent_at(path: &[(f64, f64, f64)], index: usize) -> Option<(f64, f64)> {
+    path[index..]
+        .windows(2)
+        .find_map(|w| normalize((w[1].0 - w[0].0, w[1].1 - w[0].1)))
+}
+
+fn normalize(v: (f64, f64)) -> Option<(f64, f64)> {
+    let length = v.0.hypot(v.1);
+    (length > 1e-9).then(|| (v.0 / length, v.1 / length))
+}
+
+fn is_closed(path: &[(f64, f64, f64)]) -> bool {
+    match (path.first(), path.last()) {
+        (Some(first), Some(last)) if path.len() > 2 => {
+            (first.0 - last.0).abs() < 1e-9 && (first.1 - last.1).abs() < 1e-9
+        }
+        _ => false,
+    }
+}
+
+fn signed_area(path: &[(f64, f64, f64)]) -> f64 {
+    path.windows(2)
+        .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+        .sum::<f64>()
+        / 2.0
+}
+
+fn obstacle_distance(point: (f64, f64), obstacles: &[Vec<(f64, f64)>]) -> f64 {
+    let mut best = f64::INFINITY;
+    for ring in obstacles {
+        for w in ring.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+            let len_sq = dx * dx + dy * dy;
+            let t = if len_sq > 0.0 {
+                (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            best = best.min((point.0 - (a.0 + dx * t)).hypot(point.1 - (a.1 + dy * t)));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::generate_profile_toolpath;
+    use crate::types::{FeedSettings, ToolType, Units};
+
+    fn tool() -> Tool {
+        Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 5.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    fn square(min: f64, max: f64) -> Vec<(f64, f64)> {
+        vec![(min, min), (max, min), (max, max), (min, max), (min, min)]
+    }
+
+    fn inside(point: (f64, f64), min: f64, max: f64) -> bool {
+        point.0 > min && point.0 < max && point.1 > min && point.1 < max
+    }
+
+    fn arc_leads() -> LeadSettings {
+        LeadSettings {
+            kind: LeadKind::Arc,
+            length: 4.0,
+            overlap: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_outside_leads_on_waste_side() {
+        let part = square(0.0, 50.0);
+        let mut toolpath =
+            generate_profile_toolpath(&part, &tool(), &CutSide::Outside, 3.0).unwrap();
+        let loop_len = toolpath.paths[0].len();
+        let dropped = apply_leads(
+            &mut toolpath,
+            &CutSide::Outside,
+            &arc_leads(),
+            &tool(),
+            std::slice::from_ref(&part),
+        )
+        .unwrap();
+        assert_eq!(dropped, 0);
+
+        let path = &toolpath.paths[0];
+        assert!(path.len() > loop_len + 2 * ARC_SEGMENTS);
+        for point in path {
+            assert!(!inside((point.0, point.1), 0.0, 50.0));
+            assert!(
+                obstacle_distance((point.0, point.1), std::slice::from_ref(&part)) >= 3.0 - 0.05
+            );
+            assert_eq!(point.2, -3.0);
+        }
+
+        // The lead starts away from the cut and leaves it again after the overlap.
+        let distance =
+            |p: &(f64, f64, f64)| obstacle_distance((p.0, p.1), std::slice::from_ref(&part));
+        assert!(distance(&path[0]) > 3.0 + 1.0);
+        assert!(distance(path.last().unwrap()) > 3.0 + 1.0);
+    }
+
+    #[test]
+    fn test_inside_leads_point_inward() {
+        let part = square(0.0, 50.0);
+        let mut toolpath =
+            generate_profile_toolpath(&part, &tool(), &CutSide::Inside, 3.0).unwrap();
+        let leads = LeadSettings {
+            kind: LeadKind::Line,
+            length: 5.0,
+            overlap: 1.0,
+        };
+        apply_leads(
+            &mut toolpath,
+            &CutSide::Inside,
+            &leads,
+            &tool(),
+            std::slice::from_ref(&part),
+        )
+        .unwrap();
+
+        let path = &toolpath.paths[0];
+        assert!(inside((path[0].0, path[0].1), 3.5, 46.5));
+        let last = path.last().unwrap();
+        assert!(inside((last.0, last.1), 3.5, 46.5));
+    }
+
+    #[test]
+    fn test_leads_shrink_near_neighbours() {
+        let part = square(0.0, 50.0);
+        // A surrounding frame 9mm away leaves only a few millimetres beside the cut.
+        let frame = square(-9.0, 59.0);
+        let obstacles = vec![part.clone(), frame];
+        let mut toolpath =
+            generate_profile_toolpath(&part, &tool(), &CutSide::Outside, 3.0).unwrap();
+        let leads = LeadSettings {
+            kind: LeadKind::Arc,
+            length: 20.0,
+            overlap: 0.0,
+        };
+        let dropped = apply_leads(
+            &mut toolpath,
+            &CutSide::Outside,
+            &leads,
+            &tool(),
+            &obstacles,
+        )
+        .unwrap();
+        assert_eq!(dropped, 0);
+
+        for point in &toolpath.paths[0] {
+            assert!(obstacle_distance((point.0, point.1), &obstacles) >= 3.0 - 0.05);
+        }
+        let start = toolpath.paths[0][0];
+        assert!(obstacle_distance((start.0, start.1), std::slice::from_ref(&part)) > 3.5);
+    }
+
+    #[test]
+    fn test_leads_require_a_cut_side() {
+        let mut toolpath = Toolpath::new(vec![]);
+        let result = apply_leads(&mut toolpath, &CutSide::OnLine, &arc_leads(), &tool(), &[]);
+        assert!(result.is_err());
+    }
+}
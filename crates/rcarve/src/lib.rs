@@ -1,31 +1,64 @@
+mod arcs;
+mod bore;
+mod drill;
+mod entry;
+mod estimate;
+mod export;
 mod geometry;
+mod leads;
+mod linking;
+mod ordering;
 mod pocket;
 mod postprocessor;
 mod profile;
 mod project;
+mod simulation;
+mod tabs;
 mod tool_library;
 mod toolpath_generation;
 mod types;
 mod vcarve;
+mod verification;
 
+pub use arcs::fit_arcs;
+pub use bore::generate_helical_bore_toolpath;
+pub use drill::{generate_drill_toolpath, DRILL_RETRACT_HEIGHT, PECK_CLEARANCE};
+pub use entry::apply_entry;
+pub use estimate::{
+    estimate_project, estimate_toolpath, JobEstimate, MachineProfile, MachiningEstimate,
+};
+pub use export::{export_board_gcode, export_gcode};
 pub use geometry::*;
-pub use pocket::generate_pocket_toolpath;
-pub use postprocessor::post_process_grbl;
-pub use profile::generate_profile_toolpath;
+pub use leads::apply_leads;
+pub use linking::link_paths;
+pub use ordering::{optimize_path_order, rapid_distance};
+pub use pocket::{
+    generate_pocket_toolpath, generate_pocket_toolpath_with_levels,
+    generate_raster_pocket_toolpath, generate_rest_pocket_toolpath,
+};
+pub use postprocessor::{
+    builtin_postprocessors, find_postprocessor, load_user_postprocessors, post_process,
+    post_process_grbl, resolve_postprocessor, user_posts_dir, GCodeWriter, Grbl, LinuxCnc, Mach3,
+    Marlin, PostProcessor, PostVariables, TemplatePost,
+};
+pub use profile::{generate_profile_toolpath, generate_profile_toolpath_with_levels};
 pub use project::*;
+pub use simulation::{simulate_project, Heightmap, HeightmapMesh};
+pub use tabs::apply_tabs;
 pub use tool_library::*;
 pub use toolpath_generation::*;
 pub use types::*;
 pub use vcarve::{
-    generate_vcarve_toolpath, generate_vcarve_toolpath_with_debug, CarvePolygon, PathType,
-    VCarveDebugOutput, VCarveResult,
+    generate_vcarve_toolpath, generate_vcarve_toolpath_with_debug, inlay_plug_polygons,
+    CarvePolygon, PathType, VCarveDebugOutput, VCarveResult,
 };
+pub use verification::{verify_toolpaths, Verification};
 
 /// High-level function: geometry → toolpath → G-code
 ///
 /// Routes operations to appropriate toolpath generators based on operation type.
-/// NOTE: This function is deprecated in favor of the new ShapeRegistry-based API.
-/// TODO: Update to work with ShapeRegistry and extract polygons from curves.
+/// NOTE: This function is deprecated in favor of the new ShapeRegistry-based API:
+/// build a [`Project`], call [`generate_toolpaths_for_operations`], then [`export_gcode`].
 #[allow(deprecated)]
 pub fn generate_toolpaths(
     _polygons: Vec<Vec<(f64, f64)>>,
@@ -0,0 +1,204 @@
+use crate::types::{SegmentFeed, Toolpath};
+use clipper2::{
+    difference, inflate, union, EndType, JoinType, Path, PathType, Polygon, Polygons, Vertex,
+};
+
+/// A link may skim this much uncut material at its edges, which covers the arc error of
+/// the swept areas (mm).
+const LINK_SLACK: f64 = 0.05;
+
+/// Uncut area a link may cross and still count as inside the machined area (mm²).
+const LINK_AREA_TOLERANCE: f64 = 1e-3;
+
+/// Join consecutive paths of `toolpath` with a feed move at cutting depth wherever the
+/// tool can get from one to the next through material it has already cut, returning the
+/// number of retracts saved.
+///
+/// Only paths on the same depth level are linked, and only when the next path starts no
+/// lower than the last one ended. The link must stay inside the area swept on that level
+/// so far, or where the tool would cut plunging at the next path's start, so the tool
+/// still retracts rather than cross uncut stock or an island. Drill cycles are left alone.
+pub fn link_paths(toolpath: &mut Toolpath, tool_radius: f64) -> usize {
+    if tool_radius <= LINK_SLACK || toolpath.drill.is_some() || toolpath.paths.len() < 2 {
+        return 0;
+    }
+
+    let feeds: Vec<_> = (0..toolpath.paths.len())
+        .map(|index| toolpath.path_segment_feeds(index))
+        .collect();
+    let keep_feeds = !toolpath.segment_feeds.is_empty();
+    let paths = std::mem::take(&mut toolpath.paths);
+    let levels = std::mem::take(&mut toolpath.path_levels);
+    toolpath.arcs.clear();
+    let mut linked: Vec<Vec<(f64, f64, f64)>> = Vec::with_capacity(paths.len());
+    let mut linked_feeds: Vec<Vec<SegmentFeed>> = Vec::with_capacity(paths.len());
+    let mut linked_levels = Vec::with_capacity(levels.len());
+    let mut machined = Polygons::new(Vec::new());
+    let mut links = 0;
+    for ((index, path), path_feeds) in paths.into_iter().enumerate().zip(feeds) {
+        let level = levels.get(index).copied();
+        let same_level = index > 0 && levels.get(index - 1).copied() == level;
+        if !same_level {
+            machined = Polygons::new(Vec::new());
+        }
+
+        // The link runs before the path, so it may only cross what is already cut.
+        let from = linked.last().and_then(|last| last.last()).copied();
+        let link = match (from, path.first()) {
+            (Some(from), Some(&to)) if same_level => {
+                to.2 >= from.2 - 1e-9 && is_machined(&machined, from, to, tool_radius)
+            }
+            _ => false,
+        };
+        machined = add(&machined, sweep(&path, tool_radius));
+
+        match linked.last_mut() {
+            Some(last) if link => {
+                last.extend(path);
+                let last_feeds = linked_feeds.last_mut().expect("feeds follow the paths");
+                last_feeds.push(SegmentFeed::Cut);
+                last_feeds.extend(path_feeds);
+                links += 1;
+            }
+            _ => {
+                linked.push(path);
+                linked_feeds.push(path_feeds);
+                if let Some(level) = level {
+                    linked_levels.push(level);
+                }
+            }
+        }
+    }
+
+    toolpath.paths = linked;
+    toolpath.path_levels = linked_levels;
+    if keep_feeds {
+        toolpath.segment_feeds = linked_feeds;
+    }
+    links
+}
+
+/// Whether the tool moving from `from` to `to` only cuts inside `machined`, or where it
+/// would cut plunging at `to` instead.
+fn is_machined(
+    machined: &Polygons,
+    from: (f64, f64, f64),
+    to: (f64, f64, f64),
+    tool_radius: f64,
+) -> bool {
+    let link = sweep(&[from, to], tool_radius);
+    let reachable = add(machined, sweep(&[to, to], tool_radius));
+    // Slivers thinner than the slack are only skimmed.
+    let uncut = inflate(
+        difference(link, reachable),
+        -LINK_SLACK,
+        JoinType::Round,
+        EndType::ClosedPolygon,
+        2.0,
+        0.01,
+    );
+    area(&uncut) < LINK_AREA_TOLERANCE
+}
+
+/// The union of `a` and `b`.
+fn add(a: &Polygons, b: Polygons) -> Polygons {
+    let mut area = a.polygons().clone();
+    area.extend(b.polygons().iter().cloned());
+    union(Polygons::new(area))
+}
+
+/// The area the tool covers following `path` in XY.
+fn sweep(path: &[(f64, f64, f64)], tool_radius: f64) -> Polygons {
+    let vertices = path.iter().map(|p| Vertex::new(p.0, p.1)).collect();
+    let polygon = Polygon::new(vec![Path::new(vertices, false)], PathType::Subject);
+    inflate(
+        Polygons::new(vec![polygon]),
+        tool_radius,
+        JoinType::Round,
+        EndType::OpenRound,
+        2.0,
+        0.01,
+    )
+}
+
+/// Net area of `polygons`; holes wind the other way and subtract.
+fn area(polygons: &Polygons) -> f64 {
+    let mut total = 0.0;
+    for polygon in polygons.polygons() {
+        for path in polygon.paths() {
+            let points = path.vertices();
+            for (index, a) in points.iter().enumerate() {
+                let b = points[(index + 1) % points.len()];
+                total += a.x() * b.y() - b.x() * a.y();
+            }
+        }
+    }
+    total.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(half: f64, z: f64) -> Vec<(f64, f64, f64)> {
+        vec![
+            (-half, -half, z),
+            (half, -half, z),
+            (half, half, z),
+            (-half, half, z),
+            (-half, -half, z),
+        ]
+    }
+
+    #[test]
+    fn test_link_paths_stays_down_between_adjacent_rings() {
+        // Concentric rings a stepover apart, cut outside in: each starts where the one
+        // before has already cleared.
+        let mut toolpath = Toolpath::new(vec![ring(6.0, -1.0), ring(4.0, -1.0), ring(2.0, -1.0)]);
+        toolpath.set_segment_feeds(1, vec![SegmentFeed::Ramp; 4]);
+        assert_eq!(link_paths(&mut toolpath, 1.5), 2);
+        assert_eq!(toolpath.paths.len(), 1);
+        assert_eq!(toolpath.paths[0].len(), 15);
+        // Links cut at the feed rate, and the moves they join keep their own rates.
+        let feeds = &toolpath.segment_feeds[0];
+        assert_eq!(feeds.len(), 14);
+        assert_eq!(feeds[4], SegmentFeed::Cut);
+        assert!(feeds[5..9].iter().all(|feed| *feed == SegmentFeed::Ramp));
+        assert_eq!(feeds[9], SegmentFeed::Cut);
+    }
+
+    #[test]
+    fn test_link_paths_retracts_over_stock_the_next_path_removes() {
+        // Cut inside out, the way to the next ring crosses the band that ring is about to
+        // clear, so the link would slot through it.
+        let mut toolpath = Toolpath::new(vec![ring(2.0, -1.0), ring(4.0, -1.0), ring(6.0, -1.0)]);
+        assert_eq!(link_paths(&mut toolpath, 1.5), 0);
+        assert_eq!(toolpath.paths.len(), 3);
+    }
+
+    #[test]
+    fn test_link_paths_retracts_over_stock_islands_and_levels() {
+        // Rings too far apart to link without cutting the stock between them.
+        let mut apart = Toolpath::new(vec![ring(2.0, -1.0), ring(10.0, -1.0)]);
+        assert_eq!(link_paths(&mut apart, 1.5), 0);
+        assert_eq!(apart.paths.len(), 2);
+
+        // Two pockets either side of an island.
+        let left: Vec<_> = ring(2.0, -1.0)
+            .iter()
+            .map(|p| (p.0 - 10.0, p.1, p.2))
+            .collect();
+        let right: Vec<_> = ring(2.0, -1.0)
+            .iter()
+            .map(|p| (p.0 + 10.0, p.1, p.2))
+            .collect();
+        let mut island = Toolpath::new(vec![left, right]);
+        assert_eq!(link_paths(&mut island, 1.5), 0);
+
+        // The same rings on separate levels, and a link that would plunge.
+        let mut levels = Toolpath::new(vec![ring(2.0, -1.0), ring(4.0, -1.0), ring(4.0, -2.0)]);
+        levels.path_levels = vec![0, 1, 1];
+        assert_eq!(link_paths(&mut levels, 1.5), 0);
+        assert_eq!(levels.path_levels, vec![0, 1, 1]);
+    }
+}
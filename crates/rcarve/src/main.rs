@@ -1,199 +1,706 @@
+use anyhow::{anyhow, Context, Result};
 use rcarve::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use ulid::Ulid;
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let operation = args.get(1).map(|s| s.as_str()).unwrap_or("profile");
-
-    match operation {
-        "profile" => demo_profile(),
-        "pocket" => demo_pocket(),
-        "pocket-island" => demo_pocket_with_island(),
-        "vcarve" => demo_vcarve(),
-        _ => {
-            println!("Usage: rcarve [profile|pocket|pocket-island|vcarve]");
-            println!("  profile        - Generate profile toolpath (default)");
-            println!("  pocket         - Generate simple pocket toolpath");
-            println!("  pocket-island  - Generate pocket toolpath with island");
-            println!("  vcarve         - Generate V-carve toolpath (Phase 3)");
-        }
-    }
-}
+const USAGE: &str = "\
+Usage: rcarve <command> [arguments]
 
-fn demo_profile() {
-    println!("rcarve Phase 1 - Profile Toolpath Generator");
-    println!("============================================\n");
+Commands:
+  new <project> --width W --height H --thickness T [--name NAME] [--units mm|in]
+      Create an empty project file with the given stock size. The project's units
+      (default mm) apply to every length given to it on the command line.
+  import <project> <file.svg>
+      Import an SVG into the project.
+  add <project> <profile|pocket|vcarve|inlay|drill|bore> --tool N [options]
+      Append an operation. Options:
+        --depth D             cut depth (required for all but vcarve; the pocket depth of an inlay)
+        --side S              profile side: outside (default), inside or online;
+                              bore side: inside (default), outside or online
+        --finish-pass D       depth left for a final pass at full depth
+        --raster ANGLE        pocket with zig-zag passes at ANGLE degrees, then a boundary pass
+        --rest-from N         pocket only what larger tool N left behind
+        --clearance-tool N    v-carve or inlay clearance tool
+        --start-depth D       inlay plug: depth where its walls begin (required for inlay)
+        --flat-depth D        inlay plug: depth of the flat around it (required for inlay)
+        --glue-gap D          inlay plug: shrink it by D on every side for glue
+        --peck D              drill in pecks of D, retracting between them
+        --dwell S             pause S seconds at the bottom of each drilled hole
+        --pitch P             bore helix descent per turn (required for bore)
+        --curves ID,ID,...    target curves (default: the most recent import)
+        --import ID|LABEL     target every curve of an import
+  generate <project> [--tools LIBRARY]
+      Generate toolpaths for every operation and save them in the project.
+  gcode <project> [--output FILE] [--tools LIBRARY] [--post NAME] [--order ORDER]
+        [--units mm|in] [--board stock|plug]
+      Write G-code for the project (default post: grbl, default output: <project>.nc).
+      An inlay plug is cut from its own board, so a project with one is written as two
+      programs: --board stock, then --board plug (default output: <project>-plug.nc).
+      ORDER is inside-out (default: features inside a profile are cut before it) or
+      listed (operations exactly as added). The program is in the project's units
+      (G21 for mm, G20 for inches) unless --units says otherwise.
+  estimate <project> [--rapid-rate R] [--acceleration A] [--tool-change S] [--hourly-rate C]
+      Estimate the machining time of the generated toolpaths, per operation and in total.
+      The options update the project's machine profile: rapid rate in mm/min, acceleration
+      in mm/s², seconds per tool change and machine cost per hour.
+  simulate <project> [--tools LIBRARY] [--resolution R] [--output FILE.obj]
+        [--board stock|plug]
+      Cut the generated toolpaths from a virtual stock on an R mm grid (default 0.5) and
+      report the volume removed; with --output, save the cut surface as an OBJ mesh.
+      --board plug simulates the inlay plug's board instead of the stock.
+  posts
+      List the available postprocessors.
 
-    // Hardcoded test case matching integration test
-    let square = vec![
-        (0.0, 0.0),
-        (100.0, 0.0),
-        (100.0, 100.0),
-        (0.0, 100.0),
-        (0.0, 0.0),
-    ];
+Tool numbers are 1-based positions in the tool library (default ~/.rcarve/tools/library.json).
+Every command prints a JSON summary on success. Failures print a JSON error to stderr and
+exit with status 1, or 2 for usage errors.";
 
-    let tool = Tool {
-        name: "6mm Endmill".to_string(),
-        diameter: 6.0,
-        stepover: 0.4,
-        pass_depth: 5.0,
-        tool_type: ToolType::Endmill { diameter: 6.0 },
-    };
+/// A usage mistake, reported with exit status 2.
+#[derive(Debug)]
+struct UsageError(String);
 
-    match generate_profile_toolpath(&square, &tool, &CutSide::Outside, 5.0) {
-        Ok(toolpath) => {
-            let gcode = post_process_grbl(&toolpath);
-            println!("Generated {} path(s)", toolpath.paths.len());
-            println!("\nG-code:\n");
-            for line in gcode.lines {
-                println!("{}", line);
-            }
-        }
-        Err(e) => eprintln!("Error: {}", e),
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
-fn demo_pocket() {
-    println!("rcarve Phase 2 - Pocket Toolpath Generator");
-    println!("==========================================\n");
+impl std::error::Error for UsageError {}
 
-    // Simple square pocket: 100x100mm
-    let outer = vec![
-        (0.0, 0.0),
-        (100.0, 0.0),
-        (100.0, 100.0),
-        (0.0, 100.0),
-        (0.0, 0.0),
-    ];
+fn usage_error(message: impl Into<String>) -> anyhow::Error {
+    UsageError(message.into()).into()
+}
 
-    let tool = Tool {
-        name: "6mm Endmill".to_string(),
-        diameter: 6.0,
-        stepover: 0.4, // 40% = 2.4mm stepover
-        pass_depth: 5.0,
-        tool_type: ToolType::Endmill { diameter: 6.0 },
-    };
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || matches!(args[0].as_str(), "help" | "-h" | "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
 
-    match generate_pocket_toolpath(&outer, &[], &tool, 5.0) {
-        Ok(toolpath) => {
-            let gcode = post_process_grbl(&toolpath);
+    match run(&args[0], &args[1..]) {
+        Ok(summary) => {
             println!(
-                "Generated {} concentric path(s) for pocket",
-                toolpath.paths.len()
+                "{}",
+                serde_json::to_string_pretty(&summary).unwrap_or_default()
             );
-            println!("\nG-code:\n");
-            for line in gcode.lines {
-                println!("{}", line);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            let usage = err.is::<UsageError>();
+            let error = json!({ "error": format!("{err:#}") });
+            eprintln!("{error}");
+            if usage {
+                eprintln!("\n{USAGE}");
+                ExitCode::from(2)
+            } else {
+                ExitCode::FAILURE
             }
         }
-        Err(e) => eprintln!("Error: {}", e),
-    }
-}
-
-fn demo_pocket_with_island() {
-    println!("rcarve Phase 2 - Pocket Toolpath Generator (with Island)");
-    println!("=======================================================\n");
-
-    // Square pocket with a square island in the center
-    let outer = vec![
-        (0.0, 0.0),
-        (100.0, 0.0),
-        (100.0, 100.0),
-        (0.0, 100.0),
-        (0.0, 0.0),
-    ];
-
-    // Square island in center (40x40mm)
-    let island = vec![
-        (30.0, 30.0),
-        (70.0, 30.0),
-        (70.0, 70.0),
-        (30.0, 70.0),
-        (30.0, 30.0),
-    ];
-
-    let tool = Tool {
-        name: "6mm Endmill".to_string(),
-        diameter: 6.0,
-        stepover: 0.4,
-        pass_depth: 5.0,
-        tool_type: ToolType::Endmill { diameter: 6.0 },
-    };
+    }
+}
 
-    match generate_pocket_toolpath(&outer, &[island], &tool, 5.0) {
-        Ok(toolpath) => {
-            let gcode = post_process_grbl(&toolpath);
-            println!(
-                "Generated {} path(s) for pocket with island",
-                toolpath.paths.len()
-            );
-            println!("\nG-code:\n");
-            for line in gcode.lines {
-                println!("{}", line);
+fn run(command: &str, args: &[String]) -> Result<Value> {
+    match command {
+        "new" => new_project(&Args::parse(
+            args,
+            &["width", "height", "thickness", "name", "units"],
+        )?),
+        "import" => import_svg(&Args::parse(args, &[])?),
+        "add" => add_operation(&Args::parse(
+            args,
+            &[
+                "tool",
+                "depth",
+                "side",
+                "finish-pass",
+                "raster",
+                "rest-from",
+                "clearance-tool",
+                "start-depth",
+                "flat-depth",
+                "glue-gap",
+                "peck",
+                "dwell",
+                "pitch",
+                "curves",
+                "import",
+            ],
+        )?),
+        "generate" => generate(&Args::parse(args, &["tools"])?),
+        "gcode" => write_gcode(&Args::parse(
+            args,
+            &["output", "tools", "post", "order", "units", "board"],
+        )?),
+        "estimate" => estimate(&Args::parse(
+            args,
+            &["rapid-rate", "acceleration", "tool-change", "hourly-rate"],
+        )?),
+        "simulate" => simulate(&Args::parse(
+            args,
+            &["tools", "resolution", "output", "board"],
+        )?),
+        "posts" => list_posts(&Args::parse(args, &[])?),
+        other => Err(usage_error(format!("unknown command `{other}`"))),
+    }
+}
+
+/// Positional arguments plus `--key value` options.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                positional.push(arg.clone());
+                continue;
+            };
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| usage_error(format!("`--{option}` needs a value")))?;
+                    (option.to_string(), value.clone())
+                }
+            };
+            if !allowed.contains(&key.as_str()) {
+                return Err(usage_error(format!("unknown option `--{key}`")));
+            }
+            if options.insert(key.clone(), value).is_some() {
+                return Err(usage_error(format!("`--{key}` is given more than once")));
             }
         }
-        Err(e) => eprintln!("Error: {}", e),
-    }
-}
-
-fn demo_vcarve() {
-    println!("rcarve Phase 3 - V-Carve Toolpath Generator");
-    println!("==========================================\n");
-
-    // Letter-like shape for V-carving (simplified "A" shape)
-    let shape = vec![
-        (0.0, 0.0),   // Bottom left
-        (30.0, 0.0),  // Bottom right
-        (25.0, 20.0), // Right side of A
-        (20.0, 15.0), // Right inner
-        (10.0, 15.0), // Left inner
-        (5.0, 20.0),  // Left side of A
-        (0.0, 0.0),   // Close
-    ];
-
-    let tool = Tool {
-        name: "60deg V-bit".to_string(),
-        diameter: 0.0,
-        stepover: 0.0,
-        pass_depth: 0.0,
-        tool_type: ToolType::VBit {
-            angle_degrees: 60.0,
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| usage_error(format!("missing <{name}>")))
+    }
+
+    fn expect_positionals(&self, count: usize) -> Result<()> {
+        match self.positional.get(count) {
+            Some(extra) => Err(usage_error(format!("unexpected argument `{extra}`"))),
+            None => Ok(()),
+        }
+    }
+
+    fn option(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    fn number(&self, key: &str) -> Result<Option<f64>> {
+        self.option(key)
+            .map(|value| {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.is_finite() && *n > 0.0)
+                    .ok_or_else(|| usage_error(format!("`--{key}` must be a positive number")))
+            })
+            .transpose()
+    }
+
+    fn required_number(&self, key: &str) -> Result<f64> {
+        self.number(key)?
+            .ok_or_else(|| usage_error(format!("`--{key}` is required")))
+    }
+
+    /// A positive length given in `units`, returned in millimetres.
+    fn length(&self, key: &str, units: Units) -> Result<Option<f64>> {
+        Ok(self.number(key)?.map(|value| units.to_mm(value)))
+    }
+
+    fn required_length(&self, key: &str, units: Units) -> Result<f64> {
+        Ok(units.to_mm(self.required_number(key)?))
+    }
+
+    fn units(&self) -> Result<Option<Units>> {
+        self.option("units")
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| usage_error("`--units` must be mm or in"))
+            })
+            .transpose()
+    }
+
+    fn board(&self) -> Result<Option<Board>> {
+        self.option("board")
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| usage_error("`--board` must be stock or plug"))
+            })
+            .transpose()
+    }
+
+    /// A 1-based tool number, returned as a library index.
+    fn tool_index(&self, key: &str) -> Result<Option<usize>> {
+        self.option(key)
+            .map(|value| match value.parse::<usize>() {
+                Ok(number) if number > 0 => Ok(number - 1),
+                _ => Err(usage_error(format!(
+                    "`--{key}` must be a tool number starting at 1"
+                ))),
+            })
+            .transpose()
+    }
+
+    fn tools(&self) -> Result<ToolLibrary> {
+        let path = match self.option("tools") {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                if !path.exists() {
+                    return Err(anyhow!("tool library {} does not exist", path.display()));
+                }
+                path
+            }
+            None => ToolLibrary::default_library_path()?,
+        };
+        ToolLibrary::load_from_path(path)
+    }
+}
+
+fn load_project(path: &str) -> Result<Project> {
+    let mut project = Project::load_from_path(path)?;
+    project.sync_operation_states();
+    Ok(project)
+}
+
+fn new_project(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    args.expect_positionals(1)?;
+    if Path::new(path).exists() {
+        return Err(anyhow!("{path} already exists"));
+    }
+
+    let units = args.units()?.unwrap_or_default();
+    let stock = StockSpec::with_units(
+        args.required_number("width")?,
+        args.required_number("height")?,
+        args.required_number("thickness")?,
+        units,
+    );
+    let name = match args.option("name") {
+        Some(name) => name.to_string(),
+        None => Path::new(path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Untitled")
+            .to_string(),
+    };
+
+    let mut project = Project::new(name, stock);
+    project.units = units;
+    project.save_to_path(path)?;
+    Ok(json!({ "project": path, "summary": project.summary() }))
+}
+
+fn import_svg(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    let svg = args.positional(1, "file.svg")?;
+    args.expect_positionals(2)?;
+
+    let mut project = load_project(path)?;
+    let import = project.import_svg(svg)?;
+    project.save_to_path(path)?;
+
+    let curves: Vec<String> = import.curve_ids.iter().map(CurveId::to_string).collect();
+    Ok(json!({
+        "project": path,
+        "import": import.id.to_string(),
+        "label": import.label,
+        "curves": curves,
+        "summary": project.summary(),
+    }))
+}
+
+/// The `--side` option of a profile or bore, or `default` when it is absent.
+fn cut_side(args: &Args, default: &str) -> Result<CutSide> {
+    match args.option("side").unwrap_or(default) {
+        "outside" => Ok(CutSide::Outside),
+        "inside" => Ok(CutSide::Inside),
+        "online" | "on-line" => Ok(CutSide::OnLine),
+        other => Err(usage_error(format!("unknown side `{other}`"))),
+    }
+}
+
+fn add_operation(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    let kind = args.positional(1, "profile|pocket|vcarve|inlay|drill|bore")?;
+    args.expect_positionals(2)?;
+
+    let mut project = load_project(path)?;
+    let tool_index = args
+        .tool_index("tool")?
+        .ok_or_else(|| usage_error("`--tool` is required"))?;
+    let curves = target_curves(&project, args)?;
+    let units = project.units;
+    let finish_pass_depth = args.length("finish-pass", units)?;
+
+    let operation = match kind {
+        "profile" => Operation::Profile {
+            target_depth: args.required_length("depth", units)?,
+            cut_side: cut_side(args, "outside")?,
+            tool_index,
+            targets: OperationTarget::Curves(curves),
+            finish_pass_depth,
+            tabs: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
+        },
+        "pocket" => Operation::Pocket {
+            target_depth: args.required_length("depth", units)?,
+            tool_index,
+            target: OperationTarget::Curves(curves),
+            finish_pass_depth,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            feeds: FeedSettings::default(),
+            strategy: match args.option("raster") {
+                Some(angle) => PocketStrategy::Raster {
+                    angle_deg: angle
+                        .parse()
+                        .map_err(|_| usage_error("`--raster` must be an angle in degrees"))?,
+                    boundary_pass: true,
+                },
+                None => PocketStrategy::Offset,
+            },
+            rest_tool_index: args.tool_index("rest-from")?,
+        },
+        "vcarve" => Operation::VCarve {
+            config: VCarveConfig {
+                flat_depth: args.length("depth", units)?,
+                ..VCarveConfig::default()
+            },
+            tool_index,
+            targets: OperationTarget::Curves(curves),
+            clearance_tool_index: args.tool_index("clearance-tool")?,
+            feeds: FeedSettings::default(),
+            inlay_plug: None,
+        },
+        "inlay" => Operation::VCarve {
+            config: VCarveConfig {
+                flat_depth: Some(args.required_length("depth", units)?),
+                ..VCarveConfig::default()
+            },
+            tool_index,
+            targets: OperationTarget::Curves(curves),
+            clearance_tool_index: args.tool_index("clearance-tool")?,
+            feeds: FeedSettings::default(),
+            inlay_plug: None,
         },
+        "drill" => Operation::Drill {
+            target_depth: args.required_length("depth", units)?,
+            tool_index,
+            targets: OperationTarget::Curves(curves),
+            peck_depth: args.length("peck", units)?,
+            dwell: args.number("dwell")?,
+            feeds: FeedSettings::default(),
+        },
+        "bore" => Operation::Bore {
+            target_depth: args.required_length("depth", units)?,
+            tool_index,
+            targets: OperationTarget::Curves(curves),
+            cut_side: cut_side(args, "inside")?,
+            pitch: args.required_length("pitch", units)?,
+            direction: CutDirection::Climb,
+            feeds: FeedSettings::default(),
+        },
+        other => return Err(usage_error(format!("unknown operation `{other}`"))),
     };
+    if !matches!(kind, "profile" | "bore") && args.option("side").is_some() {
+        return Err(usage_error("`--side` only applies to profiles and bores"));
+    }
+    if kind != "pocket" && args.option("raster").is_some() {
+        return Err(usage_error("`--raster` only applies to pockets"));
+    }
+    if kind != "pocket" && args.option("rest-from").is_some() {
+        return Err(usage_error("`--rest-from` only applies to pockets"));
+    }
+    if !matches!(kind, "vcarve" | "inlay") && args.option("clearance-tool").is_some() {
+        return Err(usage_error(
+            "`--clearance-tool` only applies to v-carves and inlays",
+        ));
+    }
+    for option in ["start-depth", "flat-depth", "glue-gap"] {
+        if kind != "inlay" && args.option(option).is_some() {
+            return Err(usage_error(format!("`--{option}` only applies to inlays")));
+        }
+    }
+    for option in ["peck", "dwell"] {
+        if kind != "drill" && args.option(option).is_some() {
+            return Err(usage_error(format!("`--{option}` only applies to drills")));
+        }
+    }
+    if kind != "bore" && args.option("pitch").is_some() {
+        return Err(usage_error("`--pitch` only applies to bores"));
+    }
+
+    let (index, plug_index) = if kind == "inlay" {
+        let plug = InlayPlug {
+            start_depth: args.required_length("start-depth", units)?,
+            glue_gap: args.length("glue-gap", units)?.unwrap_or(0.0),
+        };
+        let flat_depth = args.required_length("flat-depth", units)?;
+        let (pocket, plug) = project.add_inlay(operation, plug, flat_depth)?;
+        (pocket, Some(plug))
+    } else {
+        (project.add_operation(operation), None)
+    };
+    project.save_to_path(path)?;
+    let mut result = json!({
+        "project": path,
+        "operation": index + 1,
+        "kind": kind,
+        "summary": project.summary(),
+    });
+    if let Some(plug_index) = plug_index {
+        result["plug_operation"] = json!(plug_index + 1);
+    }
+    Ok(result)
+}
 
-    let polygons = vec![CarvePolygon {
-        outer: shape,
-        holes: Vec::new(),
-    }];
-
-    match generate_vcarve_toolpath(&polygons, &tool, Some(5.0)) {
-        Ok(path_types) => {
-            // Convert PathType to Toolpath
-            let mut paths_3d = Vec::new();
-            for pt in path_types {
-                match pt {
-                    PathType::Crease { start, end } => {
-                        paths_3d.push(vec![
-                            (start[0], start[1], start[2]),
-                            (end[0], end[1], end[2]),
-                        ]);
-                    }
-                    PathType::PocketBoundary { path, depth } => {
-                        let z = -depth.abs();
-                        let path_3d = path.into_iter().map(|p| (p[0], p[1], z)).collect();
-                        paths_3d.push(path_3d);
-                    }
+/// Curves named by `--curves` or `--import`, or every curve of the most recent import.
+fn target_curves(project: &Project, args: &Args) -> Result<Vec<CurveId>> {
+    let curves = match (args.option("curves"), args.option("import")) {
+        (Some(_), Some(_)) => {
+            return Err(usage_error("use either `--curves` or `--import`, not both"));
+        }
+        (Some(list), None) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                let curve = Ulid::from_string(id)
+                    .map(CurveId::from_ulid)
+                    .map_err(|_| usage_error(format!("`{id}` is not a curve id")))?;
+                if project.shapes.get_curve(&curve).is_none() {
+                    return Err(anyhow!("curve {id} is not in the project"));
                 }
-            }
-            let toolpath = Toolpath { paths: paths_3d };
-            
-            let gcode = post_process_grbl(&toolpath);
-            println!("Generated {} path(s) for V-carve", toolpath.paths.len());
-            println!("\nG-code:\n");
-            for line in gcode.lines {
-                println!("{}", line);
-            }
+                Ok(curve)
+            })
+            .collect::<Result<Vec<_>>>()?,
+        (None, Some(name)) => project
+            .imported_svgs
+            .iter()
+            .find(|import| import.id.to_string() == name || import.label == name)
+            .map(|import| import.curve_ids.clone())
+            .ok_or_else(|| anyhow!("no import named `{name}`"))?,
+        (None, None) => project
+            .imported_svgs
+            .last()
+            .map(|import| import.curve_ids.clone())
+            .ok_or_else(|| anyhow!("project has no imports; import an SVG or pass `--curves`"))?,
+    };
+
+    if curves.is_empty() {
+        return Err(anyhow!("the operation has no target curves"));
+    }
+    Ok(curves)
+}
+
+fn generate(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    args.expect_positionals(1)?;
+
+    let mut project = load_project(path)?;
+    let tools = args.tools()?;
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    project.save_to_path(path)?;
+
+    let failed: Vec<usize> = reports
+        .iter()
+        .filter(|report| report.error.is_some())
+        .map(|report| report.operation_index + 1)
+        .collect();
+    let operations: Vec<Value> = reports
+        .iter()
+        .map(|report| {
+            json!({
+                "operation": report.operation_index + 1,
+                "ok": report.error.is_none(),
+                "warnings": report.warnings,
+                "rapid_distance_saved": report.rapid_distance_saved,
+                "retracts_saved": report.retracts_saved,
+                "error": report.error,
+            })
+        })
+        .collect();
+    let summary = json!({ "project": path, "operations": operations });
+
+    if failed.is_empty() {
+        Ok(summary)
+    } else {
+        // The report still goes to stdout so callers can see which operations failed.
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        Err(anyhow!(
+            "toolpath generation failed for operation(s) {failed:?}"
+        ))
+    }
+}
+
+fn write_gcode(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    args.expect_positionals(1)?;
+
+    let mut project = load_project(path)?;
+    let tools = args.tools()?;
+    if let Some(order) = args.option("order") {
+        project.cut_order = match order {
+            "inside-out" => CutOrder::InsideOut,
+            "listed" => CutOrder::Listed,
+            other => return Err(usage_error(format!("unknown cut order `{other}`"))),
+        };
+    }
+    if let Some(units) = args.units()? {
+        project.units = units;
+    }
+    let post_name = args.option("post").unwrap_or("grbl");
+    let user_dir = user_posts_dir().ok();
+    let post = resolve_postprocessor(post_name, user_dir.as_deref())?;
+
+    let board = args.board()?;
+    let gcode = match board {
+        Some(board) => export_board_gcode(&project, &tools, post.as_ref(), board)?,
+        None => export_gcode(&project, &tools, post.as_ref())?,
+    };
+    let output = match args.option("output") {
+        Some(output) => PathBuf::from(output),
+        None if board == Some(Board::InlayPlug) => {
+            let stem = Path::new(path).file_stem().unwrap_or_default();
+            let mut name = stem.to_os_string();
+            name.push("-plug.nc");
+            Path::new(path).with_file_name(name)
+        }
+        None => Path::new(path).with_extension("nc"),
+    };
+    let mut contents = gcode.lines.join("\n");
+    contents.push('\n');
+    fs::write(&output, contents).with_context(|| format!("write {}", output.display()))?;
+
+    Ok(json!({
+        "project": path,
+        "output": output.display().to_string(),
+        "postprocessor": post.name(),
+        "units": project.units,
+        "board": board.unwrap_or_default(),
+        "operations": project.operations.len(),
+        "order": operation_cut_order(&project)
+            .iter()
+            .map(|index| index + 1)
+            .collect::<Vec<_>>(),
+        "lines": gcode.lines.len(),
+    }))
+}
+
+fn estimate(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    args.expect_positionals(1)?;
+
+    let mut project = load_project(path)?;
+    let machine = &mut project.machine;
+    let mut changed = false;
+    for (key, value) in [
+        ("rapid-rate", &mut machine.rapid_rate),
+        ("acceleration", &mut machine.acceleration),
+        ("tool-change", &mut machine.tool_change_seconds),
+    ] {
+        if let Some(number) = args.number(key)? {
+            *value = number;
+            changed = true;
+        }
+    }
+    if let Some(rate) = args.number("hourly-rate")? {
+        machine.hourly_rate = Some(rate);
+        changed = true;
+    }
+    if changed {
+        project.save_to_path(path)?;
+    }
+
+    let estimate = estimate_project(&project);
+    let operations: Vec<Value> = estimate
+        .operations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, operation)| {
+            let operation = operation.as_ref()?;
+            Some(json!({ "operation": index + 1, "estimate": operation }))
+        })
+        .collect();
+    Ok(json!({
+        "project": path,
+        "machine": project.machine,
+        "operations": operations,
+        "total": estimate.total,
+        "cost": estimate.cost,
+    }))
+}
+
+fn simulate(args: &Args) -> Result<Value> {
+    let path = args.positional(0, "project")?;
+    args.expect_positionals(1)?;
+
+    let project = load_project(path)?;
+    let tools = args.tools()?;
+    let resolution = args.number("resolution")?.unwrap_or(0.5);
+    let board = args.board()?.unwrap_or_default();
+    let heightmap = simulate_project(&project, &tools, resolution, board)?;
+    let output = args.option("output").map(PathBuf::from);
+    if let Some(output) = &output {
+        fs::write(output, heightmap.to_mesh().to_obj())
+            .with_context(|| format!("write {}", output.display()))?;
+    }
+
+    let lowest = heightmap.data.iter().copied().fold(heightmap.top, f64::min);
+    Ok(json!({
+        "project": path,
+        "resolution": resolution,
+        "board": board,
+        "grid": [heightmap.width_steps, heightmap.height_steps],
+        "removed_volume": heightmap.removed_volume(),
+        "deepest_cut": heightmap.top - lowest,
+        "output": output.map(|output| output.display().to_string()),
+    }))
+}
+
+fn list_posts(args: &Args) -> Result<Value> {
+    args.expect_positionals(0)?;
+
+    let mut posts: Vec<Value> = builtin_postprocessors()
+        .iter()
+        .map(|post| json!({ "name": post.name(), "source": "builtin" }))
+        .collect();
+    if let Some(dir) = user_posts_dir().ok().filter(|dir| dir.is_dir()) {
+        for (path, post) in load_user_postprocessors(&dir)? {
+            posts.push(match post {
+                Ok(post) => json!({ "name": post.name(), "source": path.display().to_string() }),
+                Err(err) => json!({
+                    "source": path.display().to_string(),
+                    "error": format!("{err:#}"),
+                }),
+            });
         }
-        Err(e) => eprintln!("Error: {}", e),
     }
+    Ok(json!({ "postprocessors": posts }))
 }
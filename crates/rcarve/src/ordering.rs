@@ -0,0 +1,381 @@
+use crate::geometry::offset::is_point_inside;
+use crate::types::Toolpath;
+
+/// Path ends closer than this are treated as meeting, so the path is cut the same
+/// distance from either end (mm).
+const CLOSED_TOLERANCE: f64 = 1e-6;
+
+/// Runs longer than this are only ordered nearest-neighbour; 2-opt is quadratic.
+const MAX_TWO_OPT_PATHS: usize = 2000;
+
+/// Passes of 2-opt refinement over a run before settling for what it has.
+const MAX_TWO_OPT_SWEEPS: usize = 8;
+
+type Point = (f64, f64);
+
+/// Reorder the paths of `toolpath` to shorten the rapid moves between them, returning
+/// the rapid distance saved (mm, in XY).
+///
+/// Paths are ordered nearest-neighbour, then refined with 2-opt. They never move across
+/// depth levels, and a path nested inside another keeps its place before or after it, so
+/// inner contours still come before outer ones and islands before the final profile.
+/// With `allow_reverse`, open paths may also be cut from their far end; closed paths keep
+/// their direction either way.
+pub fn optimize_path_order(toolpath: &mut Toolpath, allow_reverse: bool) -> f64 {
+    let before = rapid_distance(toolpath);
+    if toolpath.paths.len() < 3 {
+        return 0.0;
+    }
+
+    let feeds: Vec<_> = (0..toolpath.paths.len())
+        .map(|index| toolpath.path_segment_feeds(index))
+        .collect();
+    let paths = std::mem::take(&mut toolpath.paths);
+    toolpath.arcs.clear();
+    let mut ordered = Vec::with_capacity(paths.len());
+    let mut ordered_feeds = Vec::with_capacity(paths.len());
+    let mut entry = None;
+    let mut start = 0;
+    while start < paths.len() {
+        let level = toolpath.path_levels.get(start);
+        let end = (start..paths.len())
+            .find(|&index| toolpath.path_levels.get(index) != level)
+            .unwrap_or(paths.len());
+        let run = order_run(&paths[start..end], entry, allow_reverse);
+        for (index, reversed) in run {
+            let mut path = paths[start + index].clone();
+            let mut path_feeds = feeds[start + index].clone();
+            if reversed {
+                path.reverse();
+                path_feeds.reverse();
+            }
+            ordered.push(path);
+            ordered_feeds.push(path_feeds);
+        }
+        entry = ordered
+            .last()
+            .and_then(|path| path.last())
+            .map(|p| (p.0, p.1));
+        start = end;
+    }
+
+    let original = std::mem::replace(&mut toolpath.paths, ordered);
+    let after = rapid_distance(toolpath);
+    if after > before {
+        toolpath.paths = original;
+        return 0.0;
+    }
+    if !toolpath.segment_feeds.is_empty() {
+        toolpath.segment_feeds = ordered_feeds;
+    }
+    before - after
+}
+
+/// Total XY distance travelled at safe height between consecutive paths (mm).
+pub fn rapid_distance(toolpath: &Toolpath) -> f64 {
+    let ends: Vec<(Point, Point)> = toolpath
+        .paths
+        .iter()
+        .filter_map(|path| {
+            let (first, last) = (path.first()?, path.last()?);
+            Some(((first.0, first.1), (last.0, last.1)))
+        })
+        .collect();
+    ends.windows(2)
+        .map(|pair| distance(pair[0].1, pair[1].0))
+        .sum()
+}
+
+/// One path as the optimizer sees it.
+struct Item {
+    start: Point,
+    end: Point,
+    /// Whether the path may be cut from its far end.
+    reversible: bool,
+    /// Paths that must come before this one.
+    after: Vec<usize>,
+    /// Paths this one is nested with, which must keep their order relative to it.
+    nested: Vec<usize>,
+}
+
+impl Item {
+    /// Whether cutting the path either way round starts where it would otherwise end.
+    fn symmetric(&self) -> bool {
+        self.reversible || distance(self.start, self.end) < CLOSED_TOLERANCE
+    }
+
+    fn ends(&self, reversed: bool) -> (Point, Point) {
+        if reversed {
+            (self.end, self.start)
+        } else {
+            (self.start, self.end)
+        }
+    }
+}
+
+/// Order the paths of one depth level, starting from `entry` (the end of the last level),
+/// as `(index, reversed)` pairs.
+fn order_run(
+    paths: &[Vec<(f64, f64, f64)>],
+    entry: Option<Point>,
+    allow_reverse: bool,
+) -> Vec<(usize, bool)> {
+    let items = build_items(paths, allow_reverse);
+    let mut order = nearest_neighbour(&items, entry);
+    if items.len() <= MAX_TWO_OPT_PATHS {
+        two_opt(&items, &mut order, entry);
+    }
+    order
+}
+
+fn build_items(paths: &[Vec<(f64, f64, f64)>], allow_reverse: bool) -> Vec<Item> {
+    let mut items: Vec<Item> = paths
+        .iter()
+        .map(|path| {
+            let start = path.first().map_or((0.0, 0.0), |p| (p.0, p.1));
+            let end = path.last().map_or((0.0, 0.0), |p| (p.0, p.1));
+            Item {
+                start,
+                end,
+                reversible: allow_reverse && distance(start, end) >= CLOSED_TOLERANCE,
+                after: Vec::new(),
+                nested: Vec::new(),
+            }
+        })
+        .collect();
+
+    let outlines: Vec<Vec<Point>> = paths
+        .iter()
+        .map(|path| path.iter().map(|p| (p.0, p.1)).collect())
+        .collect();
+    let bounds: Vec<_> = outlines.iter().map(|outline| bounds(outline)).collect();
+    for later in 0..paths.len() {
+        for earlier in 0..later {
+            if nests(&outlines, &bounds, earlier, later)
+                || nests(&outlines, &bounds, later, earlier)
+            {
+                items[later].after.push(earlier);
+                items[later].nested.push(earlier);
+                items[earlier].nested.push(later);
+            }
+        }
+    }
+    items
+}
+
+/// Whether path `inner` lies inside the loop traced by path `outer`.
+fn nests(outlines: &[Vec<Point>], bounds: &[(Point, Point)], outer: usize, inner: usize) -> bool {
+    let (outer_min, outer_max) = bounds[outer];
+    let (inner_min, inner_max) = bounds[inner];
+    outlines[outer].len() >= 3
+        && !outlines[inner].is_empty()
+        && outer_min.0 <= inner_min.0
+        && outer_min.1 <= inner_min.1
+        && outer_max.0 >= inner_max.0
+        && outer_max.1 >= inner_max.1
+        && (outer_min, outer_max) != (inner_min, inner_max)
+        && is_point_inside(&outlines[inner][0], &outlines[outer])
+}
+
+fn nearest_neighbour(items: &[Item], entry: Option<Point>) -> Vec<(usize, bool)> {
+    let mut waiting: Vec<usize> = items.iter().map(|item| item.after.len()).collect();
+    let mut done = vec![false; items.len()];
+    let mut order = Vec::with_capacity(items.len());
+    let mut at = entry;
+    for _ in 0..items.len() {
+        let mut best: Option<(f64, usize, bool)> = None;
+        for (index, item) in items.iter().enumerate() {
+            if done[index] || waiting[index] > 0 {
+                continue;
+            }
+            let Some(from) = at else {
+                // Without an entry point, start where the generator did.
+                best = Some((0.0, index, false));
+                break;
+            };
+            for reversed in [false, true] {
+                if reversed && !item.reversible {
+                    continue;
+                }
+                let gap = distance(from, item.ends(reversed).0);
+                if best.is_none_or(|(closest, _, _)| gap < closest) {
+                    best = Some((gap, index, reversed));
+                }
+            }
+        }
+        let (_, index, reversed) = best.expect("nesting never forms a cycle");
+        done[index] = true;
+        for (other, item) in items.iter().enumerate() {
+            if item.after.contains(&index) {
+                waiting[other] -= 1;
+            }
+        }
+        at = Some(items[index].ends(reversed).1);
+        order.push((index, reversed));
+    }
+    order
+}
+
+/// Reverse stretches of `order` wherever that shortens the rapids at either end of them.
+///
+/// Only stretches whose paths cost the same cut in either direction are reversed, so the
+/// rapids inside them are unchanged, and never ones holding both paths of a nested pair.
+fn two_opt(items: &[Item], order: &mut [(usize, bool)], entry: Option<Point>) {
+    let count = order.len();
+    let mut position = vec![0; items.len()];
+    for (slot, &(index, _)) in order.iter().enumerate() {
+        position[index] = slot;
+    }
+
+    for _ in 0..MAX_TWO_OPT_SWEEPS {
+        let mut improved = false;
+        for i in 0..count {
+            if !items[order[i].0].symmetric() {
+                continue;
+            }
+            let before = if i == 0 {
+                entry
+            } else {
+                let (index, reversed) = order[i - 1];
+                Some(items[index].ends(reversed).1)
+            };
+            for j in i + 1..count {
+                if !items[order[j].0].symmetric() {
+                    break;
+                }
+                let (first, last) = (order[i], order[j]);
+                let first_start = items[first.0].ends(first.1).0;
+                let last_end = items[last.0].ends(last.1).1;
+                let after = order
+                    .get(j + 1)
+                    .map(|&(index, reversed)| items[index].ends(reversed).0);
+                let gap = |a: Option<Point>, b: Option<Point>| match (a, b) {
+                    (Some(a), Some(b)) => distance(a, b),
+                    _ => 0.0,
+                };
+                let old = gap(before, Some(first_start)) + gap(Some(last_end), after);
+                let new = gap(before, Some(last_end)) + gap(Some(first_start), after);
+                if new < old - 1e-9 && !splits_nesting(items, order, &position, i, j) {
+                    order[i..=j].reverse();
+                    for (slot, (index, reversed)) in
+                        order.iter_mut().enumerate().take(j + 1).skip(i)
+                    {
+                        *reversed ^= items[*index].reversible;
+                        position[*index] = slot;
+                    }
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+/// Whether reversing `order[i..=j]` would swap a nested pair.
+fn splits_nesting(
+    items: &[Item],
+    order: &[(usize, bool)],
+    position: &[usize],
+    i: usize,
+    j: usize,
+) -> bool {
+    order[i..=j].iter().any(|&(index, _)| {
+        items[index]
+            .nested
+            .iter()
+            .any(|&other| (i..=j).contains(&position[other]))
+    })
+}
+
+fn bounds(points: &[Point]) -> (Point, Point) {
+    points.iter().fold(
+        ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)),
+        |(min, max), p| {
+            (
+                (min.0.min(p.0), min.1.min(p.1)),
+                (max.0.max(p.0), max.1.max(p.1)),
+            )
+        },
+    )
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<(f64, f64, f64)> {
+        vec![
+            (x, y, -1.0),
+            (x + size, y, -1.0),
+            (x + size, y + size, -1.0),
+            (x, y + size, -1.0),
+            (x, y, -1.0),
+        ]
+    }
+
+    #[test]
+    fn test_optimize_path_order_shortens_rapids() {
+        // Squares along a line, generated in a scattered order.
+        let xs = [0.0, 40.0, 10.0, 30.0, 20.0, 50.0];
+        let mut toolpath = Toolpath::new(xs.iter().map(|&x| square(x, 0.0, 5.0)).collect());
+        let before = rapid_distance(&toolpath);
+
+        let saved = optimize_path_order(&mut toolpath, false);
+        assert!((before - rapid_distance(&toolpath) - saved).abs() < 1e-9);
+        assert!((rapid_distance(&toolpath) - 50.0).abs() < 1e-9);
+        let starts: Vec<f64> = toolpath.paths.iter().map(|path| path[0].0).collect();
+        assert_eq!(starts, vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0]);
+    }
+
+    #[test]
+    fn test_optimize_path_order_keeps_levels_and_nesting() {
+        // An outer ring cut after the ring nested in it, at two levels, with two squares
+        // far away generated in between.
+        let mut toolpath = Toolpath::new(vec![
+            square(5.0, 5.0, 10.0),
+            square(100.0, 0.0, 5.0),
+            square(0.0, 0.0, 20.0),
+            square(110.0, 0.0, 5.0),
+            square(5.0, 5.0, 10.0),
+            square(0.0, 0.0, 20.0),
+        ]);
+        toolpath.path_levels = vec![0, 0, 0, 0, 1, 1];
+        assert!(optimize_path_order(&mut toolpath, true) > 0.0);
+        assert!(optimize_path_order(&mut toolpath, true) == 0.0);
+        assert_eq!(toolpath.path_levels, vec![0, 0, 0, 0, 1, 1]);
+        let sizes: Vec<f64> = toolpath
+            .paths
+            .iter()
+            .map(|path| path[1].0 - path[0].0)
+            .collect();
+        assert_eq!(sizes, vec![10.0, 20.0, 5.0, 5.0, 10.0, 20.0]);
+        // Closed paths keep their direction.
+        assert!(toolpath.paths.iter().all(|path| path[1].1 == path[0].1));
+    }
+
+    #[test]
+    fn test_optimize_path_order_reverses_open_paths_when_allowed() {
+        let lines = || {
+            Toolpath::new(vec![
+                vec![(0.0, 0.0, -1.0), (10.0, 0.0, -1.0)],
+                vec![(0.0, 5.0, -1.0), (10.0, 5.0, -1.0)],
+                vec![(0.0, 10.0, -1.0), (10.0, 10.0, -1.0)],
+            ])
+        };
+        let mut fixed = lines();
+        assert_eq!(optimize_path_order(&mut fixed, false), 0.0);
+
+        let mut reversible = lines();
+        let before = rapid_distance(&reversible);
+        let saved = optimize_path_order(&mut reversible, true);
+        assert!((rapid_distance(&reversible) - 10.0).abs() < 1e-9);
+        assert!((before - saved - 10.0).abs() < 1e-9);
+        assert_eq!(reversible.paths[1][0], (10.0, 5.0, -1.0));
+    }
+}
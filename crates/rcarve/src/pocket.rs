@@ -1,111 +1,436 @@
-use crate::types::{Tool, Toolpath};
-use clipper2::{difference, inflate, EndType, JoinType, Path, PathType, Polygon, Polygons, Vertex};
+use crate::types::{CutDirection, DepthLevel, PocketStrategy, Tool, Toolpath};
+use anyhow::anyhow;
+use clipper2::{
+    difference, inflate, intersect, EndType, JoinType, Path, PathType, Polygon, Polygons, Vertex,
+};
+
+/// 2D point lists, open or closed.
+type Polylines = Vec<Vec<(f64, f64)>>;
 
 /// Generate a 2D pocket toolpath using iterative offset (contour-parallel) strategy
 ///
 /// This function uses clipper2 to perform offset pocketing by iteratively shrinking
 /// the pocket boundary inward by the stepover distance until the area is cleared.
-/// Supports islands (holes) that should not be milled.
+/// Supports islands (holes) that should not be milled. The area is cleared once per
+/// depth level, stepping down by at most the tool's `pass_depth`, climb milling.
 pub fn generate_pocket_toolpath(
     outer_boundary: &[(f64, f64)],
     islands: &[Vec<(f64, f64)>],
     tool: &Tool,
     target_depth: f64,
+) -> anyhow::Result<Toolpath> {
+    let levels = DepthLevel::schedule(target_depth, tool.pass_depth, None);
+    generate_pocket_toolpath_with_levels(
+        outer_boundary,
+        islands,
+        tool,
+        CutDirection::Climb,
+        &levels,
+    )
+}
+
+/// Generate a pocket toolpath that clears every offset ring at each depth level in turn.
+///
+/// The first ring keeps the tool edge on the walls and islands; each further ring steps
+/// in by the stepover.
+pub fn generate_pocket_toolpath_with_levels(
+    outer_boundary: &[(f64, f64)],
+    islands: &[Vec<(f64, f64)>],
+    tool: &Tool,
+    direction: CutDirection,
+    levels: &[DepthLevel],
 ) -> anyhow::Result<Toolpath> {
     // Step 1: Calculate stepover distance (Section 4.2, line 309)
+    let stepover_dist = stepover_distance(tool)?;
+
+    // Steps 2-3: The tool centre may reach anywhere in the pocket minus islands, less a
+    // tool radius.
+    let centres = offset(pocket_area(outer_boundary, islands), -tool.diameter / 2.0);
+
+    // Steps 4-5: Shrink by the stepover until the area is cleared, then cut every ring at
+    // one level before stepping down to the next.
+    let rings = offset_rings(centres, stepover_dist, direction);
+    Ok(closed_paths_at_levels(&[], &rings, levels))
+}
+
+/// Generate a zig-zag pocket toolpath with passes at `angle_deg` (counter-clockwise from X).
+///
+/// Neighbouring passes are joined by a straight link when it stays inside the reachable
+/// area; otherwise the tool lifts and starts a new run. The passes alternate direction, so
+/// `direction` only applies to the optional `boundary_pass` ring along walls and islands.
+pub fn generate_raster_pocket_toolpath(
+    outer_boundary: &[(f64, f64)],
+    islands: &[Vec<(f64, f64)>],
+    tool: &Tool,
+    angle_deg: f64,
+    boundary_pass: bool,
+    direction: CutDirection,
+    levels: &[DepthLevel],
+) -> anyhow::Result<Toolpath> {
+    let stepover_dist = stepover_distance(tool)?;
+    if !angle_deg.is_finite() {
+        return Err(anyhow!("Raster angle must be a number"));
+    }
+
+    let centres = offset(pocket_area(outer_boundary, islands), -tool.diameter / 2.0);
+    let (runs, finishing) =
+        raster_paths(&centres, stepover_dist, angle_deg, boundary_pass, direction);
+    Ok(closed_paths_at_levels(&runs, &finishing, levels))
+}
+
+/// Generate a pocket toolpath that only clears the material `previous_tool` left behind.
+///
+/// The area the previous tool swept is the pocket shrunk by its radius and grown back
+/// again; corners and slots beyond that are the rest area. `strategy` clears every tool
+/// position that cuts into the rest area, so an empty toolpath means nothing was left.
+pub fn generate_rest_pocket_toolpath(
+    outer_boundary: &[(f64, f64)],
+    islands: &[Vec<(f64, f64)>],
+    tool: &Tool,
+    previous_tool: &Tool,
+    strategy: &PocketStrategy,
+    direction: CutDirection,
+    levels: &[DepthLevel],
+) -> anyhow::Result<Toolpath> {
+    // Material thinner than this is left for the finish, not chased with extra paths.
+    const SLIVER: f64 = 0.05;
+
+    let stepover_dist = stepover_distance(tool)?;
+    if previous_tool.diameter <= tool.diameter {
+        return Err(anyhow!(
+            "Rest machining needs a previous tool larger than {:.2}mm; {} is {:.2}mm",
+            tool.diameter,
+            previous_tool.name,
+            previous_tool.diameter
+        ));
+    }
+
+    let area = pocket_area(outer_boundary, islands);
+    let previous_radius = previous_tool.diameter / 2.0;
+    let swept = fine_offset(fine_offset(area.clone(), -previous_radius), previous_radius);
+    let rest = fine_offset(
+        fine_offset(difference(area.clone(), swept), -SLIVER),
+        SLIVER,
+    );
+    if rest.polygons().is_empty() {
+        return Ok(closed_paths_at_levels(&[], &[], levels));
+    }
+
+    // Anywhere the tool fits and comes within a radius of the rest area.
+    let radius = tool.diameter / 2.0;
+    let centres = intersect(offset(area, -radius), fine_offset(rest, radius));
+
+    let (runs, rings) = match strategy {
+        PocketStrategy::Offset => (Vec::new(), offset_rings(centres, stepover_dist, direction)),
+        PocketStrategy::Raster {
+            angle_deg,
+            boundary_pass,
+        } => {
+            if !angle_deg.is_finite() {
+                return Err(anyhow!("Raster angle must be a number"));
+            }
+            raster_paths(
+                &centres,
+                stepover_dist,
+                *angle_deg,
+                *boundary_pass,
+                direction,
+            )
+        }
+    };
+    Ok(closed_paths_at_levels(&runs, &rings, levels))
+}
+
+fn stepover_distance(tool: &Tool) -> anyhow::Result<f64> {
     let stepover_dist = tool.diameter * tool.stepover;
+    if stepover_dist <= 0.0 {
+        return Err(anyhow!(
+            "Pocket tool needs a positive diameter and stepover"
+        ));
+    }
+    Ok(stepover_dist)
+}
+
+/// Concentric rings over the tool-centre area, each `stepover` inside the last.
+fn offset_rings(centres: Polygons, stepover: f64, direction: CutDirection) -> Vec<Vec<(f64, f64)>> {
+    // Iterative offset pocketing (Section 4.2, lines 307-329)
+    // Loop: shrink the polygon inward by stepover distance until it collapses
+    let mut current = centres;
+    let mut rings = Vec::new();
+    while !current.polygons().is_empty() {
+        rings.extend(oriented_rings(&current, direction));
+        current = offset(current, -stepover);
+    }
+    rings
+}
 
-    // Step 2: Convert outer boundary to clipper2::Polygons
-    let outer_vertices: Vec<Vertex> = outer_boundary
+/// Zig-zag runs over the tool-centre area, plus its boundary rings if `boundary_pass`.
+fn raster_paths(
+    centres: &Polygons,
+    stepover: f64,
+    angle_deg: f64,
+    boundary_pass: bool,
+    direction: CutDirection,
+) -> (Polylines, Polylines) {
+    let boundary = oriented_rings(centres, direction);
+
+    // Work in a frame where the passes run along X.
+    let (sin, cos) = angle_deg.to_radians().sin_cos();
+    let to_frame = |(x, y): (f64, f64)| (x * cos + y * sin, y * cos - x * sin);
+    let from_frame = |(u, v): (f64, f64)| (u * cos - v * sin, u * sin + v * cos);
+    let rings: Vec<Vec<(f64, f64)>> = boundary
         .iter()
-        .map(|(x, y)| Vertex::new(*x, *y))
+        .map(|ring| ring.iter().copied().map(to_frame).collect())
         .collect();
-    let outer_path = Path::new(outer_vertices, true); // closed polygon
-    let outer_polygon = Polygon::new(vec![outer_path], PathType::Subject);
-    let mut outer_polygons = Polygons::new(vec![outer_polygon]);
-
-    // Step 3: Handle islands by subtracting them from the outer boundary (Section 4.4, lines 351-356)
-    if !islands.is_empty() {
-        // Convert islands to clipper2::Polygons
-        let island_polygons: Vec<Polygon> = islands
-            .iter()
-            .map(|island| {
-                let island_vertices: Vec<Vertex> =
-                    island.iter().map(|(x, y)| Vertex::new(*x, *y)).collect();
-                let island_path = Path::new(island_vertices, true);
-                Polygon::new(vec![island_path], PathType::Clip)
-            })
-            .collect();
-        let islands_polygons = Polygons::new(island_polygons);
 
-        // Use clipper2's difference operation to subtract islands from outer boundary
-        outer_polygons = difference(outer_polygons, islands_polygons);
+    let runs: Vec<Vec<(f64, f64)>> = raster_runs(&rings, stepover)
+        .into_iter()
+        .map(|run| run.into_iter().map(from_frame).collect())
+        .collect();
+
+    let finishing = if boundary_pass { boundary } else { Vec::new() };
+    (runs, finishing)
+}
+
+/// The pocket outline with the islands subtracted (Section 4.4, lines 351-356).
+fn pocket_area(outer_boundary: &[(f64, f64)], islands: &[Vec<(f64, f64)>]) -> Polygons {
+    let to_path = |points: &[(f64, f64)]| {
+        let vertices: Vec<Vertex> = points.iter().map(|(x, y)| Vertex::new(*x, *y)).collect();
+        Path::new(vertices, true) // closed polygon
+    };
+    let outer_polygon = Polygon::new(vec![to_path(outer_boundary)], PathType::Subject);
+    let outer_polygons = Polygons::new(vec![outer_polygon]);
+    if islands.is_empty() {
+        return outer_polygons;
     }
 
-    // Step 4: Iterative offset pocketing (Section 4.2, lines 307-329)
-    // Start with the pocket shape (outer boundary minus islands)
-    let mut current_pocket = outer_polygons;
-    let mut pocket_paths: Vec<Vec<(f64, f64)>> = Vec::new();
+    let island_polygons: Vec<Polygon> = islands
+        .iter()
+        .map(|island| Polygon::new(vec![to_path(island)], PathType::Clip))
+        .collect();
+    // Use clipper2's difference operation to subtract islands from outer boundary
+    difference(outer_polygons, Polygons::new(island_polygons))
+}
 
-    // Loop: shrink the polygon inward by stepover distance until it collapses
-    loop {
-        // Use negative delta to shrink (deflate) the polygon inward
-        let offset_result = inflate(
-            current_pocket.clone(),
-            -stepover_dist,  // Negative = shrink inward
-            JoinType::Round, // Round joins for smooth curves (circles, arcs)
-            EndType::ClosedPolygon,
-            2.0,  // Miter limit (unused for Round joins)
-            0.25, // Arc tolerance - controls smoothness of rounded corners
-        );
+/// Grow (positive `delta`) or shrink (negative) every polygon.
+fn offset(polygons: Polygons, delta: f64) -> Polygons {
+    offset_with_tolerance(polygons, delta, 0.25)
+}
+
+/// Like [`offset`], with arcs close enough that growing back a shrunk area does not leave
+/// chord-shaped slivers behind.
+fn fine_offset(polygons: Polygons, delta: f64) -> Polygons {
+    offset_with_tolerance(polygons, delta, 0.01)
+}
 
-        // If inflating (shrinking) produces no paths, we are done
-        if offset_result.polygons().is_empty() {
-            break;
+fn offset_with_tolerance(polygons: Polygons, delta: f64, arc_tolerance: f64) -> Polygons {
+    inflate(
+        polygons,
+        delta,
+        JoinType::Round, // Round joins for smooth curves (circles, arcs)
+        EndType::ClosedPolygon,
+        2.0,           // Miter limit (unused for Round joins)
+        arc_tolerance, // Controls smoothness of rounded corners
+    )
+}
+
+/// Every ring of `polygons`, wound so the cut follows `direction`.
+fn oriented_rings(polygons: &Polygons, direction: CutDirection) -> Vec<Vec<(f64, f64)>> {
+    let mut rings: Vec<Vec<(f64, f64)>> = Vec::new();
+    for polygon in polygons.polygons() {
+        for path in polygon.paths() {
+            let path_2d: Vec<(f64, f64)> = path.vertices().iter().map(|v| (v.x(), v.y())).collect();
+            if !path_2d.is_empty() {
+                rings.push(path_2d);
+            }
         }
+    }
 
-        // Extract all paths from the offset result and add to pocket_paths
-        for polygon in offset_result.polygons() {
-            for path in polygon.paths() {
-                // Convert path vertices to (f64, f64) tuples
-                let path_2d: Vec<(f64, f64)> =
-                    path.vertices().iter().map(|v| (v.x(), v.y())).collect();
-                if !path_2d.is_empty() {
-                    pocket_paths.push(path_2d);
-                }
+    // A ring nested inside an odd number of others runs around an island, so the
+    // wall it cuts is inside it; every other ring cuts the pocket wall outside it.
+    let nesting: Vec<usize> = rings
+        .iter()
+        .enumerate()
+        .map(|(i, ring)| {
+            rings
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *j != i && contains_point(other, ring[0]))
+                .count()
+        })
+        .collect();
+    rings
+        .into_iter()
+        .zip(nesting)
+        .map(|(mut ring, depth)| {
+            direction.orient(&mut ring, depth % 2 == 1);
+            ring
+        })
+        .collect()
+}
+
+/// Repeat `open` paths then `closed` rings at every level, top to bottom.
+fn closed_paths_at_levels(
+    open: &[Vec<(f64, f64)>],
+    closed: &[Vec<(f64, f64)>],
+    levels: &[DepthLevel],
+) -> Toolpath {
+    let mut toolpath = Toolpath {
+        levels: levels.to_vec(),
+        ..Default::default()
+    };
+
+    for (level_index, level) in levels.iter().enumerate() {
+        for path_2d in open {
+            toolpath
+                .paths
+                .push(path_2d.iter().map(|(x, y)| (*x, *y, level.z)).collect());
+            toolpath.path_levels.push(level_index);
+        }
+        for path_2d in closed {
+            let mut path_3d: Vec<(f64, f64, f64)> =
+                path_2d.iter().map(|(x, y)| (*x, *y, level.z)).collect();
+
+            // Close the path by adding first point at end if not already closed
+            if !path_3d.is_empty() && path_3d[0] != *path_3d.last().unwrap() {
+                path_3d.push(path_3d[0]);
             }
+
+            toolpath.paths.push(path_3d);
+            toolpath.path_levels.push(level_index);
         }
+    }
 
-        // Update current_pocket for next iteration
-        current_pocket = offset_result;
+    toolpath
+}
+
+/// Zig-zag runs along X over the area bounded by `rings` (even-odd), `spacing` apart.
+fn raster_runs(rings: &[Vec<(f64, f64)>], spacing: f64) -> Vec<Vec<(f64, f64)>> {
+    // Keep run ends a hair inside the boundary so links can be tested for crossings.
+    const INSET: f64 = 1e-3;
+
+    let (min_y, max_y) = rings
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.1), hi.max(p.1))
+        });
+    if rings.is_empty() || max_y - min_y <= 2.0 * INSET {
+        return Vec::new();
     }
 
-    // Step 5: Convert 2D paths to 3D toolpaths (Section 4.2, line 332)
-    // TODO: Multi-pass Z handling (for now, single pass at target_depth)
-    let target_z = -target_depth;
-    let mut paths_3d: Vec<Vec<(f64, f64, f64)>> = Vec::new();
+    // Spread the passes evenly so the first and last sit on the boundary extremes.
+    let count = ((max_y - min_y) / spacing).ceil().max(1.0) as usize;
+    let step = (max_y - min_y) / count as f64;
+    let ys: Vec<f64> = (0..=count)
+        .map(|i| (min_y + i as f64 * step).clamp(min_y + INSET, max_y - INSET))
+        .collect();
 
-    for path_2d in pocket_paths {
-        // Ensure path is closed by repeating first point at end if needed
-        let mut path_3d: Vec<(f64, f64, f64)> =
-            path_2d.iter().map(|(x, y)| (*x, *y, target_z)).collect();
+    // The spans of each pass that lie inside the area.
+    let mut rows: Vec<Vec<(f64, f64)>> = ys
+        .iter()
+        .map(|&y| {
+            let mut crossings: Vec<f64> = Vec::new();
+            for ring in rings {
+                for (index, a) in ring.iter().enumerate() {
+                    let b = ring[(index + 1) % ring.len()];
+                    if (a.1 > y) != (b.1 > y) {
+                        crossings.push(a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1));
+                    }
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            crossings
+                .chunks_exact(2)
+                .filter(|pair| pair[1] - pair[0] > 2.0 * INSET)
+                .map(|pair| (pair[0] + INSET, pair[1] - INSET))
+                .collect()
+        })
+        .collect();
 
-        // Close the path by adding first point at end if not already closed
-        if !path_3d.is_empty() && path_3d[0] != *path_3d.last().unwrap() {
-            path_3d.push(path_3d[0]);
-        }
+    let mut runs = Vec::new();
+    while let Some(start_row) = rows.iter().position(|row| !row.is_empty()) {
+        let (x0, x1) = rows[start_row].remove(0);
+        let mut run = vec![(x0, ys[start_row]), (x1, ys[start_row])];
+        let mut row = start_row;
+        let mut rightward = true;
+
+        // Step to the next row while a straight link from the run end stays inside.
+        while row + 1 < rows.len() {
+            let from = *run.last().unwrap();
+            let entry = |span: &(f64, f64)| if rightward { span.1 } else { span.0 };
+            let next = rows[row + 1]
+                .iter()
+                .enumerate()
+                .filter(|(_, span)| {
+                    let to = (entry(span), ys[row + 1]);
+                    link_stays_inside(rings, from, to)
+                })
+                .min_by(|(_, a), (_, b)| {
+                    (entry(a) - from.0)
+                        .abs()
+                        .total_cmp(&(entry(b) - from.0).abs())
+                })
+                .map(|(index, _)| index);
+            let Some(index) = next else {
+                break;
+            };
 
-        paths_3d.push(path_3d);
+            let span = rows[row + 1].remove(index);
+            row += 1;
+            rightward = !rightward;
+            let (enter, leave) = if rightward {
+                (span.0, span.1)
+            } else {
+                (span.1, span.0)
+            };
+            run.push((enter, ys[row]));
+            run.push((leave, ys[row]));
+        }
+        runs.push(run);
     }
 
-    // Step 6: Return Toolpath struct
-    Ok(Toolpath { paths: paths_3d })
+    runs
+}
+
+/// Whether the segment `from`-`to` crosses none of the `rings`' edges.
+fn link_stays_inside(rings: &[Vec<(f64, f64)>], from: (f64, f64), to: (f64, f64)) -> bool {
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    rings.iter().all(|ring| {
+        (0..ring.len()).all(|index| {
+            let a = ring[index];
+            let b = ring[(index + 1) % ring.len()];
+            let d1 = cross(a, b, from);
+            let d2 = cross(a, b, to);
+            let d3 = cross(from, to, a);
+            let d4 = cross(from, to, b);
+            !((d1 > 0.0) != (d2 > 0.0) && (d3 > 0.0) != (d4 > 0.0))
+        })
+    })
+}
+
+/// Even-odd point-in-polygon test.
+fn contains_point(ring: &[(f64, f64)], point: (f64, f64)) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[j]);
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::ToolType;
+    use crate::types::{FeedSettings, ToolType, Units};
 
     #[test]
     fn test_simple_pocket() {
@@ -124,6 +449,9 @@ mod tests {
             stepover: 0.4, // 40% = 2.4mm stepover
             pass_depth: 5.0,
             tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
         };
 
         let result = generate_pocket_toolpath(&outer, &[], &tool, 5.0);
@@ -148,6 +476,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::manual_range_contains)]
     fn test_pocket_with_island() {
         // Square pocket with a circular island in the center
         let outer = vec![
@@ -173,6 +502,9 @@ mod tests {
             stepover: 0.4,
             pass_depth: 5.0,
             tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
         };
 
         let result = generate_pocket_toolpath(&outer, &[island], &tool, 5.0);
@@ -198,4 +530,365 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_pocket_multi_pass_levels() {
+        let outer = vec![
+            (0.0, 0.0),
+            (40.0, 0.0),
+            (40.0, 40.0),
+            (0.0, 40.0),
+            (0.0, 0.0),
+        ];
+
+        let tool = Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        };
+
+        let single = generate_pocket_toolpath(&outer, &[], &tool, 2.0).unwrap();
+        let toolpath = generate_pocket_toolpath(&outer, &[], &tool, 5.0).unwrap();
+
+        assert_eq!(toolpath.levels.len(), 3);
+        assert_eq!(toolpath.paths.len(), single.paths.len() * 3);
+
+        // Levels are cut in order, top to bottom, never deeper than pass_depth per step.
+        let mut previous_z = 0.0;
+        let mut previous_level = 0;
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            let level_index = toolpath.path_levels[index];
+            assert!(level_index >= previous_level);
+            previous_level = level_index;
+
+            let z = toolpath.levels[level_index].z;
+            assert!(path.iter().all(|point| point.2 == z));
+            assert!(previous_z - z <= tool.pass_depth + 1e-9);
+            previous_z = z;
+        }
+        assert_eq!(previous_z, -5.0);
+    }
+
+    #[test]
+    fn test_pocket_winding_follows_cut_direction() {
+        let outer = vec![
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+        ];
+        let island = vec![
+            (30.0, 30.0),
+            (70.0, 30.0),
+            (70.0, 70.0),
+            (30.0, 70.0),
+            (30.0, 30.0),
+        ];
+        let mut reversed_outer = outer.clone();
+        reversed_outer.reverse();
+        let mut reversed_island = island.clone();
+        reversed_island.reverse();
+
+        let tool = Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 5.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        };
+        let levels = DepthLevel::schedule(3.0, tool.pass_depth, None);
+        let signed_area = |path: &[(f64, f64, f64)]| {
+            path.windows(2)
+                .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+                .sum::<f64>()
+        };
+
+        // With an M3 spindle, climb milling runs counter-clockwise along the pocket wall
+        // and clockwise around islands.
+        for direction in [CutDirection::Climb, CutDirection::Conventional] {
+            let wall_ccw = direction == CutDirection::Climb;
+            for (outer, island) in [(&outer, &island), (&reversed_outer, &reversed_island)] {
+                let toolpath = generate_pocket_toolpath_with_levels(
+                    outer,
+                    std::slice::from_ref(island),
+                    &tool,
+                    direction,
+                    &levels,
+                )
+                .unwrap();
+
+                let (mut walls, mut islands) = (0, 0);
+                for path in &toolpath.paths {
+                    let min_x = path.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+                    let max_x = path.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+                    let ccw = signed_area(path) > 0.0;
+                    // Wall rings stay near the outside; island rings hug the island.
+                    if min_x < 10.0 {
+                        assert_eq!(ccw, wall_ccw, "wall ring, {direction:?}");
+                        walls += 1;
+                    } else if min_x > 20.0 && min_x < 30.0 && max_x > 70.0 && max_x < 80.0 {
+                        assert_eq!(ccw, !wall_ccw, "island ring, {direction:?}");
+                        islands += 1;
+                    }
+                }
+                assert!(walls > 0 && islands > 0);
+            }
+        }
+    }
+    fn square(min: f64, max: f64) -> Vec<(f64, f64)> {
+        vec![(min, min), (max, min), (max, max), (min, max), (min, min)]
+    }
+
+    fn endmill() -> Tool {
+        Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    #[test]
+    fn test_pocket_first_ring_keeps_tool_inside_wall() {
+        let toolpath = generate_pocket_toolpath(&square(0.0, 40.0), &[], &endmill(), 2.0).unwrap();
+        let min_x = toolpath.paths[0]
+            .iter()
+            .map(|p| p.0)
+            .fold(f64::INFINITY, f64::min);
+        assert!((min_x - 3.0).abs() < 0.01, "first ring at x={min_x}");
+    }
+
+    #[test]
+    fn test_raster_pocket_zig_zags_in_one_run() {
+        let levels = DepthLevel::schedule(4.0, 2.0, None);
+        let toolpath = generate_raster_pocket_toolpath(
+            &square(0.0, 40.0),
+            &[],
+            &endmill(),
+            0.0,
+            true,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap();
+
+        // One zig-zag run plus one boundary ring per level.
+        assert_eq!(toolpath.paths.len(), 4);
+        assert_eq!(toolpath.path_levels, vec![0, 0, 1, 1]);
+
+        let run = &toolpath.paths[0];
+        let rows = run.len() / 2;
+        assert!(rows >= 34.0_f64.div_euclid(2.4) as usize);
+        for (index, pair) in run.chunks(2).enumerate() {
+            // Each pass is horizontal, alternating direction, and within a tool radius.
+            assert!((pair[0].1 - pair[1].1).abs() < 1e-9);
+            assert_eq!(pair[1].0 > pair[0].0, index % 2 == 0);
+            for point in pair {
+                assert!((2.99..=37.01).contains(&point.0) && (2.99..=37.01).contains(&point.1));
+            }
+        }
+        // Passes are no further apart than the stepover.
+        for window in run.windows(3).step_by(2) {
+            assert!(window[2].1 - window[1].1 <= 2.4 + 1e-9);
+        }
+
+        let ring = &toolpath.paths[1];
+        assert_eq!(ring.first(), ring.last());
+    }
+
+    #[test]
+    fn test_raster_pocket_angle_and_islands() {
+        let island = square(30.0, 70.0);
+        let levels = DepthLevel::schedule(2.0, 2.0, None);
+        let toolpath = generate_raster_pocket_toolpath(
+            &square(0.0, 100.0),
+            std::slice::from_ref(&island),
+            &endmill(),
+            45.0,
+            false,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap();
+
+        // The island splits the passes, so the tool has to lift at least once.
+        assert!(toolpath.paths.len() > 1);
+
+        let distance_to_island = |x: f64, y: f64| {
+            let dx = (30.0 - x).max(x - 70.0).max(0.0);
+            let dy = (30.0 - y).max(y - 70.0).max(0.0);
+            dx.hypot(dy)
+        };
+        for path in &toolpath.paths {
+            for pair in path.chunks(2) {
+                // Passes run at 45 degrees.
+                let (dx, dy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+                assert!((dx - dy).abs() < 1e-6, "pass ({dx}, {dy})");
+            }
+            for segment in path.windows(2) {
+                // Neither passes nor links come within a tool radius of the island, less
+                // the 0.25mm arc tolerance of the rounded offset corners.
+                for step in 0..=20 {
+                    let t = step as f64 / 20.0;
+                    let x = segment[0].0 + (segment[1].0 - segment[0].0) * t;
+                    let y = segment[0].1 + (segment[1].1 - segment[0].1) * t;
+                    assert!(
+                        distance_to_island(x, y) > 2.74,
+                        "({x}, {y}) gouges the island"
+                    );
+                    assert!((2.9..=97.1).contains(&x) && (2.9..=97.1).contains(&y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_ring_keeps_a_tool_radius_from_the_walls() {
+        let outer = vec![
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+        ];
+        let island = vec![
+            (30.0, 30.0),
+            (70.0, 30.0),
+            (70.0, 70.0),
+            (30.0, 70.0),
+            (30.0, 30.0),
+        ];
+
+        // A small stepover would have put the first ring inside the wall.
+        let tool = Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.2,
+            pass_depth: 5.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        };
+        let toolpath = generate_pocket_toolpath(&outer, &[island], &tool, 5.0).unwrap();
+
+        // Distance from a point to the wall, and to the island.
+        let to_wall = |p: &(f64, f64, f64)| p.0.min(p.1).min(100.0 - p.0).min(100.0 - p.1);
+        let to_island = |p: &(f64, f64, f64)| {
+            let dx = (30.0 - p.0).max(p.0 - 70.0).max(0.0);
+            let dy = (30.0 - p.1).max(p.1 - 70.0).max(0.0);
+            dx.hypot(dy)
+        };
+        let points: Vec<_> = toolpath.paths.iter().flatten().collect();
+        let nearest_wall = points.iter().map(|p| to_wall(p)).fold(f64::MAX, f64::min);
+        let nearest_island = points.iter().map(|p| to_island(p)).fold(f64::MAX, f64::min);
+        assert!((nearest_wall - 3.0).abs() < 1e-3, "{nearest_wall}");
+        assert!((nearest_island - 3.0).abs() < 1e-3, "{nearest_island}");
+    }
+
+    #[test]
+    fn test_rest_pocket_only_clears_corners() {
+        let previous = Tool {
+            name: "12mm Endmill".to_string(),
+            diameter: 12.0,
+            tool_type: ToolType::Endmill { diameter: 12.0 },
+            ..endmill()
+        };
+        let tool = Tool {
+            name: "3mm Endmill".to_string(),
+            diameter: 3.0,
+            tool_type: ToolType::Endmill { diameter: 3.0 },
+            ..endmill()
+        };
+        let levels = DepthLevel::schedule(2.0, 2.0, None);
+        let raster = PocketStrategy::Raster {
+            angle_deg: 0.0,
+            boundary_pass: true,
+        };
+
+        for strategy in [PocketStrategy::Offset, raster] {
+            let toolpath = generate_rest_pocket_toolpath(
+                &square(0.0, 40.0),
+                &[],
+                &tool,
+                &previous,
+                &strategy,
+                CutDirection::Climb,
+                &levels,
+            )
+            .unwrap();
+            let points: Vec<_> = toolpath.paths.iter().flatten().collect();
+            assert!(!points.is_empty(), "{strategy:?}");
+
+            // The 12mm tool leaves a 6mm fillet in each corner; the 3mm tool only needs
+            // to reach within its radius of that.
+            for point in &points {
+                let near_x = point.0.min(40.0 - point.0);
+                let near_y = point.1.min(40.0 - point.1);
+                assert!(near_x >= 1.49 && near_y >= 1.49, "{point:?} gouges a wall");
+                assert!(
+                    near_x <= 7.6 && near_y <= 7.6,
+                    "{point:?} is outside a corner"
+                );
+            }
+            // Every corner is cleaned right into the tool radius.
+            for corner in [(1.5, 1.5), (38.5, 1.5), (38.5, 38.5), (1.5, 38.5)] {
+                assert!(points
+                    .iter()
+                    .any(|p| (p.0 - corner.0).hypot(p.1 - corner.1) < 0.1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rest_pocket_needs_a_larger_previous_tool() {
+        let levels = DepthLevel::schedule(2.0, 2.0, None);
+        let result = generate_rest_pocket_toolpath(
+            &square(0.0, 40.0),
+            &[],
+            &endmill(),
+            &endmill(),
+            &PocketStrategy::Offset,
+            CutDirection::Climb,
+            &levels,
+        );
+        assert!(result.unwrap_err().to_string().contains("larger"));
+
+        // A pocket with no corners tighter than the previous tool leaves nothing behind.
+        let circle: Vec<(f64, f64)> = (0..=72)
+            .map(|i| {
+                let angle = (i as f64 * 5.0).to_radians();
+                (20.0 + 15.0 * angle.cos(), 20.0 + 15.0 * angle.sin())
+            })
+            .collect();
+        let small = Tool {
+            diameter: 3.0,
+            tool_type: ToolType::Endmill { diameter: 3.0 },
+            ..endmill()
+        };
+        let toolpath = generate_rest_pocket_toolpath(
+            &circle,
+            &[],
+            &small,
+            &endmill(),
+            &PocketStrategy::Offset,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap();
+        assert!(toolpath.paths.is_empty());
+    }
 }
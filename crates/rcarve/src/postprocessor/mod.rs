@@ -0,0 +1,806 @@
+use crate::drill::PECK_CLEARANCE;
+use crate::types::{DrillCycle, FeedSettings, GCode, PathArc, SegmentFeed, Tool, Toolpath, Units};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+mod template;
+
+pub use template::{load_user_postprocessors, user_posts_dir, TemplatePost};
+
+/// Feed rate (mm/min) for toolpaths whose tool sets none.
+const DEFAULT_FEED: f64 = 100.0;
+
+/// Values a dialect may substitute into its output, tracked by [`GCodeWriter`].
+#[derive(Debug, Clone, Default)]
+pub struct PostVariables {
+    /// 1-based number of the current tool; 0 before the first tool change.
+    pub tool_number: usize,
+    pub tool_name: String,
+    pub tool_diameter: f64,
+    /// Spindle speed of the current tool, when known.
+    pub spindle_rpm: Option<f64>,
+    pub safe_z: f64,
+    /// Units the program is written in; every length and rate above is already in them.
+    pub units: Units,
+}
+
+/// A G-code dialect: everything that differs between controllers.
+///
+/// Implementations only describe syntax; [`GCodeWriter`] walks the toolpaths and asks the
+/// dialect how to spell each line.
+pub trait PostProcessor {
+    /// Short name used to pick the dialect, e.g. `"grbl"`.
+    fn name(&self) -> &str;
+
+    /// Lines emitted before any motion.
+    fn header(&self, vars: &PostVariables) -> Vec<String> {
+        vec![
+            "G90".to_string(),
+            vars.units.gcode().to_string(),
+            "G17".to_string(),
+        ]
+    }
+
+    /// Lines emitted after the final retract.
+    fn footer(&self, _vars: &PostVariables) -> Vec<String> {
+        vec!["M5".to_string(), "M2".to_string()]
+    }
+
+    /// A comment line.
+    fn comment(&self, text: &str) -> String {
+        // Nested parentheses would end the comment early.
+        format!("({})", text.replace('(', "[").replace(')', "]"))
+    }
+
+    /// Digits after the decimal point for coordinates.
+    fn decimals(&self) -> usize {
+        4
+    }
+
+    /// Format a coordinate value.
+    fn format_number(&self, value: f64) -> String {
+        format!("{:.*}", self.decimals(), value)
+    }
+
+    /// Digits after the decimal point for feed rates.
+    fn feed_decimals(&self) -> usize {
+        1
+    }
+
+    /// Format a feed rate.
+    fn format_feed(&self, value: f64) -> String {
+        format!("{:.*}", self.feed_decimals(), value)
+    }
+
+    /// Increment between `N` line numbers, or `None` to leave lines unnumbered.
+    fn line_number_step(&self) -> Option<usize> {
+        None
+    }
+
+    /// Rapid move in XY at safe height.
+    fn rapid(&self, x: f64, y: f64) -> String {
+        format!("G0 X{} Y{}", self.format_number(x), self.format_number(y))
+    }
+
+    /// Vertical feed move down into the material.
+    fn plunge(&self, z: f64, feed: f64) -> String {
+        format!("G1 Z{} F{}", self.format_number(z), self.format_feed(feed))
+    }
+
+    /// Cutting move; `z` is only given when it changes, and `feed` when the rate changes.
+    fn cut(&self, x: f64, y: f64, z: Option<f64>, feed: Option<f64>) -> String {
+        let mut line = format!("G1 X{} Y{}", self.format_number(x), self.format_number(y));
+        if let Some(z) = z {
+            line.push_str(&format!(" Z{}", self.format_number(z)));
+        }
+        if let Some(feed) = feed {
+            line.push_str(&format!(" F{}", self.format_feed(feed)));
+        }
+        line
+    }
+
+    /// Start the spindle clockwise at `vars.spindle_rpm`.
+    fn spindle_on(&self, vars: &PostVariables) -> String {
+        format!("M3 S{:.0}", vars.spindle_rpm.unwrap_or_default())
+    }
+
+    /// Rapid move up to `z`.
+    fn retract(&self, z: f64) -> String {
+        format!("G0 Z{z:.1}")
+    }
+
+    /// Rapid vertical move to `z`, which may be below the retract height.
+    fn rapid_z(&self, z: f64) -> String {
+        format!("G0 Z{}", self.format_number(z))
+    }
+
+    /// Pause for `seconds`.
+    fn dwell(&self, seconds: f64) -> String {
+        format!("G4 P{seconds}")
+    }
+
+    /// Whether the controller has the G81, G82 and G83 canned drilling cycles.
+    fn supports_canned_cycles(&self) -> bool {
+        true
+    }
+
+    /// One hole of a canned cycle: rapid to the hole, drill from the `retract_z` plane to
+    /// `bottom`, then return to the starting height (G98).
+    ///
+    /// Returns `None` if the dialect cannot express `cycle` and the caller must expand it
+    /// into moves.
+    fn drill_cycle(
+        &self,
+        bottom: (f64, f64, f64),
+        retract_z: f64,
+        cycle: &DrillCycle,
+        feed: Option<f64>,
+    ) -> Option<String> {
+        if !self.supports_canned_cycles() {
+            return None;
+        }
+        let (code, extra) = match (cycle.peck_depth, cycle.dwell) {
+            (None, None) => ("G81", String::new()),
+            (None, Some(dwell)) => ("G82", format!(" P{dwell}")),
+            (Some(peck), None) => ("G83", format!(" Q{}", self.format_number(peck))),
+            // G83 has no dwell.
+            (Some(_), Some(_)) => return None,
+        };
+        let mut line = format!(
+            "G98 {code} X{} Y{} Z{} R{}{extra}",
+            self.format_number(bottom.0),
+            self.format_number(bottom.1),
+            self.format_number(bottom.2),
+            self.format_number(retract_z),
+        );
+        if let Some(feed) = feed {
+            line.push_str(&format!(" F{}", self.format_feed(feed)));
+        }
+        Some(line)
+    }
+
+    /// Cancel the canned cycle after the last hole.
+    fn cancel_drill_cycle(&self) -> String {
+        "G80".to_string()
+    }
+
+    /// Whether the controller accepts G2/G3 arcs.
+    fn supports_arcs(&self) -> bool {
+        true
+    }
+
+    /// A G2 (clockwise) or G3 arc to `end`, with the centre given relative to the start.
+    ///
+    /// Returns `None` if the dialect cannot cut arcs and the caller must use line segments.
+    fn arc(
+        &self,
+        clockwise: bool,
+        end: (f64, f64, f64),
+        center_offset: (f64, f64),
+    ) -> Option<String> {
+        if !self.supports_arcs() {
+            return None;
+        }
+        Some(format!(
+            "{} X{} Y{} Z{} I{} J{}",
+            if clockwise { "G2" } else { "G3" },
+            self.format_number(end.0),
+            self.format_number(end.1),
+            self.format_number(end.2),
+            self.format_number(center_offset.0),
+            self.format_number(center_offset.1),
+        ))
+    }
+
+    /// Commands that swap to the tool in `vars`. The tool is already at safe height and a
+    /// comment naming the tool has been written.
+    fn tool_change(&self, vars: &PostVariables) -> Vec<String>;
+
+    /// Height for rapid moves between paths.
+    fn safe_z(&self) -> f64 {
+        10.0
+    }
+}
+
+/// GRBL hobby routers. No tool changer, so tool changes pause the program.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Grbl;
+
+impl PostProcessor for Grbl {
+    fn name(&self) -> &str {
+        "grbl"
+    }
+
+    fn supports_canned_cycles(&self) -> bool {
+        false
+    }
+
+    fn tool_change(&self, _vars: &PostVariables) -> Vec<String> {
+        vec!["M5".to_string(), "M0".to_string()]
+    }
+}
+
+/// LinuxCNC mills, with tool length offsets applied after each change.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinuxCnc;
+
+impl PostProcessor for LinuxCnc {
+    fn name(&self) -> &str {
+        "linuxcnc"
+    }
+
+    fn header(&self, vars: &PostVariables) -> Vec<String> {
+        // Blend corners within a hundredth of a millimetre.
+        let blend = match vars.units {
+            Units::Millimeters => "0.01",
+            Units::Inches => "0.0004",
+        };
+        vec![
+            "G90".to_string(),
+            vars.units.gcode().to_string(),
+            "G17".to_string(),
+            // Cancel cutter compensation, tool length offset and canned cycles.
+            "G40 G49 G80".to_string(),
+            format!("G64 P{blend}"),
+        ]
+    }
+
+    fn tool_change(&self, vars: &PostVariables) -> Vec<String> {
+        let number = vars.tool_number;
+        vec![format!("T{number} M6"), format!("G43 H{number}")]
+    }
+}
+
+/// Mach3 routers, with numbered lines.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mach3;
+
+impl PostProcessor for Mach3 {
+    fn name(&self) -> &str {
+        "mach3"
+    }
+
+    fn header(&self, vars: &PostVariables) -> Vec<String> {
+        vec![
+            "G90".to_string(),
+            vars.units.gcode().to_string(),
+            "G17".to_string(),
+            "G40 G49".to_string(),
+        ]
+    }
+
+    fn footer(&self, _vars: &PostVariables) -> Vec<String> {
+        vec!["M5".to_string(), "M30".to_string()]
+    }
+
+    fn line_number_step(&self) -> Option<usize> {
+        Some(10)
+    }
+
+    fn tool_change(&self, vars: &PostVariables) -> Vec<String> {
+        let number = vars.tool_number;
+        vec![
+            "M5".to_string(),
+            format!("T{number} M6"),
+            format!("G43 H{number}"),
+        ]
+    }
+}
+
+/// Marlin firmware on 3D-printer style CNC conversions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Marlin;
+
+impl PostProcessor for Marlin {
+    fn name(&self) -> &str {
+        "marlin"
+    }
+
+    fn header(&self, vars: &PostVariables) -> Vec<String> {
+        vec!["G90".to_string(), vars.units.gcode().to_string()]
+    }
+
+    fn footer(&self, _vars: &PostVariables) -> Vec<String> {
+        // Marlin has no program end; release the motors instead.
+        vec!["M5".to_string(), "M84".to_string()]
+    }
+
+    fn comment(&self, text: &str) -> String {
+        format!("; {text}")
+    }
+
+    fn decimals(&self) -> usize {
+        3
+    }
+
+    fn dwell(&self, seconds: f64) -> String {
+        // Marlin reads P as milliseconds.
+        format!("G4 S{seconds}")
+    }
+
+    fn supports_canned_cycles(&self) -> bool {
+        false
+    }
+
+    fn tool_change(&self, vars: &PostVariables) -> Vec<String> {
+        // M0 shows its message on the printer display while it waits.
+        vec![
+            "M5".to_string(),
+            format!("M0 Insert T{} {}", vars.tool_number, vars.tool_name),
+        ]
+    }
+}
+
+/// Every built-in dialect.
+pub fn builtin_postprocessors() -> Vec<Box<dyn PostProcessor>> {
+    vec![
+        Box::new(Grbl),
+        Box::new(LinuxCnc),
+        Box::new(Mach3),
+        Box::new(Marlin),
+    ]
+}
+
+/// Look up a built-in dialect by name, ignoring case.
+pub fn find_postprocessor(name: &str) -> Option<Box<dyn PostProcessor>> {
+    builtin_postprocessors()
+        .into_iter()
+        .find(|post| post.name().eq_ignore_ascii_case(name))
+}
+
+/// Find a dialect by name: built-ins first, then definitions in `user_dir`.
+///
+/// User posts match on their `name` setting or file name. A matching definition with
+/// errors is reported rather than skipped.
+pub fn resolve_postprocessor(
+    name: &str,
+    user_dir: Option<&Path>,
+) -> Result<Box<dyn PostProcessor>> {
+    if let Some(post) = find_postprocessor(name) {
+        return Ok(post);
+    }
+    if let Some(dir) = user_dir.filter(|dir| dir.is_dir()) {
+        for (path, post) in load_user_postprocessors(dir)? {
+            let stem_matches = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.eq_ignore_ascii_case(name));
+            match post {
+                Ok(post) if stem_matches || post.name().eq_ignore_ascii_case(name) => {
+                    return Ok(Box::new(post))
+                }
+                Err(err) if stem_matches => return Err(err),
+                _ => {}
+            }
+        }
+    }
+    Err(anyhow!("unknown postprocessor `{name}`"))
+}
+
+/// Builds a G-code program one toolpath at a time in a given dialect.
+///
+/// The header is written just before the first output, so a program that starts with a
+/// tool change gives the header access to that tool's variables.
+pub struct GCodeWriter<'a> {
+    post: &'a dyn PostProcessor,
+    vars: PostVariables,
+    lines: Vec<String>,
+    next_line_number: usize,
+    started: bool,
+    /// Whether the tool is already at safe height, so another retract is redundant.
+    at_safe_z: bool,
+    /// The modal feed rate last written.
+    feed: Option<f64>,
+    /// Speed the spindle is running at, if it has been started.
+    spindle: Option<f64>,
+}
+
+impl<'a> GCodeWriter<'a> {
+    /// A writer for a program in millimetres.
+    pub fn new(post: &'a dyn PostProcessor) -> Self {
+        Self {
+            post,
+            vars: PostVariables {
+                safe_z: post.safe_z(),
+                ..Default::default()
+            },
+            lines: Vec::new(),
+            next_line_number: post.line_number_step().unwrap_or(0),
+            started: false,
+            at_safe_z: false,
+            feed: None,
+            spindle: None,
+        }
+    }
+
+    /// A writer for a program in `units`. Toolpaths and tools are still given in
+    /// millimetres and converted as they are written; so is the dialect's safe height.
+    pub fn with_units(post: &'a dyn PostProcessor, units: Units) -> Self {
+        let mut writer = Self::new(post);
+        writer.vars.units = units;
+        if units != Units::Millimeters {
+            // Rounded up to a tenth, so a retract printed to one place never lands lower.
+            let safe_z = units.from_mm(post.safe_z());
+            writer.vars.safe_z = (safe_z * 10.0 - 1e-9).ceil() / 10.0;
+        }
+        writer
+    }
+
+    /// Add a comment line.
+    pub fn comment(&mut self, text: &str) {
+        self.start();
+        let line = self.post.comment(text);
+        self.lines.push(line);
+    }
+
+    /// Retract and swap to tool `number` (1-based), to cut with `feeds` (the first
+    /// toolpath's settings), falling back to the tool's own.
+    pub fn tool_change(&mut self, number: usize, tool: &Tool, feeds: &FeedSettings) {
+        self.vars.tool_number = number;
+        self.vars.tool_name = tool.name.clone();
+        self.vars.tool_diameter = self.vars.units.from_mm(tool.diameter);
+        self.vars.spindle_rpm = feeds.or(tool.feeds).spindle_rpm;
+        self.start();
+        self.retract();
+        self.comment(&format!("T{number} {}", tool.name));
+        for line in self.post.tool_change(&self.vars) {
+            self.push(line);
+        }
+        // Tool changes stop the spindle.
+        self.spindle = None;
+    }
+
+    /// Cut every path in `toolpath`, retracting to safe height after each one.
+    ///
+    /// Starts the spindle if the toolpath sets a speed. Each move is cut at the rate its
+    /// [`SegmentFeed`] picks.
+    pub fn toolpath(&mut self, toolpath: &Toolpath) {
+        self.start();
+        let post = self.post;
+
+        let feeds = toolpath.feeds;
+        let cut_rate = self.rate(feeds.feed_rate.unwrap_or(DEFAULT_FEED));
+        let plunge_rate = feeds.plunge_rate.map_or(cut_rate, |rate| self.rate(rate));
+        let ramp_rate = feeds.ramp_rate.map_or(plunge_rate, |rate| self.rate(rate));
+        let converted;
+        let toolpath = match self.vars.units {
+            Units::Millimeters => toolpath,
+            units => {
+                converted = toolpath_in_units(toolpath, units);
+                &converted
+            }
+        };
+        if let Some(rpm) = feeds.spindle_rpm {
+            if self.spindle != Some(rpm) {
+                self.vars.spindle_rpm = Some(rpm);
+                self.push(post.spindle_on(&self.vars));
+                self.spindle = Some(rpm);
+            }
+        }
+
+        if let Some(cycle) = &toolpath.drill {
+            self.drill(&toolpath.paths, cycle, plunge_rate);
+            return;
+        }
+
+        let mut current_level = None;
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            if path.is_empty() {
+                continue;
+            }
+
+            // Mark the start of each depth level
+            let level_index = toolpath.path_levels.get(index).copied();
+            if level_index != current_level {
+                if let Some(level) = toolpath.level_of(index) {
+                    let z = post.format_number(level.z);
+                    if level.finishing {
+                        self.comment(&format!("Finishing pass Z{z}"));
+                    } else {
+                        self.comment(&format!(
+                            "Level {}/{} Z{z}",
+                            level_index.unwrap_or_default() + 1,
+                            toolpath.levels.iter().filter(|l| !l.finishing).count(),
+                        ));
+                    }
+                }
+                current_level = level_index;
+            }
+
+            // Entry move (Section 2.3, lines 196-199)
+            let start = path[0];
+            self.push(post.rapid(start.0, start.1));
+            self.at_safe_z = false;
+            self.push(post.plunge(start.2, plunge_rate));
+            self.feed = Some(plunge_rate);
+
+            // Cutting moves (Section 2.3, lines 200-202)
+            // Generate G1 commands for remaining points, adding Z only where it changes.
+            // Fitted arcs replace the lines through their points where the dialect cuts
+            // arcs. Arc lines carry no feed, so a rate change is cut as a line to the next
+            // point and the arc picks up from there.
+            let fitted = match toolpath.arcs.get(index) {
+                Some(arcs) if toolpath.arcs.len() == toolpath.paths.len() => arcs.as_slice(),
+                _ => &[],
+            };
+            let mut arcs = fitted
+                .iter()
+                .filter(|arc| post.supports_arcs() && arc.start < arc.end && arc.end < path.len());
+            let mut arc = arcs.next();
+            let mut previous = start;
+            let mut next = 1;
+            let rate_to = |point: usize| match toolpath.segment_feed(index, point) {
+                SegmentFeed::Ramp => ramp_rate,
+                SegmentFeed::Plunge => plunge_rate,
+                _ => cut_rate,
+            };
+            while next < path.len() {
+                let point = path[next];
+                let rate = rate_to(next);
+
+                while arc.is_some_and(|arc| arc.end < next) {
+                    arc = arcs.next();
+                }
+                let same_rate = |arc: &&PathArc| {
+                    self.feed == Some(rate) && (next..=arc.end).all(|point| rate_to(point) == rate)
+                };
+                if let Some(arc) = arc.filter(|arc| arc.start < next && same_rate(arc)) {
+                    let end = path[arc.end];
+                    let offset = (arc.center.0 - previous.0, arc.center.1 - previous.1);
+                    if let Some(line) = post.arc(arc.clockwise, end, offset) {
+                        self.push(line);
+                        previous = end;
+                        next = arc.end + 1;
+                        continue;
+                    }
+                }
+
+                let feed = (self.feed != Some(rate)).then_some(rate);
+                self.feed = Some(rate);
+                let z = (point.2 != previous.2).then_some(point.2);
+                self.push(post.cut(point.0, point.1, z, feed));
+                previous = point;
+                next += 1;
+            }
+
+            // Exit move (Section 2.3, lines 203-204)
+            self.retract();
+        }
+    }
+
+    /// Drill each path as one hole, with the dialect's canned cycle where it has one and
+    /// expanded moves otherwise.
+    fn drill(&mut self, paths: &[Vec<(f64, f64, f64)>], cycle: &DrillCycle, rate: f64) {
+        let post = self.post;
+        let mut canned = false;
+        for path in paths {
+            let (Some(&top), Some(&bottom)) = (path.first(), path.last()) else {
+                continue;
+            };
+            self.retract();
+
+            let feed = (self.feed != Some(rate)).then_some(rate);
+            if let Some(line) = post.drill_cycle(bottom, top.2, cycle, feed) {
+                // The cycle returns to safe height on its own.
+                self.push(line);
+                self.feed = Some(rate);
+                canned = true;
+                continue;
+            }
+
+            self.push(post.rapid(top.0, top.1));
+            self.push(post.rapid_z(top.2));
+            self.at_safe_z = false;
+            let clearance = self.vars.units.from_mm(PECK_CLEARANCE);
+            let mut depth = top.2;
+            loop {
+                depth = match cycle.peck_depth {
+                    Some(peck) => (depth - peck).max(bottom.2),
+                    None => bottom.2,
+                };
+                self.push(post.plunge(depth, rate));
+                self.feed = Some(rate);
+                if depth <= bottom.2 {
+                    break;
+                }
+                // Clear the chips, then drop back to just above the bottom so far and
+                // feed the rest, in case chips have fallen into the hole.
+                self.push(post.rapid_z(top.2));
+                self.push(post.rapid_z((depth + clearance).min(top.2)));
+            }
+            if let Some(seconds) = cycle.dwell {
+                self.push(post.dwell(seconds));
+            }
+            self.retract();
+        }
+        if canned {
+            self.push(post.cancel_drill_cycle());
+        }
+    }
+
+    /// End the program with the dialect's footer.
+    pub fn finish(mut self) -> GCode {
+        self.start();
+        for line in self.post.footer(&self.vars) {
+            self.push(line);
+        }
+        GCode { lines: self.lines }
+    }
+
+    /// Write the header and move to safe height, once.
+    fn start(&mut self) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        for line in self.post.header(&self.vars) {
+            self.push(line);
+        }
+        self.retract();
+    }
+
+    fn retract(&mut self) {
+        if !self.at_safe_z {
+            self.push(self.post.retract(self.vars.safe_z));
+            self.at_safe_z = true;
+        }
+    }
+
+    /// A feed rate given in mm/min, in the program's units, rounded to the precision the
+    /// dialect prints so equal rates compare equal.
+    fn rate(&self, mm_per_min: f64) -> f64 {
+        let scale = 10f64.powi(self.post.feed_decimals() as i32);
+        (self.vars.units.from_mm(mm_per_min) * scale).round() / scale
+    }
+
+    fn push(&mut self, line: String) {
+        match self.post.line_number_step() {
+            Some(step) => {
+                self.lines
+                    .push(format!("N{} {line}", self.next_line_number));
+                self.next_line_number += step;
+            }
+            None => self.lines.push(line),
+        }
+    }
+}
+
+/// `toolpath` with its lengths converted from millimetres to `units`.
+fn toolpath_in_units(toolpath: &Toolpath, units: Units) -> Toolpath {
+    let mut converted = toolpath.clone();
+    for point in converted.paths.iter_mut().flatten() {
+        *point = (
+            units.from_mm(point.0),
+            units.from_mm(point.1),
+            units.from_mm(point.2),
+        );
+    }
+    for level in &mut converted.levels {
+        level.z = units.from_mm(level.z);
+    }
+    for tab in &mut converted.tabs {
+        *tab = (units.from_mm(tab.0), units.from_mm(tab.1));
+    }
+    for arc in converted.arcs.iter_mut().flatten() {
+        arc.center = (units.from_mm(arc.center.0), units.from_mm(arc.center.1));
+    }
+    if let Some(cycle) = &mut converted.drill {
+        cycle.peck_depth = cycle.peck_depth.map(|depth| units.from_mm(depth));
+    }
+    converted
+}
+
+/// Convert a single toolpath to G-code in the given dialect.
+pub fn post_process(post: &dyn PostProcessor, toolpath: &Toolpath) -> GCode {
+    let mut writer = GCodeWriter::new(post);
+    writer.toolpath(toolpath);
+    writer.finish()
+}
+
+/// Convert generic toolpath to Grbl-compatible G-code
+pub fn post_process_grbl(toolpath: &Toolpath) -> GCode {
+    post_process(&Grbl, toolpath)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DepthLevel;
+
+    #[test]
+    fn test_postprocessor_structure() {
+        let toolpath = Toolpath::new(vec![vec![
+            (0.0, 0.0, -5.0),
+            (100.0, 0.0, -5.0),
+            (100.0, 100.0, -5.0),
+        ]]);
+
+        let gcode = post_process_grbl(&toolpath);
+
+        // Verify header
+        assert_eq!(gcode.lines[0], "G90", "First command should be G90");
+        assert_eq!(gcode.lines[1], "G21", "Second command should be G21");
+        assert_eq!(gcode.lines[2], "G17", "Third command should be G17");
+        assert_eq!(
+            gcode.lines[3], "G0 Z10.0",
+            "Fourth command should be safe height"
+        );
+
+        // Verify entry move
+        assert!(
+            gcode.lines[4].starts_with("G0 X"),
+            "Should have rapid move to start"
+        );
+        assert!(
+            gcode.lines[5].starts_with("G1 Z"),
+            "Should have plunge move"
+        );
+
+        // Verify cutting moves
+        assert!(
+            gcode.lines[6].starts_with("G1 X"),
+            "Should have cutting move"
+        );
+
+        // Verify exit move
+        assert!(
+            gcode.lines.iter().any(|l| l == "G0 Z10.0"),
+            "Should have exit move to safe height"
+        );
+    }
+
+    #[test]
+    fn test_postprocessor_plunge_move() {
+        let toolpath = Toolpath::new(vec![vec![(10.0, 20.0, -5.0)]]);
+
+        let gcode = post_process_grbl(&toolpath);
+        let gcode_str = gcode.lines.join("\n");
+
+        assert!(gcode_str.contains("G1 Z"), "Should have plunge move");
+        assert!(gcode_str.contains("F100"), "Should have feed rate");
+    }
+
+    #[test]
+    fn test_postprocessor_marks_depth_levels() {
+        let levels = DepthLevel::schedule(4.0, 2.0, Some(0.5));
+        let toolpath = Toolpath {
+            paths: levels
+                .iter()
+                .map(|level| vec![(0.0, 0.0, level.z), (10.0, 0.0, level.z)])
+                .collect(),
+            path_levels: (0..levels.len()).collect(),
+            levels,
+            ..Default::default()
+        };
+
+        let gcode = post_process_grbl(&toolpath);
+        let comments: Vec<&String> = gcode.lines.iter().filter(|l| l.starts_with('(')).collect();
+
+        assert_eq!(
+            comments,
+            vec![
+                "(Level 1/2 Z-1.7500)",
+                "(Level 2/2 Z-3.5000)",
+                "(Finishing pass Z-4.0000)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_postprocessor_emits_z_changes_within_path() {
+        let toolpath = Toolpath::new(vec![vec![
+            (0.0, 0.0, -5.0),
+            (10.0, 0.0, -5.0),
+            (10.0, 0.0, -3.0),
+            (20.0, 0.0, -3.0),
+        ]]);
+
+        let gcode = post_process_grbl(&toolpath);
+
+        assert!(gcode.lines.contains(&"G1 X10.0000 Y0.0000".to_string()));
+        assert!(gcode
+            .lines
+            .contains(&"G1 X10.0000 Y0.0000 Z-3.0000".to_string()));
+        assert!(gcode.lines.contains(&"G1 X20.0000 Y0.0000".to_string()));
+    }
+}
@@ -0,0 +1,738 @@
+use super::{PostProcessor, PostVariables};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extensions recognised as post definitions.
+const POST_EXTENSIONS: &[&str] = &["toml", "post", "txt"];
+
+/// A placeholder that a template can substitute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var {
+    ToolName,
+    ToolNumber,
+    ToolDiameter,
+    SpindleRpm,
+    SafeZ,
+    Units,
+    Text,
+    X,
+    Y,
+    Z,
+    I,
+    J,
+    Feed,
+}
+
+impl Var {
+    const ALL: [Var; 13] = [
+        Var::ToolName,
+        Var::ToolNumber,
+        Var::ToolDiameter,
+        Var::SpindleRpm,
+        Var::SafeZ,
+        Var::Units,
+        Var::Text,
+        Var::X,
+        Var::Y,
+        Var::Z,
+        Var::I,
+        Var::J,
+        Var::Feed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Var::ToolName => "tool_name",
+            Var::ToolNumber => "tool_number",
+            Var::ToolDiameter => "tool_diameter",
+            Var::SpindleRpm => "spindle_rpm",
+            Var::SafeZ => "safe_z",
+            Var::Units => "units",
+            Var::Text => "text",
+            Var::X => "x",
+            Var::Y => "y",
+            Var::Z => "z",
+            Var::I => "i",
+            Var::J => "j",
+            Var::Feed => "feed",
+        }
+    }
+}
+
+/// Variables available in header, footer and tool change blocks.
+const PROGRAM_VARS: &[Var] = &[
+    Var::ToolName,
+    Var::ToolNumber,
+    Var::ToolDiameter,
+    Var::SpindleRpm,
+    Var::SafeZ,
+    Var::Units,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Var(Var),
+}
+
+/// One output line with `{variable}` placeholders.
+#[derive(Debug, Clone, PartialEq)]
+struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    /// Parse a template line; `{{` and `}}` are literal braces.
+    fn parse(source: &str, line: usize, key: &str, allowed: &[Var]) -> Result<Self> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(anyhow!("line {line}: unclosed `{{` in `{key}`")),
+                        }
+                    }
+                    let name = name.trim();
+                    let var = Var::ALL
+                        .into_iter()
+                        .find(|var| var.name() == name)
+                        .ok_or_else(|| anyhow!("line {line}: unknown variable `{{{name}}}`"))?;
+                    if !allowed.contains(&var) {
+                        return Err(anyhow!(
+                            "line {line}: `{{{name}}}` is not available in `{key}`"
+                        ));
+                    }
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Var(var));
+                }
+                '}' => return Err(anyhow!("line {line}: unmatched `}}` in `{key}`")),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(Self { pieces })
+    }
+
+    /// A built-in default, known to be valid.
+    fn builtin(source: &str) -> Self {
+        Self::parse(source, 0, "default", &Var::ALL).expect("valid built-in template")
+    }
+
+    fn render(&self, value: impl Fn(Var) -> String) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Var(var) => value(*var),
+            })
+            .collect()
+    }
+}
+
+/// A raw setting value with the line(s) it came from.
+enum Value {
+    /// String contents, one entry per source line.
+    Text(Vec<(String, usize)>),
+    Number(f64),
+    Bool(bool),
+}
+
+/// A postprocessor defined by a user template file.
+///
+/// Definitions use a small TOML subset: `key = value` lines, `#` comments, quoted strings,
+/// `"""` multi-line strings, numbers and booleans.
+///
+/// ```toml
+/// name = "router-vacuum"
+/// decimals = 3
+/// header = """
+/// G90 {units}
+/// M7 (vacuum table on)
+/// """
+/// tool_change = """
+/// M5
+/// M0 (Load {tool_name})
+/// M3 S{spindle_rpm}
+/// """
+/// ```
+///
+/// Settings: `name`, `decimals`, `feed_decimals`, `line_numbers` (step, 0 for none), `arcs`,
+/// `canned_cycles` (G81/G82/G83 drilling, off by default), `safe_z` (mm),
+/// `comment` (`{text}`), `header`, `footer` and `tool_change` blocks (`{tool_name}`,
+/// `{tool_number}`, `{tool_diameter}`, `{spindle_rpm}`, `{safe_z}`, and `{units}`, G20 or
+/// G21 for the units the program is written in), the one-line
+/// `spindle_on` (same variables), and the move formats `rapid` (`{x}` `{y}`), `plunge`
+/// (`{z}` `{feed}`), `cut` (`{x}` `{y}`), `cut_z` (adds `{z}`), `feed` (`{feed}`, appended to
+/// cuts when the rate changes), `retract` (`{safe_z}`), `arc_cw` and `arc_ccw` (`{x}` `{y}`
+/// `{z}` `{i}` `{j}`).
+#[derive(Debug, Clone)]
+pub struct TemplatePost {
+    name: String,
+    decimals: usize,
+    feed_decimals: usize,
+    line_number_step: Option<usize>,
+    arcs: bool,
+    canned_cycles: bool,
+    safe_z: f64,
+    comment: Template,
+    header: Vec<Template>,
+    footer: Vec<Template>,
+    tool_change: Vec<Template>,
+    spindle_on: Template,
+    rapid: Template,
+    plunge: Template,
+    feed: Template,
+    cut: Template,
+    cut_z: Template,
+    retract: Template,
+    arc_cw: Template,
+    arc_ccw: Template,
+}
+
+impl TemplatePost {
+    /// Parse a definition. `default_name` is used when the file does not set `name`.
+    pub fn parse(source: &str, default_name: &str) -> Result<Self> {
+        let mut post = Self {
+            name: default_name.to_string(),
+            decimals: 4,
+            feed_decimals: 1,
+            line_number_step: None,
+            arcs: true,
+            canned_cycles: false,
+            safe_z: 10.0,
+            comment: Template::builtin("({text})"),
+            header: vec![
+                Template::builtin("G90"),
+                Template::builtin("{units}"),
+                Template::builtin("G17"),
+            ],
+            footer: vec![Template::builtin("M5"), Template::builtin("M2")],
+            tool_change: vec![Template::builtin("M5"), Template::builtin("M0")],
+            spindle_on: Template::builtin("M3 S{spindle_rpm}"),
+            rapid: Template::builtin("G0 X{x} Y{y}"),
+            plunge: Template::builtin("G1 Z{z} F{feed}"),
+            feed: Template::builtin("F{feed}"),
+            cut: Template::builtin("G1 X{x} Y{y}"),
+            cut_z: Template::builtin("G1 X{x} Y{y} Z{z}"),
+            retract: Template::builtin("G0 Z{safe_z}"),
+            arc_cw: Template::builtin("G2 X{x} Y{y} Z{z} I{i} J{j}"),
+            arc_ccw: Template::builtin("G3 X{x} Y{y} Z{z} I{i} J{j}"),
+        };
+
+        let mut seen = HashSet::new();
+        for (key, value, line) in parse_settings(source)? {
+            if !seen.insert(key.clone()) {
+                return Err(anyhow!("line {line}: `{key}` is set more than once"));
+            }
+            match key.as_str() {
+                "name" => post.name = single_line(&key, value, line)?.0,
+                "decimals" | "feed_decimals" => match value {
+                    Value::Number(n) if n.fract() == 0.0 && (0.0..=8.0).contains(&n) => {
+                        if key == "decimals" {
+                            post.decimals = n as usize;
+                        } else {
+                            post.feed_decimals = n as usize;
+                        }
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "line {line}: `{key}` must be a whole number from 0 to 8"
+                        ))
+                    }
+                },
+                "line_numbers" => match value {
+                    Value::Number(n) if n.fract() == 0.0 && n >= 0.0 => {
+                        post.line_number_step = (n > 0.0).then_some(n as usize)
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "line {line}: `line_numbers` must be a whole number step, or 0"
+                        ))
+                    }
+                },
+                "arcs" => match value {
+                    Value::Bool(b) => post.arcs = b,
+                    _ => return Err(anyhow!("line {line}: `arcs` must be true or false")),
+                },
+                "canned_cycles" => match value {
+                    Value::Bool(b) => post.canned_cycles = b,
+                    _ => {
+                        return Err(anyhow!(
+                            "line {line}: `canned_cycles` must be true or false"
+                        ))
+                    }
+                },
+                "safe_z" => match value {
+                    Value::Number(n) => post.safe_z = n,
+                    _ => return Err(anyhow!("line {line}: `safe_z` must be a number")),
+                },
+                "header" => post.header = block(&key, value, line, PROGRAM_VARS)?,
+                "footer" => post.footer = block(&key, value, line, PROGRAM_VARS)?,
+                "tool_change" => post.tool_change = block(&key, value, line, PROGRAM_VARS)?,
+                "comment" => post.comment = move_format(&key, value, line, &[Var::Text])?,
+                "rapid" => post.rapid = move_format(&key, value, line, &[Var::X, Var::Y])?,
+                "plunge" => post.plunge = move_format(&key, value, line, &[Var::Z, Var::Feed])?,
+                "feed" => post.feed = move_format(&key, value, line, &[Var::Feed])?,
+                "cut" => post.cut = move_format(&key, value, line, &[Var::X, Var::Y])?,
+                "cut_z" => post.cut_z = move_format(&key, value, line, &[Var::X, Var::Y, Var::Z])?,
+                "spindle_on" => post.spindle_on = move_format(&key, value, line, PROGRAM_VARS)?,
+                "retract" => post.retract = move_format(&key, value, line, &[Var::SafeZ])?,
+                "arc_cw" | "arc_ccw" => {
+                    let template =
+                        move_format(&key, value, line, &[Var::X, Var::Y, Var::Z, Var::I, Var::J])?;
+                    if key == "arc_cw" {
+                        post.arc_cw = template;
+                    } else {
+                        post.arc_ccw = template;
+                    }
+                }
+                _ => return Err(anyhow!("line {line}: unknown setting `{key}`")),
+            }
+        }
+
+        Ok(post)
+    }
+
+    /// Load a definition file, naming the post after the file unless it sets `name`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("read post definition {}", path.display()))?;
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("custom");
+        Self::parse(&source, stem)
+            .with_context(|| format!("invalid post definition {}", path.display()))
+    }
+
+    fn program_value(&self, var: Var, vars: &PostVariables) -> String {
+        match var {
+            Var::ToolName => vars.tool_name.clone(),
+            Var::ToolNumber => vars.tool_number.to_string(),
+            Var::ToolDiameter => self.format_number(vars.tool_diameter),
+            Var::SpindleRpm => format!("{:.0}", vars.spindle_rpm.unwrap_or(0.0)),
+            Var::SafeZ => self.format_number(vars.safe_z),
+            Var::Units => vars.units.gcode().to_string(),
+            // Parsing only admits program variables in program blocks.
+            _ => String::new(),
+        }
+    }
+
+    fn render_block(&self, block: &[Template], vars: &PostVariables) -> Vec<String> {
+        block
+            .iter()
+            .map(|template| template.render(|var| self.program_value(var, vars)))
+            .collect()
+    }
+
+    fn render_move(&self, template: &Template, values: &[(Var, f64)]) -> String {
+        template.render(|var| {
+            let value = values
+                .iter()
+                .find(|(v, _)| *v == var)
+                .map(|(_, value)| *value)
+                .unwrap_or_default();
+            match var {
+                Var::Feed => self.format_feed(value),
+                _ => self.format_number(value),
+            }
+        })
+    }
+}
+
+impl PostProcessor for TemplatePost {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn header(&self, vars: &PostVariables) -> Vec<String> {
+        self.render_block(&self.header, vars)
+    }
+
+    fn footer(&self, vars: &PostVariables) -> Vec<String> {
+        self.render_block(&self.footer, vars)
+    }
+
+    fn comment(&self, text: &str) -> String {
+        // Parenthesised comments cannot nest, so swap inner parentheses for brackets.
+        let text = if self.comment.render(|_| String::new()).contains('(') {
+            text.replace('(', "[").replace(')', "]")
+        } else {
+            text.to_string()
+        };
+        self.comment.render(|_| text.clone())
+    }
+
+    fn decimals(&self) -> usize {
+        self.decimals
+    }
+
+    fn feed_decimals(&self) -> usize {
+        self.feed_decimals
+    }
+
+    fn line_number_step(&self) -> Option<usize> {
+        self.line_number_step
+    }
+
+    fn rapid(&self, x: f64, y: f64) -> String {
+        self.render_move(&self.rapid, &[(Var::X, x), (Var::Y, y)])
+    }
+
+    fn plunge(&self, z: f64, feed: f64) -> String {
+        self.render_move(&self.plunge, &[(Var::Z, z), (Var::Feed, feed)])
+    }
+
+    fn cut(&self, x: f64, y: f64, z: Option<f64>, feed: Option<f64>) -> String {
+        let mut line = match z {
+            Some(z) => self.render_move(&self.cut_z, &[(Var::X, x), (Var::Y, y), (Var::Z, z)]),
+            None => self.render_move(&self.cut, &[(Var::X, x), (Var::Y, y)]),
+        };
+        if let Some(feed) = feed {
+            line.push(' ');
+            line.push_str(&self.render_move(&self.feed, &[(Var::Feed, feed)]));
+        }
+        line
+    }
+
+    fn spindle_on(&self, vars: &PostVariables) -> String {
+        self.spindle_on.render(|var| self.program_value(var, vars))
+    }
+
+    fn retract(&self, z: f64) -> String {
+        self.render_move(&self.retract, &[(Var::SafeZ, z)])
+    }
+
+    fn supports_arcs(&self) -> bool {
+        self.arcs
+    }
+
+    fn arc(
+        &self,
+        clockwise: bool,
+        end: (f64, f64, f64),
+        center_offset: (f64, f64),
+    ) -> Option<String> {
+        if !self.arcs {
+            return None;
+        }
+        let template = if clockwise {
+            &self.arc_cw
+        } else {
+            &self.arc_ccw
+        };
+        Some(self.render_move(
+            template,
+            &[
+                (Var::X, end.0),
+                (Var::Y, end.1),
+                (Var::Z, end.2),
+                (Var::I, center_offset.0),
+                (Var::J, center_offset.1),
+            ],
+        ))
+    }
+
+    fn supports_canned_cycles(&self) -> bool {
+        self.canned_cycles
+    }
+
+    fn tool_change(&self, vars: &PostVariables) -> Vec<String> {
+        self.render_block(&self.tool_change, vars)
+    }
+
+    fn safe_z(&self) -> f64 {
+        self.safe_z
+    }
+}
+
+/// Resolve the user post directory (`~/.rcarve/posts`). The directory may not exist.
+pub fn user_posts_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("could not determine home directory"))?;
+    Ok(home.join(".rcarve").join("posts"))
+}
+
+/// Load every post definition in `dir`, sorted by file name.
+///
+/// Each file gets its own result so one broken definition does not hide the others.
+pub fn load_user_postprocessors<P: AsRef<Path>>(
+    dir: P,
+) -> Result<Vec<(PathBuf, Result<TemplatePost>)>> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("read post directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| POST_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let post = TemplatePost::load(&path);
+            (path, post)
+        })
+        .collect())
+}
+
+fn single_line(key: &str, value: Value, line: usize) -> Result<(String, usize)> {
+    match value {
+        Value::Text(mut lines) if lines.len() == 1 => Ok(lines.remove(0)),
+        Value::Text(_) => Err(anyhow!("line {line}: `{key}` must be a single line")),
+        _ => Err(anyhow!("line {line}: `{key}` must be a quoted string")),
+    }
+}
+
+fn move_format(key: &str, value: Value, line: usize, allowed: &[Var]) -> Result<Template> {
+    let (text, line) = single_line(key, value, line)?;
+    Template::parse(&text, line, key, allowed)
+}
+
+fn block(key: &str, value: Value, line: usize, allowed: &[Var]) -> Result<Vec<Template>> {
+    match value {
+        Value::Text(lines) => lines
+            .iter()
+            .filter(|(text, _)| !text.trim().is_empty())
+            .map(|(text, line)| Template::parse(text.trim(), *line, key, allowed))
+            .collect(),
+        _ => Err(anyhow!("line {line}: `{key}` must be a quoted string")),
+    }
+}
+
+/// Split a definition into `(key, value, line)` settings.
+fn parse_settings(source: &str) -> Result<Vec<(String, Value, usize)>> {
+    let mut settings = Vec::new();
+    let mut lines = source.lines().enumerate().map(|(i, text)| (i + 1, text));
+
+    while let Some((line, text)) = lines.next() {
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with('[') {
+            return Err(anyhow!(
+                "line {line}: tables are not supported; use `key = value` settings"
+            ));
+        }
+        let (key, rest) = trimmed
+            .split_once('=')
+            .ok_or_else(|| anyhow!("line {line}: expected `key = value`"))?;
+        let key = key.trim();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(anyhow!("line {line}: invalid setting name `{key}`"));
+        }
+        let rest = rest.trim();
+
+        let value = if let Some(body) = rest.strip_prefix("\"\"\"") {
+            if let Some((content, _)) = body.split_once("\"\"\"") {
+                Value::Text(vec![(content.to_string(), line)])
+            } else {
+                let mut block = Vec::new();
+                if !body.trim().is_empty() {
+                    block.push((body.to_string(), line));
+                }
+                loop {
+                    let (block_line, block_text) = lines.next().ok_or_else(|| {
+                        anyhow!("line {line}: multi-line string for `{key}` is never closed")
+                    })?;
+                    if let Some((content, _)) = block_text.split_once("\"\"\"") {
+                        if !content.trim().is_empty() {
+                            block.push((content.to_string(), block_line));
+                        }
+                        break;
+                    }
+                    block.push((block_text.to_string(), block_line));
+                }
+                Value::Text(block)
+            }
+        } else if rest.starts_with('"') || rest.starts_with('\'') {
+            Value::Text(vec![(parse_quoted(rest, line)?, line)])
+        } else {
+            let bare = rest.split('#').next().unwrap_or_default().trim();
+            match bare {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::Number(bare.parse().map_err(|_| {
+                    anyhow!(
+                        "line {line}: expected a quoted string, number or true/false for `{key}`"
+                    )
+                })?),
+            }
+        };
+
+        settings.push((key.to_string(), value, line));
+    }
+
+    Ok(settings)
+}
+
+/// Parse a `"basic"` (with escapes) or `'literal'` string followed by an optional comment.
+fn parse_quoted(text: &str, line: usize) -> Result<String> {
+    let mut chars = text.chars();
+    let quote = chars.next().unwrap_or('"');
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            None => return Err(anyhow!("line {line}: unterminated string")),
+            Some(c) if c == quote => break,
+            Some('\\') if quote == '"' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                Some(c) => return Err(anyhow!("line {line}: unknown escape `\\{c}`")),
+                None => return Err(anyhow!("line {line}: unterminated string")),
+            },
+            Some(c) => value.push(c),
+        }
+    }
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(anyhow!(
+            "line {line}: unexpected text after string: `{rest}`"
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VACUUM_POST: &str = r#"
+# Router with a vacuum table
+name = "vacuum"
+decimals = 2
+line_numbers = 5
+safe_z = 15
+
+header = """
+G90 G21
+M7 (vacuum on, {tool_name})
+"""
+footer = """
+M9
+M2
+"""
+tool_change = """
+M5
+M0 (load T{tool_number} {tool_name} D{tool_diameter})
+M3 S{spindle_rpm}
+"""
+comment = "; {text}"
+feed = "F{feed}"
+"#;
+
+    #[test]
+    fn test_template_post_renders_settings() {
+        let post = TemplatePost::parse(VACUUM_POST, "fallback").unwrap();
+        assert_eq!(post.name(), "vacuum");
+        assert_eq!(post.line_number_step(), Some(5));
+        assert_eq!(post.safe_z(), 15.0);
+
+        let vars = PostVariables {
+            tool_number: 2,
+            tool_name: "1/4in Downcut".to_string(),
+            tool_diameter: 6.35,
+            spindle_rpm: Some(18000.0),
+            safe_z: 15.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            post.header(&vars),
+            vec!["G90 G21", "M7 (vacuum on, 1/4in Downcut)"]
+        );
+        assert_eq!(
+            post.tool_change(&vars),
+            vec!["M5", "M0 (load T2 1/4in Downcut D6.35)", "M3 S18000"]
+        );
+        assert_eq!(post.footer(&vars), vec!["M9", "M2"]);
+        assert_eq!(post.comment("Level (1)"), "; Level (1)");
+        assert_eq!(post.spindle_on(&vars), "M3 S18000");
+        assert_eq!(
+            post.cut(1.0, 2.5, None, Some(800.0)),
+            "G1 X1.00 Y2.50 F800.0"
+        );
+        assert_eq!(
+            post.cut(1.0, 2.5, Some(-1.0), None),
+            "G1 X1.00 Y2.50 Z-1.00"
+        );
+        assert_eq!(post.retract(15.0), "G0 Z15.00");
+    }
+
+    #[test]
+    fn test_template_errors_report_line_numbers() {
+        let unknown_variable = "name = \"x\"\n\nheader = \"\"\"\nG90\nM3 S{spindle}\n\"\"\"\n";
+        let err = TemplatePost::parse(unknown_variable, "x").unwrap_err();
+        assert_eq!(err.to_string(), "line 5: unknown variable `{spindle}`");
+
+        let wrong_context = "rapid = \"G0 X{x} Y{y} ({tool_name})\"";
+        let err = TemplatePost::parse(wrong_context, "x").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: `{tool_name}` is not available in `rapid`"
+        );
+
+        let unclosed = "decimals = 3\nfooter = \"\"\"\nM2\n";
+        let err = TemplatePost::parse(unclosed, "x").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: multi-line string for `footer` is never closed"
+        );
+
+        let unknown_key = "\n\nspindle = \"M3\"";
+        let err = TemplatePost::parse(unknown_key, "x").unwrap_err();
+        assert_eq!(err.to_string(), "line 3: unknown setting `spindle`");
+    }
+
+    #[test]
+    fn test_template_defaults_match_grbl() {
+        let post = TemplatePost::parse("", "plain").unwrap();
+        let grbl = super::super::Grbl;
+        let vars = PostVariables::default();
+        assert_eq!(post.name(), "plain");
+        assert_eq!(post.header(&vars), grbl.header(&vars));
+        assert_eq!(post.rapid(1.0, 2.0), grbl.rapid(1.0, 2.0));
+        assert_eq!(post.plunge(-1.0, 100.0), grbl.plunge(-1.0, 100.0));
+        assert_eq!(
+            post.arc(true, (1.0, 0.0, -1.0), (0.5, 0.0)),
+            grbl.arc(true, (1.0, 0.0, -1.0), (0.5, 0.0))
+        );
+        assert!(!post.supports_canned_cycles());
+
+        let drilling = TemplatePost::parse("canned_cycles = true", "drilling").unwrap();
+        let cycle = crate::types::DrillCycle::default();
+        assert_eq!(
+            drilling.drill_cycle((1.0, 2.0, -3.0), 1.0, &cycle, Some(90.0)),
+            Some("G98 G81 X1.0000 Y2.0000 Z-3.0000 R1.0000 F90.0".to_string())
+        );
+    }
+}
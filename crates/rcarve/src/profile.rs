@@ -1,16 +1,28 @@
-use crate::types::{CutSide, Tool, Toolpath};
+use crate::types::{CutDirection, CutSide, DepthLevel, Tool, Toolpath};
 use clipper2::{inflate, EndType, JoinType, Path, PathType, Polygon, Polygons, Vertex};
 
 /// Generate a 2D profile toolpath using polygon offsetting
 ///
 /// This function uses clipper2 to perform proper polygon offsetting (inflating/deflating)
-/// based on the tool diameter and cut side. The result is a 3D toolpath with the
-/// appropriate Z-depth.
+/// based on the tool diameter and cut side. The result is a 3D toolpath stepping
+/// down to the target depth in passes no deeper than the tool's `pass_depth`, climb milling.
 pub fn generate_profile_toolpath(
     input_poly: &[(f64, f64)],
     tool: &Tool,
     cut_side: &CutSide,
     target_depth: f64,
+) -> anyhow::Result<Toolpath> {
+    let levels = DepthLevel::schedule(target_depth, tool.pass_depth, None);
+    generate_profile_toolpath_with_levels(input_poly, tool, cut_side, CutDirection::Climb, &levels)
+}
+
+/// Generate a profile toolpath that repeats the offset contour at each depth level.
+pub fn generate_profile_toolpath_with_levels(
+    input_poly: &[(f64, f64)],
+    tool: &Tool,
+    cut_side: &CutSide,
+    direction: CutDirection,
+    levels: &[DepthLevel],
 ) -> anyhow::Result<Toolpath> {
     // Step 1: Calculate offset delta (Section 2.2, line 161)
     let radius = tool.diameter / 2.0;
@@ -43,37 +55,60 @@ pub fn generate_profile_toolpath(
         0.0,                    // Arc tolerance
     );
 
-    // Extract the first path from the result
-    // For a simple profile, we expect one polygon with one path
-    let offset_polygon = offset_polygons.polygons().first().ok_or_else(|| {
-        anyhow::anyhow!("No offset polygon generated - polygon may have collapsed")
-    })?;
-
-    let offset_path = offset_polygon
-        .paths()
-        .first()
-        .ok_or_else(|| anyhow::anyhow!("No offset path generated"))?;
-
-    // Step 4: Convert to 3D path (Section 2.2, lines 174-181)
-    // The offset_path vertices are in clipper2's internal format
-    // Extract x() and y() as f64, then add Z-coordinate
-    let target_z = -target_depth;
-    let path_3d: Vec<(f64, f64, f64)> = offset_path
-        .vertices()
-        .iter()
-        .map(|vertex| (vertex.x(), vertex.y(), target_z))
-        .collect();
+    // An inside offset can split into several loops where the shape necks down narrower
+    // than the tool, so every resulting loop is cut.
+    let mut contours: Vec<Vec<(f64, f64)>> = Vec::new();
+    for offset_polygon in offset_polygons.polygons() {
+        for offset_path in offset_polygon.paths() {
+            // Clipper2 keeps whatever winding the input had, so orient the loop explicitly.
+            // The part lies inside the loop unless we are cutting out a hole.
+            let mut contour: Vec<(f64, f64)> = offset_path
+                .vertices()
+                .iter()
+                .map(|vertex| (vertex.x(), vertex.y()))
+                .collect();
+            if contour.len() < 3 {
+                continue;
+            }
+            direction.orient(&mut contour, !matches!(cut_side, CutSide::Inside));
+            contours.push(contour);
+        }
+    }
+    if contours.is_empty() {
+        anyhow::bail!("No offset polygon generated - polygon may have collapsed");
+    }
+
+    // Step 4: Convert to 3D paths, one per loop and depth level (Section 2.2, lines 174-181)
+    // Add the level's Z-coordinate to every contour point
+    let mut toolpath = Toolpath {
+        levels: levels.to_vec(),
+        ..Default::default()
+    };
+    for (level_index, level) in levels.iter().enumerate() {
+        for contour in &contours {
+            let mut path_3d: Vec<(f64, f64, f64)> =
+                contour.iter().map(|(x, y)| (*x, *y, level.z)).collect();
+
+            // Close the loop so the last edge back to the start is cut
+            if let Some(&first) = path_3d.first() {
+                if path_3d.last() != Some(&first) {
+                    path_3d.push(first);
+                }
+            }
+
+            toolpath.paths.push(path_3d);
+            toolpath.path_levels.push(level_index);
+        }
+    }
 
     // Step 5: Return Toolpath struct
-    Ok(Toolpath {
-        paths: vec![path_3d],
-    })
+    Ok(toolpath)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::ToolType;
+    use crate::types::{FeedSettings, ToolType, Units};
 
     #[test]
     fn test_square_profile_offset() {
@@ -91,6 +126,9 @@ mod tests {
             stepover: 0.4,
             pass_depth: 5.0,
             tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
         };
 
         let result = generate_profile_toolpath(&square, &tool, &CutSide::Outside, 5.0);
@@ -106,4 +144,104 @@ mod tests {
         // This is a basic check - exact values depend on clipper2 implementation
         assert_eq!(first_point.2, -5.0, "Z should be negative target depth");
     }
+
+    #[test]
+    fn test_profile_steps_down_by_pass_depth() {
+        let square = vec![
+            (0.0, 0.0),
+            (50.0, 0.0),
+            (50.0, 50.0),
+            (0.0, 50.0),
+            (0.0, 0.0),
+        ];
+
+        let tool = Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        };
+
+        let levels = DepthLevel::schedule(5.0, tool.pass_depth, Some(0.5));
+        let toolpath = generate_profile_toolpath_with_levels(
+            &square,
+            &tool,
+            &CutSide::Inside,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap();
+
+        assert_eq!(toolpath.paths.len(), levels.len());
+        assert_eq!(toolpath.path_levels.len(), toolpath.paths.len());
+
+        let mut previous_z = 0.0;
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            let level = toolpath.level_of(index).unwrap();
+            assert!(path.iter().all(|point| point.2 == level.z));
+            assert!(previous_z - level.z <= tool.pass_depth + 1e-9);
+            previous_z = level.z;
+        }
+        assert!(
+            toolpath
+                .level_of(toolpath.paths.len() - 1)
+                .unwrap()
+                .finishing
+        );
+        assert_eq!(previous_z, -5.0);
+    }
+
+    /// Signed area of a closed 3D loop in XY; positive is counter-clockwise.
+    fn signed_area(path: &[(f64, f64, f64)]) -> f64 {
+        path.windows(2)
+            .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+            .sum::<f64>()
+            / 2.0
+    }
+
+    #[test]
+    fn test_profile_winding_follows_cut_direction() {
+        let square = vec![
+            (0.0, 0.0),
+            (50.0, 0.0),
+            (50.0, 50.0),
+            (0.0, 50.0),
+            (0.0, 0.0),
+        ];
+        let mut reversed = square.clone();
+        reversed.reverse();
+        let tool = Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 5.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        };
+        let levels = DepthLevel::schedule(3.0, tool.pass_depth, None);
+
+        // With an M3 spindle, climb milling runs clockwise around a part and
+        // counter-clockwise inside a hole, whatever the source winding.
+        let cases = [
+            (CutSide::Outside, CutDirection::Climb, false),
+            (CutSide::Outside, CutDirection::Conventional, true),
+            (CutSide::Inside, CutDirection::Climb, true),
+            (CutSide::Inside, CutDirection::Conventional, false),
+        ];
+        for (side, direction, expect_ccw) in cases {
+            for source in [&square, &reversed] {
+                let toolpath =
+                    generate_profile_toolpath_with_levels(source, &tool, &side, direction, &levels)
+                        .unwrap();
+                let ccw = signed_area(&toolpath.paths[0]) > 0.0;
+                assert_eq!(ccw, expect_ccw, "{side:?} {direction:?}");
+            }
+        }
+    }
 }
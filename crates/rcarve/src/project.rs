@@ -1,5 +1,7 @@
 use crate::geometry::{CurveId, RegionId, ShapeId, ShapeRegistry};
-use crate::{Operation, OperationTarget, Toolpath};
+use crate::{
+    Board, InlayPlug, MachineProfile, Operation, OperationTarget, Toolpath, Units, VCarveConfig,
+};
 use anyhow::{anyhow, Context, Result};
 use kurbo::Affine;
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,27 @@ pub struct Project {
     #[serde(default)]
     pub operation_states: Vec<OperationState>,
     pub toolpaths: Vec<Toolpath>,
+    /// How operations are sequenced when the project is exported.
+    #[serde(default)]
+    pub cut_order: CutOrder,
+    /// Machine speeds used to estimate how long the job takes.
+    #[serde(default)]
+    pub machine: MachineProfile,
+    /// Units the project is laid out in and its G-code is written in. Dimensions are
+    /// still stored in millimetres.
+    #[serde(default)]
+    pub units: Units,
+}
+
+/// How the operations of a project are sequenced for cutting.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CutOrder {
+    /// Cut everything inside a profile before the profile itself, so parts and windows
+    /// stay held by the stock until their features are done.
+    #[default]
+    InsideOut,
+    /// Cut operations exactly in list order.
+    Listed,
 }
 
 impl Project {
@@ -45,6 +68,9 @@ impl Project {
             operations: Vec::new(),
             operation_states: Vec::new(),
             toolpaths: Vec::new(),
+            cut_order: CutOrder::default(),
+            machine: MachineProfile::default(),
+            units: Units::default(),
         }
     }
 
@@ -81,6 +107,7 @@ impl Project {
             svg_imports: self.imported_svgs.len(),
             operations: self.operations.len(),
             toolpaths: self.toolpaths.len(),
+            units: self.units,
         }
     }
 
@@ -92,6 +119,52 @@ impl Project {
         self.operations.len() - 1
     }
 
+    /// Append both halves of a V-carve inlay: `pocket`, then the mirrored plug that fills
+    /// it, carved with the same tools down to `plug_flat_depth`. Returns their indices.
+    pub fn add_inlay(
+        &mut self,
+        pocket: Operation,
+        plug: InlayPlug,
+        plug_flat_depth: f64,
+    ) -> Result<(usize, usize)> {
+        let Operation::VCarve {
+            config,
+            tool_index,
+            targets,
+            clearance_tool_index,
+            feeds,
+            inlay_plug: None,
+        } = &pocket
+        else {
+            return Err(anyhow!("An inlay pocket must be a V-carve"));
+        };
+        let pocket_depth = config
+            .flat_depth
+            .ok_or_else(|| anyhow!("An inlay pocket needs a flat depth"))?;
+        if plug.start_depth >= pocket_depth {
+            return Err(anyhow!(
+                "The plug start depth must be shallower than the {pocket_depth}mm pocket, or the plug bottoms out"
+            ));
+        }
+        if plug_flat_depth <= plug.start_depth {
+            return Err(anyhow!(
+                "The plug flat depth must be deeper than its start depth"
+            ));
+        }
+        let plug = Operation::VCarve {
+            config: VCarveConfig {
+                flat_depth: Some(plug_flat_depth),
+                ..*config
+            },
+            tool_index: *tool_index,
+            targets: targets.clone(),
+            clearance_tool_index: *clearance_tool_index,
+            feeds: *feeds,
+            inlay_plug: Some(plug),
+        };
+        Ok((self.add_operation(pocket), self.add_operation(plug)))
+    }
+
     /// Replace the operation at `index`.
     pub fn update_operation(&mut self, index: usize, operation: Operation) -> Result<()> {
         let slot = self
@@ -122,6 +195,14 @@ impl Project {
             .collect()
     }
 
+    /// The boards the operations cut, the stock first.
+    pub fn boards(&self) -> Vec<Board> {
+        [Board::Stock, Board::InlayPlug]
+            .into_iter()
+            .filter(|board| self.operations.iter().any(|op| op.board() == *board))
+            .collect()
+    }
+
     /// Summaries with current toolpath status.
     pub fn operations_with_status(&self) -> Vec<(OperationSummary, ToolpathStatus)> {
         self.operation_summaries()
@@ -292,7 +373,9 @@ impl Project {
                     Operation::Pocket { target, .. } => {
                         Self::operation_target_uses_curves(target, curve_ids)
                     }
-                    Operation::VCarve { targets, .. } => {
+                    Operation::VCarve { targets, .. }
+                    | Operation::Drill { targets, .. }
+                    | Operation::Bore { targets, .. } => {
                         Self::operation_target_uses_curves(targets, curve_ids)
                     }
                 };
@@ -347,6 +430,15 @@ impl StockSpec {
             origin: None,
         }
     }
+
+    /// Stock with its size given in `units`.
+    pub fn with_units(width: f64, height: f64, thickness: f64, units: Units) -> Self {
+        Self::new(
+            units.to_mm(width),
+            units.to_mm(height),
+            units.to_mm(thickness),
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -359,6 +451,9 @@ pub struct ProjectSummary {
     pub svg_imports: usize,
     pub operations: usize,
     pub toolpaths: usize,
+    /// Units the project is laid out in; the sizes above are in millimetres regardless.
+    #[serde(default)]
+    pub units: Units,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -463,6 +558,9 @@ pub enum OperationKind {
     Profile,
     Pocket,
     VCarve,
+    InlayPlug,
+    Drill,
+    Bore,
 }
 
 #[derive(Debug, Clone)]
@@ -482,6 +580,12 @@ impl OperationSummary {
                 cut_side: _,
                 tool_index,
                 targets,
+                finish_pass_depth: _,
+                tabs: _,
+                entry: _,
+                direction: _,
+                leads: _,
+                feeds: _,
             } => Self {
                 index,
                 kind: OperationKind::Profile,
@@ -493,6 +597,12 @@ impl OperationSummary {
                 target_depth: _,
                 tool_index,
                 target,
+                finish_pass_depth: _,
+                entry: _,
+                direction: _,
+                feeds: _,
+                strategy: _,
+                rest_tool_index: _,
             } => Self {
                 index,
                 kind: OperationKind::Pocket,
@@ -501,17 +611,52 @@ impl OperationSummary {
                 clearance_tool_index: None,
             },
             Operation::VCarve {
-                target_depth: _,
+                config: _,
                 tool_index,
                 targets,
                 clearance_tool_index,
+                feeds: _,
+                inlay_plug,
             } => Self {
                 index,
-                kind: OperationKind::VCarve,
+                kind: if inlay_plug.is_some() {
+                    OperationKind::InlayPlug
+                } else {
+                    OperationKind::VCarve
+                },
                 target_count: count_targets(targets),
                 primary_tool_index: *tool_index,
                 clearance_tool_index: *clearance_tool_index,
             },
+            Operation::Drill {
+                target_depth: _,
+                tool_index,
+                targets,
+                peck_depth: _,
+                dwell: _,
+                feeds: _,
+            } => Self {
+                index,
+                kind: OperationKind::Drill,
+                target_count: count_targets(targets),
+                primary_tool_index: *tool_index,
+                clearance_tool_index: None,
+            },
+            Operation::Bore {
+                target_depth: _,
+                tool_index,
+                targets,
+                cut_side: _,
+                pitch: _,
+                direction: _,
+                feeds: _,
+            } => Self {
+                index,
+                kind: OperationKind::Bore,
+                target_count: count_targets(targets),
+                primary_tool_index: *tool_index,
+                clearance_tool_index: None,
+            },
         }
     }
 }
@@ -0,0 +1,449 @@
+use crate::project::{Project, StockSpec, ToolpathStatus};
+use crate::toolpath_generation::operation_cut_order;
+use crate::types::{Board, Tool, ToolType, Toolpath};
+use crate::ToolLibrary;
+use anyhow::{anyhow, Result};
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+/// Grids larger than this are refused rather than allocated (cells).
+const MAX_CELLS: usize = 50_000_000;
+
+/// The stock as a grid of material heights, cut down by sweeping tools along toolpaths.
+///
+/// Each cell holds the height of the material at its centre, from the stock top down to
+/// the stock bottom, where cutting stops (a through cut).
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    /// XY of the corner of the first cell, the low corner of the stock.
+    pub origin: (f64, f64),
+    /// Cell size (mm).
+    pub resolution: f64,
+    /// Number of cells along X.
+    pub width_steps: usize,
+    /// Number of cells along Y.
+    pub height_steps: usize,
+    /// Z of the uncut stock top.
+    pub top: f64,
+    /// Z of the stock bottom.
+    pub bottom: f64,
+    /// Material height of each cell, row by row: `data[y * width_steps + x]`.
+    pub data: Vec<f64>,
+}
+
+/// A triangle mesh of a heightmap's top surface, ready for a 3D view.
+#[derive(Debug, Clone, Default)]
+pub struct HeightmapMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// Three vertex indices per triangle, counter-clockwise seen from above.
+    pub indices: Vec<u32>,
+}
+
+/// A tool's cutting surface: how far above its tip it cuts at each distance from its axis.
+struct ToolShape {
+    radius: f64,
+    profile: ToolProfile,
+}
+
+enum ToolProfile {
+    Flat,
+    Cone { rise_per_mm: f64 },
+    Sphere,
+}
+
+impl ToolShape {
+    fn new(tool: &Tool, stock_depth: f64) -> Result<Self> {
+        match tool.tool_type {
+            ToolType::Endmill { diameter } | ToolType::Ballnose { diameter } => {
+                if diameter <= 0.0 {
+                    return Err(anyhow!("{} has no diameter to simulate", tool.name));
+                }
+                let profile = match tool.tool_type {
+                    ToolType::Ballnose { .. } => ToolProfile::Sphere,
+                    _ => ToolProfile::Flat,
+                };
+                Ok(Self {
+                    radius: diameter / 2.0,
+                    profile,
+                })
+            }
+            ToolType::VBit { angle_degrees } => {
+                if angle_degrees <= 0.0 || angle_degrees >= 180.0 {
+                    return Err(anyhow!("{} has no usable V angle", tool.name));
+                }
+                let tan_half = (angle_degrees.to_radians() / 2.0).tan();
+                // Without a diameter, the cone only needs to reach through the stock.
+                let radius = if tool.diameter > 0.0 {
+                    tool.diameter / 2.0
+                } else {
+                    stock_depth * tan_half
+                };
+                Ok(Self {
+                    radius,
+                    profile: ToolProfile::Cone {
+                        rise_per_mm: 1.0 / tan_half,
+                    },
+                })
+            }
+        }
+    }
+
+    /// Height of the cutting surface above the tip at `distance` from the axis.
+    fn height_at(&self, distance: f64) -> Option<f64> {
+        if distance > self.radius {
+            return None;
+        }
+        Some(match self.profile {
+            ToolProfile::Flat => 0.0,
+            ToolProfile::Cone { rise_per_mm } => distance * rise_per_mm,
+            ToolProfile::Sphere => {
+                self.radius - (self.radius * self.radius - distance * distance).sqrt()
+            }
+        })
+    }
+}
+
+impl Heightmap {
+    /// Uncut stock sampled every `resolution` mm.
+    pub fn new(stock: &StockSpec, resolution: f64) -> Result<Self> {
+        if resolution.is_nan() || resolution <= 0.0 {
+            return Err(anyhow!("Simulation resolution must be positive"));
+        }
+        if stock.width <= 0.0 || stock.height <= 0.0 || stock.thickness <= 0.0 {
+            return Err(anyhow!("Stock dimensions must be positive"));
+        }
+        let width_steps = (stock.width / resolution).ceil() as usize;
+        let height_steps = (stock.height / resolution).ceil() as usize;
+        if width_steps.saturating_mul(height_steps) > MAX_CELLS {
+            return Err(anyhow!(
+                "A {resolution}mm grid over {}x{}mm stock is too fine to simulate",
+                stock.width,
+                stock.height
+            ));
+        }
+
+        let (x, y, top) = stock.origin.unwrap_or_default();
+        Ok(Self {
+            origin: (x, y),
+            resolution,
+            width_steps,
+            height_steps,
+            top,
+            bottom: top - stock.thickness,
+            data: vec![top; width_steps * height_steps],
+        })
+    }
+
+    /// Material height at the cell holding `(x, y)`, if it is on the stock.
+    pub fn height_at(&self, x: f64, y: f64) -> Option<f64> {
+        let column = ((x - self.origin.0) / self.resolution).floor();
+        let row = ((y - self.origin.1) / self.resolution).floor();
+        if column < 0.0 || row < 0.0 {
+            return None;
+        }
+        let (column, row) = (column as usize, row as usize);
+        (column < self.width_steps && row < self.height_steps)
+            .then(|| self.data[row * self.width_steps + column])
+    }
+
+    /// Volume of material cut away so far (mm³).
+    pub fn removed_volume(&self) -> f64 {
+        let cell_area = self.resolution * self.resolution;
+        self.data.iter().map(|z| self.top - z).sum::<f64>() * cell_area
+    }
+
+    /// Sweep `tool` along every path of `toolpath`.
+    ///
+    /// Moves are stepped at half a cell at most and the tool is stamped at each step.
+    /// Rapids between paths are above the stock and cut nothing.
+    pub fn cut(&mut self, toolpath: &Toolpath, tool: &Tool) -> Result<()> {
+        self.cut_inspecting(toolpath, tool, |_, _, _| {})
+    }
+
+    /// Like [`Heightmap::cut`], calling `inspect` with the stock, the tool tip and the tool
+    /// radius before each step cuts.
+    pub(crate) fn cut_inspecting(
+        &mut self,
+        toolpath: &Toolpath,
+        tool: &Tool,
+        mut inspect: impl FnMut(&Heightmap, (f64, f64, f64), f64),
+    ) -> Result<()> {
+        let shape = ToolShape::new(tool, self.top - self.bottom)?;
+        let step = self.resolution / 2.0;
+        for path in &toolpath.paths {
+            let Some(&first) = path.first() else {
+                continue;
+            };
+            inspect(self, first, shape.radius);
+            self.stamp(first, &shape);
+            for pair in path.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                let length = (to.0 - from.0).hypot(to.1 - from.1);
+                let steps = (length / step).ceil().max(1.0) as usize;
+                for index in 1..=steps {
+                    let t = index as f64 / steps as f64;
+                    let point = (
+                        from.0 + (to.0 - from.0) * t,
+                        from.1 + (to.1 - from.1) * t,
+                        from.2 + (to.2 - from.2) * t,
+                    );
+                    inspect(self, point, shape.radius);
+                    self.stamp(point, &shape);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Highest material within `radius` of `(x, y)`, if any of the stock is that close.
+    pub(crate) fn highest_within(&self, x: f64, y: f64, radius: f64) -> Option<f64> {
+        let (columns, rows) = self.footprint(x, y, radius)?;
+        let mut highest: Option<f64> = None;
+        for row in rows {
+            let cell_y = self.origin.1 + (row as f64 + 0.5) * self.resolution;
+            for column in columns.clone() {
+                let cell_x = self.origin.0 + (column as f64 + 0.5) * self.resolution;
+                if (cell_x - x).hypot(cell_y - y) <= radius {
+                    let z = self.data[row * self.width_steps + column];
+                    highest = Some(highest.map_or(z, |h| h.max(z)));
+                }
+            }
+        }
+        highest
+    }
+
+    /// Columns and rows of the cells whose centres may lie within `radius` of `(x, y)`.
+    fn footprint(
+        &self,
+        x: f64,
+        y: f64,
+        radius: f64,
+    ) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        let cells = |centre: f64, origin: f64, count: usize| {
+            let low = ((centre - radius - origin) / self.resolution - 0.5).ceil();
+            let high = ((centre + radius - origin) / self.resolution - 0.5).floor();
+            let low = low.max(0.0) as usize;
+            let high = high.min(count as f64 - 1.0);
+            (high >= low as f64).then_some(low..=high as usize)
+        };
+        Some((
+            cells(x, self.origin.0, self.width_steps)?,
+            cells(y, self.origin.1, self.height_steps)?,
+        ))
+    }
+
+    /// Lower every cell under the tool with its tip at `tip` to the tool's surface.
+    fn stamp(&mut self, tip: (f64, f64, f64), shape: &ToolShape) {
+        if tip.2 >= self.top {
+            return;
+        }
+        let Some((columns, rows)) = self.footprint(tip.0, tip.1, shape.radius) else {
+            return;
+        };
+
+        for row in rows {
+            let y = self.origin.1 + (row as f64 + 0.5) * self.resolution;
+            for column in columns.clone() {
+                let x = self.origin.0 + (column as f64 + 0.5) * self.resolution;
+                let Some(rise) = shape.height_at((x - tip.0).hypot(y - tip.1)) else {
+                    continue;
+                };
+                let cell = &mut self.data[row * self.width_steps + column];
+                *cell = cell.min(tip.2 + rise).max(self.bottom);
+            }
+        }
+    }
+
+    /// The top surface as triangles, one vertex per cell centre.
+    pub fn to_mesh(&self) -> HeightmapMesh {
+        let (width, height) = (self.width_steps, self.height_steps);
+        let z = |column: usize, row: usize| self.data[row * width + column];
+        let mut mesh = HeightmapMesh::default();
+        for row in 0..height {
+            for column in 0..width {
+                mesh.positions.push([
+                    (self.origin.0 + (column as f64 + 0.5) * self.resolution) as f32,
+                    (self.origin.1 + (row as f64 + 0.5) * self.resolution) as f32,
+                    z(column, row) as f32,
+                ]);
+                // Slope from the neighbouring cells, one-sided at the edges.
+                let (left, right) = (column.saturating_sub(1), (column + 1).min(width - 1));
+                let (down, up) = (row.saturating_sub(1), (row + 1).min(height - 1));
+                let dx = (z(right, row) - z(left, row)) / ((right - left).max(1) as f64);
+                let dy = (z(column, up) - z(column, down)) / ((up - down).max(1) as f64);
+                let normal = (-dx / self.resolution, -dy / self.resolution, 1.0);
+                let length = (normal.0 * normal.0 + normal.1 * normal.1 + 1.0).sqrt();
+                mesh.normals.push([
+                    (normal.0 / length) as f32,
+                    (normal.1 / length) as f32,
+                    (normal.2 / length) as f32,
+                ]);
+            }
+        }
+        for row in 0..height.saturating_sub(1) {
+            for column in 0..width.saturating_sub(1) {
+                let a = (row * width + column) as u32;
+                let b = a + 1;
+                let c = a + width as u32;
+                let d = c + 1;
+                mesh.indices.extend([a, b, c, c, b, d]);
+            }
+        }
+        mesh
+    }
+}
+
+impl HeightmapMesh {
+    /// The mesh as a Wavefront OBJ document.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+        for p in &self.positions {
+            let _ = writeln!(obj, "v {} {} {}", p[0], p[1], p[2]);
+        }
+        for n in &self.normals {
+            let _ = writeln!(obj, "vn {} {} {}", n[0], n[1], n[2]);
+        }
+        for triangle in self.indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            let _ = writeln!(obj, "f {a}//{a} {b}//{b} {c}//{c}");
+        }
+        obj
+    }
+}
+
+/// Simulate cutting `board` from fresh stock, every pass of its operations in cutting
+/// order.
+///
+/// Operations without an up-to-date, valid toolpath are left out.
+pub fn simulate_project(
+    project: &Project,
+    tools: &ToolLibrary,
+    resolution: f64,
+    board: Board,
+) -> Result<Heightmap> {
+    let mut heightmap = Heightmap::new(&project.stock, resolution)?;
+    let summaries = project.operation_summaries();
+    for index in operation_cut_order(project) {
+        if project.operations[index].board() != board {
+            continue;
+        }
+        let ready = project
+            .operation_states
+            .get(index)
+            .is_some_and(|state| matches!(state.status(), ToolpathStatus::Ready { .. }));
+        let Some(artifact) = project.toolpath_for_operation(index).filter(|_| ready) else {
+            continue;
+        };
+        let tool_of = |tool_index: usize| {
+            tools.tools.get(tool_index).ok_or_else(|| {
+                anyhow!(
+                    "Operation {} uses tool #{tool_index} which is not in the library",
+                    index + 1
+                )
+            })
+        };
+        if artifact.passes.is_empty() {
+            let tool = tool_of(summaries[index].primary_tool_index)?;
+            heightmap.cut(&artifact.toolpath, tool)?;
+        }
+        for pass in &artifact.passes {
+            heightmap.cut(&pass.toolpath, tool_of(pass.tool_index)?)?;
+        }
+    }
+    Ok(heightmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FeedSettings, Units};
+
+    fn tool(tool_type: ToolType, diameter: f64) -> Tool {
+        Tool {
+            name: "Test tool".to_string(),
+            diameter,
+            stepover: 0.4,
+            pass_depth: 1.0,
+            tool_type,
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    fn stock() -> StockSpec {
+        StockSpec::new(20.0, 10.0, 5.0)
+    }
+
+    #[test]
+    fn test_flat_endmill_removes_exact_volumes() {
+        // A tool wider than the stock faces all of it.
+        let mut heightmap = Heightmap::new(&stock(), 0.5).unwrap();
+        let facing = tool(ToolType::Endmill { diameter: 30.0 }, 30.0);
+        let plunge = Toolpath::new(vec![vec![(10.0, 5.0, 1.0), (10.0, 5.0, -1.0)]]);
+        heightmap.cut(&plunge, &facing).unwrap();
+        assert_eq!(heightmap.removed_volume(), 200.0);
+        assert_eq!(heightmap.height_at(0.1, 9.9), Some(-1.0));
+
+        // Cutting below the stock stops at its bottom.
+        let through = Toolpath::new(vec![vec![(10.0, 5.0, -9.0)]]);
+        heightmap.cut(&through, &facing).unwrap();
+        assert_eq!(heightmap.removed_volume(), 1000.0);
+
+        // A 2mm slot 10mm long covers 92 quarter-square-millimetre cells.
+        let mut heightmap = Heightmap::new(&stock(), 0.5).unwrap();
+        let slot = Toolpath::new(vec![vec![(5.0, 5.0, -1.0), (15.0, 5.0, -1.0)]]);
+        heightmap
+            .cut(&slot, &tool(ToolType::Endmill { diameter: 2.0 }, 2.0))
+            .unwrap();
+        assert_eq!(heightmap.removed_volume(), 23.0);
+        assert_eq!(heightmap.height_at(10.0, 5.0), Some(-1.0));
+        assert_eq!(heightmap.height_at(10.0, 7.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_vbit_and_ballnose_cut_their_shapes() {
+        let plunge = |depth: f64| Toolpath::new(vec![vec![(10.0, 5.0, 0.0), (10.0, 5.0, -depth)]]);
+
+        // A 90 degree V-bit 2mm deep leaves a cone of radius 2.
+        let mut heightmap = Heightmap::new(&stock(), 0.05).unwrap();
+        let vbit = tool(
+            ToolType::VBit {
+                angle_degrees: 90.0,
+            },
+            0.0,
+        );
+        heightmap.cut(&plunge(2.0), &vbit).unwrap();
+        let cone = std::f64::consts::PI * 4.0 * 2.0 / 3.0;
+        assert!((heightmap.removed_volume() - cone).abs() / cone < 0.02);
+        assert!((heightmap.height_at(11.0, 5.0).unwrap() + 1.0).abs() < 0.05);
+
+        // A 6mm ball nose sunk to its equator leaves a hemisphere.
+        let mut heightmap = Heightmap::new(&stock(), 0.05).unwrap();
+        let ball = tool(ToolType::Ballnose { diameter: 6.0 }, 6.0);
+        heightmap.cut(&plunge(3.0), &ball).unwrap();
+        let hemisphere = std::f64::consts::PI * 27.0 * 2.0 / 3.0;
+        assert!((heightmap.removed_volume() - hemisphere).abs() / hemisphere < 0.02);
+    }
+
+    #[test]
+    fn test_heightmap_mesh_covers_the_grid() {
+        let mut heightmap = Heightmap::new(&StockSpec::new(2.0, 1.0, 1.0), 0.5).unwrap();
+        let endmill = tool(ToolType::Endmill { diameter: 0.5 }, 0.5);
+        heightmap
+            .cut(&Toolpath::new(vec![vec![(0.25, 0.25, -0.5)]]), &endmill)
+            .unwrap();
+
+        let mesh = heightmap.to_mesh();
+        assert_eq!(mesh.positions.len(), 8);
+        assert_eq!(mesh.normals.len(), 8);
+        assert_eq!(mesh.indices.len(), 3 * 2 * 3);
+        assert_eq!(mesh.positions[0], [0.25, 0.25, -0.5]);
+        assert_eq!(mesh.positions[7], [1.75, 0.75, 0.0]);
+        let obj = mesh.to_obj();
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 6);
+
+        assert!(Heightmap::new(&stock(), 0.0).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+use crate::types::{TabPlacement, TabSettings, Tool, Toolpath};
+use anyhow::{anyhow, Result};
+
+/// Lift the profile over holding tabs on every level that cuts below the tab top.
+///
+/// `source` is the closed curve the profile was generated from; manual tab positions are
+/// parameters along it and are projected onto the cutter path. Each tab spans its width
+/// plus the tool diameter on the cutter path, so the bridge left in the part is `width` long.
+pub fn apply_tabs(
+    toolpath: &mut Toolpath,
+    source: &[(f64, f64)],
+    tool: &Tool,
+    tabs: &TabSettings,
+) -> Result<()> {
+    if tabs.width <= 0.0 || tabs.height <= 0.0 {
+        return Err(anyhow!("Tab width and height must be positive"));
+    }
+    if toolpath.paths.is_empty() {
+        return Ok(());
+    }
+
+    // Every level repeats the same loops, so tab spans are planned once per distinct loop;
+    // an inside cut can split into several of them.
+    let mut loops: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut path_loops = Vec::with_capacity(toolpath.paths.len());
+    for path in &toolpath.paths {
+        let loop_2d: Vec<(f64, f64)> = path.iter().map(|p| (p.0, p.1)).collect();
+        let index = match loops.iter().position(|known| same_loop(known, &loop_2d)) {
+            Some(index) => index,
+            None => {
+                loops.push(loop_2d);
+                loops.len() - 1
+            }
+        };
+        path_loops.push(index);
+    }
+    let lengths: Vec<Vec<f64>> = loops.iter().map(|l| cumulative_lengths(l)).collect();
+    let perimeter = |index: usize| *lengths[index].last().unwrap_or(&0.0);
+
+    let mut centers: Vec<Vec<f64>> = vec![Vec::new(); loops.len()];
+    if tabs.positions.is_empty() {
+        for (index, centers) in centers.iter_mut().enumerate() {
+            let perimeter = perimeter(index);
+            if perimeter <= 0.0 {
+                continue;
+            }
+            let count = match tabs.placement {
+                TabPlacement::Count(count) => count,
+                TabPlacement::Spacing(spacing) if spacing > 0.0 => {
+                    ((perimeter / spacing).floor() as usize).max(1)
+                }
+                TabPlacement::Spacing(_) => return Err(anyhow!("Tab spacing must be positive")),
+            };
+            // Offset by half a spacing so no tab sits on the path start.
+            *centers = (0..count)
+                .map(|i| perimeter * (i as f64 + 0.5) / count as f64)
+                .collect();
+        }
+    } else {
+        let source_cumulative = cumulative_lengths(source);
+        for t in &tabs.positions {
+            let point = point_at_distance(
+                source,
+                &source_cumulative,
+                t.rem_euclid(1.0) * source_cumulative.last().unwrap_or(&0.0),
+            );
+            // A manual tab goes on whichever loop passes closest to it.
+            let nearest = (0..loops.len())
+                .map(|index| {
+                    (
+                        index,
+                        nearest_on_loop(&loops[index], &lengths[index], point),
+                    )
+                })
+                .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0));
+            if let Some((index, (_, along))) = nearest {
+                centers[index].push(along);
+            }
+        }
+    }
+
+    let half_span = (tabs.width + tool.diameter) / 2.0;
+    let mut spans = Vec::with_capacity(loops.len());
+    for (index, centers) in centers.iter().enumerate() {
+        spans.push(loop_spans(centers, half_span, perimeter(index))?);
+    }
+
+    let bottom_z = toolpath
+        .levels
+        .iter()
+        .map(|level| level.z)
+        .fold(f64::INFINITY, f64::min);
+    let bottom_z = if bottom_z.is_finite() {
+        bottom_z
+    } else {
+        toolpath
+            .paths
+            .iter()
+            .flatten()
+            .map(|p| p.2)
+            .fold(f64::INFINITY, f64::min)
+    };
+    let tab_top = bottom_z + tabs.height;
+
+    for (path, &index) in toolpath.paths.iter_mut().zip(&path_loops) {
+        let level_z = path.first().map(|p| p.2).unwrap_or(0.0);
+        if level_z >= tab_top || spans[index].is_empty() {
+            continue;
+        }
+        *path = lift_over_spans(
+            &loops[index],
+            &lengths[index],
+            &spans[index],
+            level_z,
+            tab_top,
+        );
+    }
+
+    toolpath.tabs = centers
+        .iter()
+        .enumerate()
+        .flat_map(|(index, centers)| centers.iter().map(move |center| (index, *center)))
+        .map(|(index, center)| point_at_distance(&loops[index], &lengths[index], center))
+        .collect();
+
+    Ok(())
+}
+
+/// Whether two paths trace the same XY loop, as the levels of one cut do.
+fn same_loop(a: &[(f64, f64)], b: &[(f64, f64)]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(p, q)| (p.0 - q.0).abs() < 1e-9 && (p.1 - q.1).abs() < 1e-9)
+}
+
+/// Distance ranges along a loop of `perimeter` covered by tabs at `centers`, split where
+/// they wrap past the loop start.
+fn loop_spans(centers: &[f64], half_span: f64, perimeter: f64) -> Result<Vec<(f64, f64)>> {
+    if !centers.is_empty() && half_span * 2.0 * centers.len() as f64 >= perimeter {
+        return Err(anyhow!("Tabs cover the whole profile"));
+    }
+    let mut spans = Vec::new();
+    for center in centers {
+        let (start, end) = (center - half_span, center + half_span);
+        if start < 0.0 {
+            spans.push((start + perimeter, perimeter));
+            spans.push((0.0, end));
+        } else if end > perimeter {
+            spans.push((start, perimeter));
+            spans.push((0.0, end - perimeter));
+        } else {
+            spans.push((start, end));
+        }
+    }
+    Ok(spans)
+}
+
+/// Rebuild a loop at `level_z`, raising it to `tab_z` inside each span.
+fn lift_over_spans(
+    points: &[(f64, f64)],
+    cumulative: &[f64],
+    spans: &[(f64, f64)],
+    level_z: f64,
+    tab_z: f64,
+) -> Vec<(f64, f64, f64)> {
+    // Split the loop at every span boundary.
+    let mut boundaries: Vec<f64> = spans.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    boundaries.sort_by(|a, b| a.total_cmp(b));
+
+    let mut split: Vec<((f64, f64), f64)> = vec![(points[0], 0.0)];
+    for i in 1..points.len() {
+        let (d0, d1) = (cumulative[i - 1], cumulative[i]);
+        for &b in &boundaries {
+            if b > d0 + 1e-9 && b < d1 - 1e-9 {
+                split.push((point_at_distance(points, cumulative, b), b));
+            }
+        }
+        split.push((points[i], d1));
+    }
+
+    let in_tab = |d: f64| spans.iter().any(|(a, b)| d > *a && d < *b);
+    let segment_z: Vec<f64> = split
+        .windows(2)
+        .map(|w| {
+            if in_tab((w[0].1 + w[1].1) / 2.0) {
+                tab_z
+            } else {
+                level_z
+            }
+        })
+        .collect();
+
+    let Some(&first_z) = segment_z.first() else {
+        return points.iter().map(|p| (p.0, p.1, level_z)).collect();
+    };
+    let mut result = vec![(split[0].0 .0, split[0].0 .1, first_z)];
+    for i in 1..split.len() {
+        let (x, y) = split[i].0;
+        result.push((x, y, segment_z[i - 1]));
+        if let Some(&next_z) = segment_z.get(i) {
+            if next_z != segment_z[i - 1] {
+                result.push((x, y, next_z));
+            }
+        }
+    }
+    result
+}
+
+fn cumulative_lengths(points: &[(f64, f64)]) -> Vec<f64> {
+    let mut cumulative = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            let prev = points[i - 1];
+            total += (point.0 - prev.0).hypot(point.1 - prev.1);
+        }
+        cumulative.push(total);
+    }
+    cumulative
+}
+
+fn point_at_distance(points: &[(f64, f64)], cumulative: &[f64], distance: f64) -> (f64, f64) {
+    for i in 1..points.len() {
+        if distance <= cumulative[i] {
+            let span = cumulative[i] - cumulative[i - 1];
+            let t = if span > 0.0 {
+                (distance - cumulative[i - 1]) / span
+            } else {
+                0.0
+            };
+            let (a, b) = (points[i - 1], points[i]);
+            return (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+        }
+    }
+    points.last().copied().unwrap_or((0.0, 0.0))
+}
+
+/// How far `target` is from the loop, and the distance along the loop of its nearest point.
+fn nearest_on_loop(points: &[(f64, f64)], cumulative: &[f64], target: (f64, f64)) -> (f64, f64) {
+    let mut best = (f64::INFINITY, 0.0);
+    for i in 1..points.len() {
+        let (a, b) = (points[i - 1], points[i]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len_sq = dx * dx + dy * dy;
+        let t = if len_sq > 0.0 {
+            (((target.0 - a.0) * dx + (target.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let (px, py) = (a.0 + dx * t, a.1 + dy * t);
+        let dist = (target.0 - px).hypot(target.1 - py);
+        if dist < best.0 {
+            best = (dist, cumulative[i - 1] + t * len_sq.sqrt());
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::generate_profile_toolpath_with_levels;
+    use crate::types::{CutDirection, CutSide, DepthLevel, FeedSettings, ToolType, Units};
+
+    fn square() -> Vec<(f64, f64)> {
+        vec![
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+        ]
+    }
+
+    fn tool() -> Tool {
+        Tool {
+            name: "6mm Endmill".to_string(),
+            diameter: 6.0,
+            stepover: 0.4,
+            pass_depth: 2.0,
+            tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    fn through_cut() -> Toolpath {
+        let levels = DepthLevel::schedule(6.0, 2.0, None);
+        generate_profile_toolpath_with_levels(
+            &square(),
+            &tool(),
+            &CutSide::Outside,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tabs_lift_only_below_tab_height() {
+        let mut toolpath = through_cut();
+        let tabs = TabSettings {
+            placement: TabPlacement::Count(4),
+            width: 8.0,
+            height: 3.0,
+            positions: Vec::new(),
+        };
+        apply_tabs(&mut toolpath, &square(), &tool(), &tabs).unwrap();
+
+        assert_eq!(toolpath.tabs.len(), 4);
+        let tab_top = -6.0 + 3.0;
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            let level_z = toolpath.level_of(index).unwrap().z;
+            let lifted = path.iter().any(|p| p.2 != level_z);
+            if level_z < tab_top {
+                assert!(lifted, "level {level_z} should lift over tabs");
+                assert!(path.iter().all(|p| p.2 == level_z || p.2 == tab_top));
+            } else {
+                assert!(!lifted, "level {level_z} is above the tabs");
+            }
+        }
+    }
+
+    #[test]
+    fn test_tab_span_includes_tool_diameter() {
+        let mut toolpath = through_cut();
+        let tabs = TabSettings {
+            placement: TabPlacement::Count(1),
+            width: 10.0,
+            height: 1.0,
+            positions: Vec::new(),
+        };
+        apply_tabs(&mut toolpath, &square(), &tool(), &tabs).unwrap();
+
+        let bottom = toolpath.paths.last().unwrap();
+        let mut lifted_length = 0.0;
+        for pair in bottom.windows(2) {
+            if pair[0].2 > -6.0 && pair[1].2 > -6.0 {
+                lifted_length += (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1);
+            }
+        }
+        assert!((lifted_length - 16.0).abs() < 1e-6, "got {lifted_length}");
+    }
+
+    #[test]
+    fn test_manual_tab_positions_follow_curve_parameter() {
+        let mut toolpath = through_cut();
+        let tabs = TabSettings {
+            placement: TabPlacement::Count(4),
+            width: 5.0,
+            height: 1.0,
+            // Midpoint of the first edge, then the midpoint of the third edge.
+            positions: vec![0.125, 0.625],
+        };
+        apply_tabs(&mut toolpath, &square(), &tool(), &tabs).unwrap();
+
+        assert_eq!(toolpath.tabs.len(), 2);
+        let (x, y) = toolpath.tabs[0];
+        assert!(
+            (x - 50.0).abs() < 0.1 && (y + 3.0).abs() < 0.1,
+            "got ({x}, {y})"
+        );
+        let (x, y) = toolpath.tabs[1];
+        assert!(
+            (x - 50.0).abs() < 0.1 && (y - 103.0).abs() < 0.1,
+            "got ({x}, {y})"
+        );
+    }
+
+    #[test]
+    fn test_tabs_follow_each_loop_of_a_split_cut() {
+        // Two squares joined by a bridge too narrow for the tool, so the inside cut is two
+        // separate loops on every level.
+        let dumbbell = vec![
+            (0.0, 0.0),
+            (40.0, 0.0),
+            (40.0, 18.0),
+            (60.0, 18.0),
+            (60.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 40.0),
+            (60.0, 40.0),
+            (60.0, 22.0),
+            (40.0, 22.0),
+            (40.0, 40.0),
+            (0.0, 40.0),
+            (0.0, 0.0),
+        ];
+        let levels = DepthLevel::schedule(6.0, 2.0, None);
+        let mut toolpath = generate_profile_toolpath_with_levels(
+            &dumbbell,
+            &tool(),
+            &CutSide::Inside,
+            CutDirection::Climb,
+            &levels,
+        )
+        .unwrap();
+        assert_eq!(toolpath.paths.len(), 2 * levels.len());
+        let sides: Vec<bool> = toolpath
+            .paths
+            .iter()
+            .map(|path| path.iter().all(|p| p.0 < 50.0))
+            .collect();
+
+        let tabs = TabSettings {
+            placement: TabPlacement::Count(2),
+            width: 4.0,
+            height: 3.0,
+            positions: Vec::new(),
+        };
+        apply_tabs(&mut toolpath, &dumbbell, &tool(), &tabs).unwrap();
+
+        assert_eq!(toolpath.tabs.len(), 4);
+        assert_eq!(toolpath.tabs.iter().filter(|t| t.0 < 50.0).count(), 2);
+        for (index, path) in toolpath.paths.iter().enumerate() {
+            // Each path keeps to its own half, lifted or not.
+            let left = path.iter().all(|p| p.0 < 50.0);
+            let right = path.iter().all(|p| p.0 > 50.0);
+            assert!(left || right);
+            assert_eq!(left, sides[index]);
+            if toolpath.level_of(index).unwrap().z < -3.0 {
+                assert!(path.iter().any(|p| p.2 == -3.0), "path {index} has no tabs");
+            }
+        }
+        assert_eq!(sides.iter().filter(|left| **left).count(), levels.len());
+    }
+
+    #[test]
+    fn test_tabs_reject_full_coverage() {
+        let mut toolpath = through_cut();
+        let tabs = TabSettings {
+            placement: TabPlacement::Count(40),
+            width: 10.0,
+            height: 1.0,
+            positions: Vec::new(),
+        };
+        assert!(apply_tabs(&mut toolpath, &square(), &tool(), &tabs).is_err());
+    }
+}
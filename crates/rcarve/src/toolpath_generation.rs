@@ -1,18 +1,36 @@
-use crate::geometry::offset::offset_polygon;
-use crate::geometry::{CurveId, Region};
-use crate::types::{Tool, ToolType};
+use crate::geometry::offset::{is_point_inside, offset_polygon};
+use crate::geometry::{Curve, CurveId, Region};
+use crate::types::{
+    CutSide, DepthLevel, DrillCycle, EntryStrategy, PocketStrategy, Tool, ToolType, VCarveConfig,
+};
 use crate::vcarve::{PathType, VCarveDebugOutput};
 use crate::{
-    generate_pocket_toolpath, generate_profile_toolpath, generate_vcarve_toolpath_with_debug,
-    CarvePolygon, Operation, OperationTarget, Project, ToolLibrary, Toolpath, ToolpathArtifact,
-    ToolpathPass, ToolpathPassKind, ToolpathStatus,
+    apply_entry, apply_leads, apply_tabs, fit_arcs, generate_drill_toolpath,
+    generate_helical_bore_toolpath, generate_pocket_toolpath, generate_pocket_toolpath_with_levels,
+    generate_profile_toolpath_with_levels, generate_raster_pocket_toolpath,
+    generate_rest_pocket_toolpath, generate_vcarve_toolpath_with_debug, inlay_plug_polygons,
+    link_paths, optimize_path_order, verify_toolpaths, CarvePolygon, CutOrder, Operation,
+    OperationTarget, Project, ToolLibrary, Toolpath, ToolpathArtifact, ToolpathPass,
+    ToolpathPassKind, ToolpathStatus,
 };
 use anyhow::{anyhow, Context, Result};
-use kurbo::Affine;
+use kurbo::{Affine, Circle};
 use std::collections::HashMap;
 
 const FLATTEN_TOLERANCE: f64 = 0.25;
 
+/// How far a point may stray from the arc fitted through it (mm).
+const ARC_TOLERANCE: f64 = 0.01;
+
+/// How far a closed path may stray from a circle and still be bored or drilled as one (mm).
+const CIRCLE_FIT_TOLERANCE: f64 = 0.05;
+
+/// Outer boundary plus holes, as flattened point loops.
+type FlattenedRegion = (Vec<(f64, f64)>, Vec<Vec<(f64, f64)>>);
+
+/// A hole centre and, for circles, the hole diameter.
+type DrillHole = ((f64, f64), Option<f64>);
+
 #[derive(Debug, Clone)]
 pub struct ToolpathGenerationReport {
     pub operation_index: usize,
@@ -21,6 +39,10 @@ pub struct ToolpathGenerationReport {
     pub error: Option<String>,
     /// Debug output for V-carve operations (Voronoi edges, etc.)
     pub vcarve_debug: Option<VCarveDebugOutput>,
+    /// Rapid travel (mm) saved by reordering the paths of each pass.
+    pub rapid_distance_saved: f64,
+    /// Retracts saved by staying down between paths inside the machined area.
+    pub retracts_saved: usize,
 }
 
 /// Generate toolpaths for every operation in the project, updating cached artifacts.
@@ -33,7 +55,12 @@ pub fn generate_toolpaths_for_operations(
     for index in 0..project.operations.len() {
         let operation = project.operations[index].clone();
         match generate_toolpath_for_operation(project, tools, index, &operation) {
-            Ok((artifact, warnings, vcarve_debug)) => {
+            Ok((mut artifact, warnings, vcarve_debug)) => {
+                // V-carve creases can be cut either way; every other path keeps its direction.
+                let reversible = matches!(operation, Operation::VCarve { .. });
+                let (rapid_distance_saved, retracts_saved) =
+                    sequence_artifact_paths(&mut artifact, tools, reversible);
+                fit_artifact_arcs(&mut artifact);
                 let status = ToolpathStatus::Ready {
                     generated_at_epoch_ms: artifact.generated_at_epoch_ms,
                     warning_count: warnings.len(),
@@ -45,6 +72,8 @@ pub fn generate_toolpaths_for_operations(
                         warnings: vec![],
                         error: Some(err.to_string()),
                         vcarve_debug: None,
+                        rapid_distance_saved: 0.0,
+                        retracts_saved: 0,
                     });
                 } else {
                     reports.push(ToolpathGenerationReport {
@@ -53,6 +82,8 @@ pub fn generate_toolpaths_for_operations(
                         warnings,
                         error: None,
                         vcarve_debug,
+                        rapid_distance_saved,
+                        retracts_saved,
                     });
                 }
             }
@@ -63,14 +94,95 @@ pub fn generate_toolpaths_for_operations(
                     warnings: vec![],
                     error: Some(err.to_string()),
                     vcarve_debug: None,
+                    rapid_distance_saved: 0.0,
+                    retracts_saved: 0,
                 });
             }
         }
     }
 
+    apply_verification(project, tools, &mut reports);
     reports
 }
 
+/// Check the generated toolpaths against the simulated stock, recording what was found on
+/// each artifact and its report. Fatal problems mark the toolpath invalid.
+fn apply_verification(
+    project: &mut Project,
+    tools: &ToolLibrary,
+    reports: &mut [ToolpathGenerationReport],
+) {
+    let verifications = verify_toolpaths(project, tools);
+    for (index, verification) in verifications.into_iter().enumerate() {
+        if verification.warnings.is_empty() {
+            continue;
+        }
+        let Some(state) = project.operation_states.get_mut(index) else {
+            continue;
+        };
+        if let Some(artifact) = state.artifact.as_mut() {
+            artifact
+                .warnings
+                .extend(verification.warnings.iter().cloned());
+            artifact.is_valid &= !verification.fatal;
+        }
+        if let Some(report) = reports.iter_mut().find(|r| r.operation_index == index) {
+            report.warnings.extend(verification.warnings);
+            report.status = state.status();
+        }
+    }
+}
+
+/// Reorder the paths of each pass to cut down on rapids, then link them at depth where
+/// the tool can stay down. Returns the rapid distance and the retracts saved.
+///
+/// The combined toolpath is handled the same way so previews match the exported passes.
+fn sequence_artifact_paths(
+    artifact: &mut ToolpathArtifact,
+    tools: &ToolLibrary,
+    allow_reverse: bool,
+) -> (f64, usize) {
+    // V-bits cut wider the deeper they go, so their paths are never linked.
+    let link_radius = |tool_index: usize| {
+        tools
+            .tools
+            .get(tool_index)
+            .filter(|tool| !matches!(tool.tool_type, ToolType::VBit { .. }))
+            .map_or(0.0, |tool| tool.diameter / 2.0)
+    };
+    let finish_tool = artifact
+        .passes
+        .iter()
+        .rev()
+        .find(|pass| pass.kind == ToolpathPassKind::Finish)
+        .map(|pass| pass.tool_index);
+
+    let combined = optimize_path_order(&mut artifact.toolpath, allow_reverse);
+    let combined_links = finish_tool.map_or(0, |tool_index| {
+        link_paths(&mut artifact.toolpath, link_radius(tool_index))
+    });
+    if artifact.passes.is_empty() {
+        return (combined, combined_links);
+    }
+
+    let mut saved = (0.0, 0);
+    for pass in &mut artifact.passes {
+        let reversible = allow_reverse && pass.kind == ToolpathPassKind::Finish;
+        saved.0 += optimize_path_order(&mut pass.toolpath, reversible);
+        saved.1 += link_paths(&mut pass.toolpath, link_radius(pass.tool_index));
+    }
+    saved
+}
+
+/// Fit arcs to the finished paths, so curves export as G2/G3 moves rather than the lines
+/// they were flattened into.
+fn fit_artifact_arcs(artifact: &mut ToolpathArtifact) {
+    fit_arcs(&mut artifact.toolpath, ARC_TOLERANCE, FLATTEN_TOLERANCE);
+    for pass in &mut artifact.passes {
+        fit_arcs(&mut pass.toolpath, ARC_TOLERANCE, FLATTEN_TOLERANCE);
+    }
+}
+
 fn generate_toolpath_for_operation(
     project: &mut Project,
     tools: &ToolLibrary,
@@ -89,6 +201,12 @@ fn generate_toolpath_for_operation(
             cut_side,
             tool_index,
             targets,
+            finish_pass_depth,
+            tabs,
+            entry,
+            direction,
+            leads,
+            feeds,
         } => {
             let tool = tools
                 .tools
@@ -96,10 +214,49 @@ fn generate_toolpath_for_operation(
                 .ok_or_else(|| anyhow!("Tool #{tool_index} not found"))?;
 
             let polygon = first_polygon(shapes, targets, &curve_transforms)?.context("Profile requires geometry")?;
-            let toolpath = generate_profile_toolpath(&polygon, tool, cut_side, *target_depth)
-                .with_context(|| {
-                    format!("Profile operation {operation_index} failed to generate")
-                })?;
+            let levels = DepthLevel::schedule(*target_depth, tool.pass_depth, *finish_pass_depth);
+            let mut toolpath = generate_profile_toolpath_with_levels(
+                &polygon, tool, cut_side, *direction, &levels,
+            )
+            .with_context(|| format!("Profile operation {operation_index} failed to generate"))?;
+            if let Some(tabs) = tabs {
+                if tabs.height >= *target_depth {
+                    warnings.push(format!(
+                        "Tab height {:.2}mm is not below the cut depth; no tabs were cut.",
+                        tabs.height
+                    ));
+                } else {
+                    apply_tabs(&mut toolpath, &polygon, tool, tabs).with_context(|| {
+                        format!("Tabs for profile operation {operation_index} failed")
+                    })?;
+                }
+            }
+            if let Some(leads) = leads {
+                if matches!(cut_side, CutSide::OnLine) {
+                    warnings
+                        .push("Leads need an inside or outside cut; none were added.".to_string());
+                } else {
+                    let obstacles = all_curve_polylines(shapes, &curve_transforms)?;
+                    let dropped = apply_leads(&mut toolpath, cut_side, leads, tool, &obstacles)
+                        .with_context(|| {
+                            format!("Leads for profile operation {operation_index} failed")
+                        })?;
+                    if dropped > 0 {
+                        warnings.push(format!(
+                            "{dropped} lead(s) were dropped to avoid cutting neighbouring geometry."
+                        ));
+                    }
+                }
+            }
+            if matches!(entry, EntryStrategy::Helix { .. }) {
+                warnings.push(
+                    "Helical entry is only supported on pockets; ramping instead.".to_string(),
+                );
+            }
+            apply_entry(&mut toolpath, entry, tool, &[]).with_context(|| {
+                format!("Entry moves for profile operation {operation_index} failed")
+            })?;
+            toolpath.feeds = feeds.or(tool.feeds);
             let pass = ToolpathPass::new(*tool_index, ToolpathPassKind::Finish, toolpath.clone());
 
             Ok((
@@ -119,6 +276,12 @@ fn generate_toolpath_for_operation(
             target_depth,
             tool_index,
             target,
+            finish_pass_depth,
+            entry,
+            direction,
+            feeds,
+            strategy,
+            rest_tool_index,
         } => {
             let tool = tools
                 .tools
@@ -141,8 +304,60 @@ fn generate_toolpath_for_operation(
                 }
             };
 
-            let toolpath = generate_pocket_toolpath(&outer, &holes, tool, *target_depth)
-                .with_context(|| format!("Pocket operation {operation_index} failed"))?;
+            let levels = DepthLevel::schedule(*target_depth, tool.pass_depth, *finish_pass_depth);
+            let mut toolpath = if let Some(rest_index) = rest_tool_index {
+                let previous_tool = tools
+                    .tools
+                    .get(*rest_index)
+                    .ok_or_else(|| anyhow!("Tool #{rest_index} not found"))?;
+                generate_rest_pocket_toolpath(
+                    &outer,
+                    &holes,
+                    tool,
+                    previous_tool,
+                    strategy,
+                    *direction,
+                    &levels,
+                )
+            } else {
+                match strategy {
+                    PocketStrategy::Offset => generate_pocket_toolpath_with_levels(
+                        &outer, &holes, tool, *direction, &levels,
+                    ),
+                    PocketStrategy::Raster {
+                        angle_deg,
+                        boundary_pass,
+                    } => generate_raster_pocket_toolpath(
+                        &outer,
+                        &holes,
+                        tool,
+                        *angle_deg,
+                        *boundary_pass,
+                        *direction,
+                        &levels,
+                    ),
+                }
+            }
+            .with_context(|| format!("Pocket operation {operation_index} failed"))?;
+            if rest_tool_index.is_some() && toolpath.paths.is_empty() {
+                warnings.push(
+                    "The previous tool already reaches the whole pocket; nothing is left to clear."
+                        .to_string(),
+                );
+            }
+
+            let mut boundary = vec![outer.clone()];
+            boundary.extend(holes.iter().cloned());
+            let fallbacks =
+                apply_entry(&mut toolpath, entry, tool, &boundary).with_context(|| {
+                    format!("Entry moves for pocket operation {operation_index} failed")
+                })?;
+            if fallbacks > 0 {
+                warnings.push(format!(
+                    "Helix did not fit on {fallbacks} path(s); ramped along the path instead."
+                ));
+            }
+            toolpath.feeds = feeds.or(tool.feeds);
             let pass = ToolpathPass::new(*tool_index, ToolpathPassKind::Finish, toolpath.clone());
 
             Ok((
@@ -159,10 +374,12 @@ fn generate_toolpath_for_operation(
             ))
         }
         Operation::VCarve {
-            target_depth,
+            config,
             tool_index,
             targets,
             clearance_tool_index,
+            feeds,
+            inlay_plug,
         } => {
             let tool = tools
                 .tools
@@ -171,6 +388,22 @@ fn generate_toolpath_for_operation(
 
             let carve_polygons = collect_vcarve_polygons(shapes, targets, &curve_transforms)?;
 
+            // A plug is carved from the background around the mirrored design, with the
+            // whole carve lowered to the plug's start depth.
+            let (carve_polygons, start_depth) = match inlay_plug {
+                Some(plug) => {
+                    let flat_depth = config
+                        .flat_depth
+                        .ok_or_else(|| anyhow!("An inlay plug needs a flat depth"))?;
+                    let background = inlay_plug_polygons(&carve_polygons, tool, plug, flat_depth)
+                        .with_context(|| {
+                        format!("Inlay plug for operation {operation_index} failed")
+                    })?;
+                    (background, plug.start_depth)
+                }
+                None => (carve_polygons, 0.0),
+            };
+
             let mut passes = Vec::new();
 
             if let Some(clearance_index) = clearance_tool_index {
@@ -179,8 +412,8 @@ fn generate_toolpath_for_operation(
                     .get(*clearance_index)
                     .ok_or_else(|| anyhow!("Tool #{clearance_index} not found"))?;
 
-                let clearance_depth = match target_depth {
-                    Some(depth) if *depth > 0.0 => *depth,
+                let clearance_depth = match config.flat_depth {
+                    Some(depth) if depth > 0.0 => depth,
                     _ => {
                         warnings.push(
                             "Clearance tool selected without a max depth; defaulting to 1mm."
@@ -195,46 +428,63 @@ fn generate_toolpath_for_operation(
                     _ => return Err(anyhow!("V-carve operation requires a V-bit tool")),
                 };
                 let rad = angle_deg.to_radians() / 2.0;
-                let limit_dist = clearance_depth * rad.tan();
+                let limit_dist = (clearance_depth - start_depth) * rad.tan();
 
                 let inner_polygons = offset_polygon(&carve_polygons, limit_dist)?;
 
-                let clearance_toolpath =
+                let mut clearance_toolpath =
                     generate_clearance_toolpath(&inner_polygons, clearance_tool, clearance_depth)
                         .with_context(|| {
                             format!("Clearance toolpath for operation {operation_index} failed")
                         })?;
+                clearance_toolpath.feeds = clearance_tool.feeds;
 
                 passes.push(ToolpathPass::new(
                     *clearance_index,
                     ToolpathPassKind::Clearance,
-                    clearance_toolpath.clone(),
+                    clearance_toolpath,
                 ));
             }
 
             // Generate V-carve toolpath with debug data collection
+            let carve_config = VCarveConfig {
+                flat_depth: config.flat_depth.map(|depth| depth - start_depth),
+                ..*config
+            };
             let vcarve_result =
-                generate_vcarve_toolpath_with_debug(&carve_polygons, tool, *target_depth, true)
+                generate_vcarve_toolpath_with_debug(&carve_polygons, tool, &carve_config, true)
                     .with_context(|| format!("V-carve operation {operation_index} failed"))?;
 
             // Convert PathType to Toolpath
-            let mut finish_paths_3d = Vec::new();
+            let mut finish_paths_3d: Vec<Vec<(f64, f64, f64)>> = Vec::new();
             for pt in vcarve_result.paths {
                 match pt {
-                    PathType::Crease { start, end } => {
-                        finish_paths_3d.push(vec![
-                            (start[0], start[1], start[2]),
-                            (end[0], end[1], end[2]),
-                        ]);
+                    PathType::Crease { path } => {
+                        let points: Vec<_> = path
+                            .into_iter()
+                            .map(|p| (p[0], p[1], p[2] - start_depth))
+                            .collect();
+                        // A branch that carries on from where the last one ended is cut
+                        // without lifting the tool.
+                        match finish_paths_3d.last_mut() {
+                            Some(last) if last.last() == points.first() => {
+                                last.extend_from_slice(&points[1..]);
+                            }
+                            _ => finish_paths_3d.push(points),
+                        }
                     }
                     PathType::PocketBoundary { path, depth } => {
-                        let z = -depth.abs();
+                        let z = -depth.abs() - start_depth;
                         let path_3d = path.into_iter().map(|p| (p[0], p[1], z)).collect();
                         finish_paths_3d.push(path_3d);
                     }
                 }
             }
-            let finish_toolpath = Toolpath { paths: finish_paths_3d };
+            let finish_toolpath = Toolpath {
+                paths: finish_paths_3d,
+                feeds: feeds.or(tool.feeds),
+                ..Default::default()
+            };
 
             passes.push(ToolpathPass::new(
                 *tool_index,
@@ -255,9 +505,170 @@ fn generate_toolpath_for_operation(
                 vcarve_result.debug, // Include V-carve debug output
             ))
         }
+        Operation::Drill {
+            target_depth,
+            tool_index,
+            targets,
+            peck_depth,
+            dwell,
+            feeds,
+        } => {
+            let tool = tools
+                .tools
+                .get(*tool_index)
+                .ok_or_else(|| anyhow!("Tool #{tool_index} not found"))?;
+
+            let holes = drill_holes(shapes, targets, &curve_transforms)?;
+            let oversized = holes
+                .iter()
+                .filter(|(_, diameter)| diameter.is_some_and(|d| tool.diameter > d + 1e-3))
+                .count();
+            if oversized > 0 {
+                warnings.push(format!(
+                    "{oversized} hole(s) are smaller than the {:.2}mm tool and will be drilled oversize.",
+                    tool.diameter
+                ));
+            }
+
+            let centres: Vec<(f64, f64)> = holes.iter().map(|(centre, _)| *centre).collect();
+            let cycle = DrillCycle {
+                peck_depth: *peck_depth,
+                dwell: *dwell,
+            };
+            let mut toolpath = generate_drill_toolpath(&centres, *target_depth, cycle)
+                .with_context(|| format!("Drill operation {operation_index} failed"))?;
+            toolpath.feeds = feeds.or(tool.feeds);
+            let pass = ToolpathPass::new(*tool_index, ToolpathPassKind::Finish, toolpath.clone());
+
+            Ok((
+                ToolpathArtifact {
+                    operation_index,
+                    toolpath,
+                    passes: vec![pass],
+                    generated_at_epoch_ms: current_epoch_ms(),
+                    warnings: warnings.clone(),
+                    is_valid: true,
+                },
+                warnings,
+                None, // No V-carve debug for Drill
+            ))
+        }
+        Operation::Bore {
+            target_depth,
+            tool_index,
+            targets,
+            cut_side,
+            pitch,
+            direction,
+            feeds,
+        } => {
+            let tool = tools
+                .tools
+                .get(*tool_index)
+                .ok_or_else(|| anyhow!("Tool #{tool_index} not found"))?;
+
+            let mut toolpath = Toolpath::default();
+            for circle in bore_circles(shapes, targets, &curve_transforms)? {
+                let bore = generate_helical_bore_toolpath(
+                    (circle.center.x, circle.center.y),
+                    circle.radius,
+                    tool,
+                    cut_side,
+                    *direction,
+                    *pitch,
+                    *target_depth,
+                )
+                .with_context(|| format!("Bore operation {operation_index} failed"))?;
+                toolpath.append(bore);
+            }
+            toolpath.feeds = feeds.or(tool.feeds);
+            let pass = ToolpathPass::new(*tool_index, ToolpathPassKind::Finish, toolpath.clone());
+
+            Ok((
+                ToolpathArtifact {
+                    operation_index,
+                    toolpath,
+                    passes: vec![pass],
+                    generated_at_epoch_ms: current_epoch_ms(),
+                    warnings: warnings.clone(),
+                    is_valid: true,
+                },
+                warnings,
+                None, // No V-carve debug for Bore
+            ))
+        }
     }
 }
 
+/// The order to cut the project's operations in, as operation indices.
+///
+/// With [`CutOrder::InsideOut`], any operation whose geometry lies inside the outline of a
+/// profile is cut before that profile, so windows and parts are not cut free while work
+/// inside them remains. Otherwise, and between unrelated operations, list order is kept.
+pub fn operation_cut_order(project: &Project) -> Vec<usize> {
+    let count = project.operations.len();
+    if project.cut_order == CutOrder::Listed {
+        return (0..count).collect();
+    }
+
+    // Operations without usable geometry are simply left unconstrained.
+    let polygons: Vec<Vec<Vec<(f64, f64)>>> = (0..count)
+        .map(|index| polygons_for_operation(project, index).unwrap_or_default())
+        .collect();
+    let mut after = vec![Vec::new(); count];
+    for (outer, operation) in project.operations.iter().enumerate() {
+        let Some(outline) = polygons[outer].first() else {
+            continue;
+        };
+        if !matches!(operation, Operation::Profile { .. }) {
+            continue;
+        }
+        for inner in (0..count).filter(|&inner| inner != outer) {
+            if lies_inside(&polygons[inner], outline) {
+                after[outer].push(inner);
+            }
+        }
+    }
+
+    let mut placed = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+    while order.len() < count {
+        let ready =
+            |index: &usize| !placed[*index] && after[*index].iter().all(|&other| placed[other]);
+        let next = (0..count)
+            .find(ready)
+            .or_else(|| (0..count).find(|&index| !placed[index]))
+            .expect("an operation remains");
+        placed[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+/// Whether every point of `polygons` is inside `outline`, and they are not the outline itself.
+fn lies_inside(polygons: &[Vec<(f64, f64)>], outline: &[(f64, f64)]) -> bool {
+    let bounds = |points: &mut dyn Iterator<Item = &(f64, f64)>| {
+        points.fold(
+            ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)),
+            |(min, max), p| {
+                (
+                    (min.0.min(p.0), min.1.min(p.1)),
+                    (max.0.max(p.0), max.1.max(p.1)),
+                )
+            },
+        )
+    };
+    let (inner_min, inner_max) = bounds(&mut polygons.iter().flatten());
+    let (outer_min, outer_max) = bounds(&mut outline.iter());
+    let same = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6;
+    polygons.iter().any(|polygon| !polygon.is_empty())
+        && !(same(inner_min, outer_min) && same(inner_max, outer_max))
+        && polygons
+            .iter()
+            .flatten()
+            .all(|point| is_point_inside(point, outline))
+}
+
 pub fn polygons_for_operation(project: &Project, index: usize) -> Result<Vec<Vec<(f64, f64)>>> {
     let shapes = &project.shapes;
     let curve_transforms = build_curve_transform_map(project);
@@ -311,6 +722,15 @@ pub fn polygons_for_operation(project: &Project, index: usize) -> Result<Vec<Vec
                 Ok(polygons)
             }
         },
+        // Drills are placed by their centres, which point markers have but no outline.
+        Operation::Drill { targets, .. } => Ok(drill_holes(shapes, targets, &curve_transforms)?
+            .into_iter()
+            .map(|(center, _)| vec![center])
+            .collect()),
+        Operation::Bore { targets, .. } => match targets {
+            OperationTarget::Curves(curves) => flatten_curves(shapes, curves, &curve_transforms),
+            OperationTarget::Region(_) => Err(anyhow!("Bores target circles, not regions")),
+        },
     }
 }
 
@@ -351,6 +771,54 @@ fn first_polygon(
     }
 }
 
+/// Centre and diameter of every drill target; point markers have no diameter.
+fn drill_holes(
+    shapes: &crate::geometry::ShapeRegistry,
+    targets: &OperationTarget,
+    curve_transforms: &HashMap<CurveId, Affine>,
+) -> Result<Vec<DrillHole>> {
+    let OperationTarget::Curves(curves) = targets else {
+        return Err(anyhow!(
+            "Drill targets circles or point markers, not regions"
+        ));
+    };
+    curves
+        .iter()
+        .map(|id| {
+            let curve = transformed_curve(shapes, id, curve_transforms)?;
+            if let Curve::Point(point) = curve {
+                return Ok(((point.x, point.y), None));
+            }
+            let circle = curve
+                .as_circle(CIRCLE_FIT_TOLERANCE)
+                .ok_or_else(|| anyhow!("Curve {:?} is not a circle or point marker", id))?;
+            Ok((
+                (circle.center.x, circle.center.y),
+                Some(circle.radius * 2.0),
+            ))
+        })
+        .collect()
+}
+
+/// Every target of a bore, each of which must be a circle.
+fn bore_circles(
+    shapes: &crate::geometry::ShapeRegistry,
+    targets: &OperationTarget,
+    curve_transforms: &HashMap<CurveId, Affine>,
+) -> Result<Vec<Circle>> {
+    let OperationTarget::Curves(curves) = targets else {
+        return Err(anyhow!("Bore targets circles, not regions"));
+    };
+    curves
+        .iter()
+        .map(|id| {
+            transformed_curve(shapes, id, curve_transforms)?
+                .as_circle(CIRCLE_FIT_TOLERANCE)
+                .ok_or_else(|| anyhow!("Curve {:?} is not a circle", id))
+        })
+        .collect()
+}
+
 fn flatten_curves(
     shapes: &crate::geometry::ShapeRegistry,
     curves: &[CurveId],
@@ -367,11 +835,27 @@ fn flatten_curves(
     Ok(result)
 }
 
+/// Every curve in the project, flattened, for clearance checks against nearby geometry.
+fn all_curve_polylines(
+    shapes: &crate::geometry::ShapeRegistry,
+    curve_transforms: &HashMap<CurveId, Affine>,
+) -> Result<Vec<Vec<(f64, f64)>>> {
+    let mut result = Vec::new();
+    for id in shapes.all_curve_ids() {
+        let mut points = flatten_curve(shapes, &id, curve_transforms)?;
+        if shapes.get_curve(&id).is_some_and(|curve| curve.is_closed()) {
+            close_loop(&mut points);
+        }
+        result.push(points);
+    }
+    Ok(result)
+}
+
 fn flatten_region(
     shapes: &crate::geometry::ShapeRegistry,
     region: &Region,
     curve_transforms: &HashMap<CurveId, Affine>,
-) -> Result<(Vec<(f64, f64)>, Vec<Vec<(f64, f64)>>)> {
+) -> Result<FlattenedRegion> {
     let mut outer = flatten_curve(shapes, &region.outer, curve_transforms)?;
     close_loop(&mut outer);
 
@@ -428,13 +912,14 @@ fn generate_clearance_toolpath(
         return Err(anyhow!("Clearance depth must be positive"));
     }
 
-    let mut paths = Vec::new();
+    // Each polygon is stepped down on its own; levels at the same Z are shared.
+    let mut combined = Toolpath::default();
     for poly in polygons {
         let toolpath = generate_pocket_toolpath(&poly.outer, &poly.holes, tool, depth)?;
-        paths.extend(toolpath.paths);
+        combined.append(toolpath);
     }
 
-    Ok(Toolpath { paths })
+    Ok(combined)
 }
 
 fn flatten_curve(
@@ -442,16 +927,7 @@ fn flatten_curve(
     id: &CurveId,
     curve_transforms: &HashMap<CurveId, Affine>,
 ) -> Result<Vec<(f64, f64)>> {
-    let curve = shapes
-        .get_curve(id)
-        .ok_or_else(|| anyhow!("Curve {:?} not found", id))?;
-    
-    // Apply transform if this curve belongs to an import
-    let mut curve = curve.clone();
-    if let Some(&transform) = curve_transforms.get(id) {
-        curve.apply_affine(transform);
-    }
-    
+    let curve = transformed_curve(shapes, id, curve_transforms)?;
     let points = curve.flatten(FLATTEN_TOLERANCE);
     if points.is_empty() {
         return Err(anyhow!("Curve {:?} produced no points", id));
@@ -459,6 +935,22 @@ fn flatten_curve(
     Ok(points)
 }
 
+/// A copy of the curve, moved into place if it belongs to an import.
+fn transformed_curve(
+    shapes: &crate::geometry::ShapeRegistry,
+    id: &CurveId,
+    curve_transforms: &HashMap<CurveId, Affine>,
+) -> Result<Curve> {
+    let mut curve = shapes
+        .get_curve(id)
+        .ok_or_else(|| anyhow!("Curve {:?} not found", id))?
+        .clone();
+    if let Some(&transform) = curve_transforms.get(id) {
+        curve.apply_affine(transform);
+    }
+    Ok(curve)
+}
+
 fn close_loop(points: &mut Vec<(f64, f64)>) {
     if let (Some(first), Some(last)) = (points.first().cloned(), points.last().cloned()) {
         if (first.0 - last.0).abs() > f64::EPSILON || (first.1 - last.1).abs() > f64::EPSILON {
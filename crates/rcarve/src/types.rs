@@ -12,6 +12,136 @@ pub struct Tool {
     pub pass_depth: f64,
     /// The specific geometry of the tool.
     pub tool_type: ToolType,
+    /// Feed rates and spindle speed for this tool.
+    #[serde(default)]
+    pub feeds: FeedSettings,
+    /// Optional: Length of the cutting flutes; cutting deeper rubs the shank on the stock.
+    #[serde(default)]
+    pub flute_length: Option<f64>,
+    /// Units the tool was declared in, for showing and entering its sizes and feeds.
+    /// The values above are always millimetres.
+    #[serde(default)]
+    pub units: Units,
+}
+
+impl Tool {
+    /// A flat endmill in millimetres, with default feeds and no flute length set.
+    pub fn endmill(name: impl Into<String>, diameter: f64, stepover: f64, pass_depth: f64) -> Self {
+        Self {
+            name: name.into(),
+            diameter,
+            stepover,
+            pass_depth,
+            tool_type: ToolType::Endmill { diameter },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
+        }
+    }
+
+    /// A V-bit in millimetres, `diameter` wide at the top of its cone. V-carving sets its
+    /// own depths, so stepover and pass depth are left at zero.
+    pub fn vbit(name: impl Into<String>, angle_degrees: f64, diameter: f64) -> Self {
+        Self {
+            tool_type: ToolType::VBit { angle_degrees },
+            ..Self::endmill(name, diameter, 0.0, 0.0)
+        }
+    }
+}
+
+/// Millimetres in an inch.
+pub const MM_PER_INCH: f64 = 25.4;
+
+/// A unit of length. Lengths are always stored in millimetres; projects and tools record
+/// the unit they were declared in, and values are converted where they are entered, shown
+/// or written out as G-code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Units {
+    #[default]
+    Millimeters,
+    Inches,
+}
+
+impl Units {
+    /// Convert `value` in these units to millimetres. Rates per minute convert the same way.
+    pub fn to_mm(self, value: f64) -> f64 {
+        match self {
+            Units::Millimeters => value,
+            Units::Inches => value * MM_PER_INCH,
+        }
+    }
+
+    /// Convert `value` in millimetres to these units.
+    pub fn from_mm(self, value: f64) -> f64 {
+        match self {
+            Units::Millimeters => value,
+            Units::Inches => value / MM_PER_INCH,
+        }
+    }
+
+    /// Short label, `mm` or `in`.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Units::Millimeters => "mm",
+            Units::Inches => "in",
+        }
+    }
+
+    /// The G-code word that selects these units.
+    pub fn gcode(self) -> &'static str {
+        match self {
+            Units::Millimeters => "G21",
+            Units::Inches => "G20",
+        }
+    }
+}
+
+impl std::str::FromStr for Units {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mm" | "millimeters" | "millimetres" | "metric" => Ok(Units::Millimeters),
+            "in" | "inch" | "inches" | "imperial" => Ok(Units::Inches),
+            other => Err(anyhow::anyhow!("unknown units `{other}`; use mm or in")),
+        }
+    }
+}
+
+impl std::fmt::Display for Units {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.suffix())
+    }
+}
+
+/// Feed rates (mm/min) and spindle speed. Unset values fall back to a more general
+/// setting: operation overrides fall back to the tool, and the tool to the post's default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedSettings {
+    /// Optional: Feed rate for cutting moves.
+    #[serde(default)]
+    pub feed_rate: Option<f64>,
+    /// Optional: Feed rate for vertical plunges; defaults to the feed rate.
+    #[serde(default)]
+    pub plunge_rate: Option<f64>,
+    /// Optional: Feed rate for ramping entry moves; defaults to the plunge rate.
+    #[serde(default)]
+    pub ramp_rate: Option<f64>,
+    /// Optional: Spindle speed (RPM). The spindle is only started when this is set.
+    #[serde(default)]
+    pub spindle_rpm: Option<f64>,
+}
+
+impl FeedSettings {
+    /// Fill unset values from `fallback`.
+    pub fn or(self, fallback: FeedSettings) -> Self {
+        Self {
+            feed_rate: self.feed_rate.or(fallback.feed_rate),
+            plunge_rate: self.plunge_rate.or(fallback.plunge_rate),
+            ramp_rate: self.ramp_rate.or(fallback.ramp_rate),
+            spindle_rpm: self.spindle_rpm.or(fallback.spindle_rpm),
+        }
+    }
 }
 
 /// Defines the geometric type of the tool.
@@ -33,6 +163,159 @@ pub enum CutSide {
     OnLine,
 }
 
+/// Which way the cutter travels relative to the wall it is cutting.
+///
+/// Assumes a clockwise (M3) spindle: climb milling keeps the material on the right of the
+/// direction of travel, conventional milling keeps it on the left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CutDirection {
+    #[default]
+    Climb,
+    Conventional,
+}
+
+impl CutDirection {
+    /// Reverse a closed loop if needed so it cuts in this direction.
+    ///
+    /// `material_inside` says whether the wall being cut lies inside the loop (outside
+    /// profiles, pocket islands) or outside it (inside profiles, pocket walls).
+    pub fn orient(self, loop_2d: &mut [(f64, f64)], material_inside: bool) {
+        let count = loop_2d.len();
+        let twice_area: f64 = (0..count)
+            .map(|i| {
+                let (a, b) = (loop_2d[i], loop_2d[(i + 1) % count]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum();
+        // Material inside a counter-clockwise loop is on the left of travel.
+        let material_on_right = (twice_area > 0.0) != material_inside;
+        if material_on_right != (self == CutDirection::Climb) {
+            loop_2d.reverse();
+        }
+    }
+}
+
+/// How the tool gets down to the cutting depth at the start of each path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum EntryStrategy {
+    /// Straight vertical plunge.
+    #[default]
+    Plunge,
+    /// Descend along the path, no steeper than the given angle from horizontal.
+    Ramp { max_angle_deg: f64 },
+    /// Spiral down on a circle of the given radius (pockets only).
+    Helix { radius: f64, max_angle_deg: f64 },
+}
+
+/// How holding tabs are distributed around a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TabPlacement {
+    /// A fixed number of tabs, spaced evenly.
+    Count(usize),
+    /// One tab per this much path length (mm).
+    Spacing(f64),
+}
+
+/// Holding tabs (bridges) that keep a through-cut part attached to the stock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSettings {
+    pub placement: TabPlacement,
+    /// Length of each tab left in the part, along the cut.
+    pub width: f64,
+    /// Height of each tab above the bottom of the cut.
+    pub height: f64,
+    /// Optional: Manual tab centres as curve parameters (0.0 - 1.0 along the curve).
+    /// Overrides `placement` when not empty.
+    #[serde(default)]
+    pub positions: Vec<f64>,
+}
+
+/// Shape of the approach and departure moves around a profile loop.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LeadKind {
+    /// Quarter-circle arc tangent to the cut.
+    Arc,
+    /// Straight line meeting the cut at 45 degrees.
+    Line,
+}
+
+/// Lead-in/lead-out moves that keep the cutter from dwelling on the part contour.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LeadSettings {
+    pub kind: LeadKind,
+    /// Arc radius or line length (mm).
+    pub length: f64,
+    /// Distance the cut continues past its start before leading out (mm).
+    #[serde(default)]
+    pub overlap: f64,
+}
+
+/// How a pocket's area is cleared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PocketStrategy {
+    /// Contour-parallel rings stepping in from the walls.
+    #[default]
+    Offset,
+    /// Parallel zig-zag passes, joined wherever the link stays inside the pocket.
+    Raster {
+        /// Direction of the passes, in degrees counter-clockwise from the X axis.
+        angle_deg: f64,
+        /// Finish with one ring along the walls and islands to remove the raster scallops.
+        #[serde(default)]
+        boundary_pass: bool,
+    },
+}
+
+/// Settings for the V-carve engine.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VCarveConfig {
+    /// Optional: A maximum depth for flat-bottom v-carving. Saved as `target_depth`, its
+    /// name from before the other settings existed.
+    #[serde(default, rename = "target_depth")]
+    pub flat_depth: Option<f64>,
+    /// Medial-axis edges between two walls whose normals are closer than this angle are
+    /// combing artifacts and are dropped (degrees). Raise it to clean up script fonts.
+    #[serde(default = "default_pruning_angle")]
+    pub pruning_angle_threshold_deg: f64,
+    /// Longest step between samples along curved medial-axis edges (mm).
+    #[serde(default = "default_sampling_resolution")]
+    pub sampling_resolution: f64,
+    /// Outlines and holes smaller than this in both directions are ignored (mm).
+    #[serde(default)]
+    pub min_feature_size: f64,
+}
+
+impl Default for VCarveConfig {
+    fn default() -> Self {
+        Self {
+            flat_depth: None,
+            pruning_angle_threshold_deg: default_pruning_angle(),
+            sampling_resolution: default_sampling_resolution(),
+            min_feature_size: 0.0,
+        }
+    }
+}
+
+fn default_pruning_angle() -> f64 {
+    25.0
+}
+
+fn default_sampling_resolution() -> f64 {
+    0.5
+}
+
+/// The plug half of a V-carve inlay, cut from a second board to fill the pocket carved
+/// for the same curves.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InlayPlug {
+    /// How far below the stock top the plug's walls begin. The plug seats this deep in
+    /// the pocket, so it must be shallower than the pocket to leave room for glue.
+    pub start_depth: f64,
+    /// How much the plug is shrunk on every side to leave room for glue.
+    #[serde(default)]
+    pub glue_gap: f64,
+}
+
 /// Target geometry for an operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OperationTarget {
@@ -55,7 +338,24 @@ pub enum Operation {
         tool_index: usize,
         /// The curves to apply this operation to.
         targets: OperationTarget,
-        // Future additions: tabs, ramps, leads.
+        /// Optional: Depth left for a final finishing pass at full depth.
+        #[serde(default)]
+        finish_pass_depth: Option<f64>,
+        /// Optional: Holding tabs for through cuts.
+        #[serde(default)]
+        tabs: Option<TabSettings>,
+        /// How the tool enters the material on each pass.
+        #[serde(default)]
+        entry: EntryStrategy,
+        /// Climb or conventional milling.
+        #[serde(default)]
+        direction: CutDirection,
+        /// Optional: Lead-in/lead-out moves on the waste side of the cut.
+        #[serde(default)]
+        leads: Option<LeadSettings>,
+        /// Feed and speed overrides for the tool.
+        #[serde(default)]
+        feeds: FeedSettings,
     },
     /// A 2D pocketing operation to clear an area.
     Pocket {
@@ -63,26 +363,347 @@ pub enum Operation {
         tool_index: usize,
         /// The region to pocket (outer boundary with optional holes).
         target: OperationTarget,
-        // Future additions: island handling, pocketing strategy (offset/raster).
+        /// Optional: Depth left for a final finishing pass at full depth.
+        #[serde(default)]
+        finish_pass_depth: Option<f64>,
+        /// How the tool enters the material on each pass.
+        #[serde(default)]
+        entry: EntryStrategy,
+        /// Climb or conventional milling.
+        #[serde(default)]
+        direction: CutDirection,
+        /// Feed and speed overrides for the tool.
+        #[serde(default)]
+        feeds: FeedSettings,
+        /// How the area is cleared.
+        #[serde(default)]
+        strategy: PocketStrategy,
+        /// Optional: A larger tool that already cleared this pocket; only the material it
+        /// could not reach is cut.
+        #[serde(default)]
+        rest_tool_index: Option<usize>,
     },
     /// A V-carving operation.
     VCarve {
-        /// Optional: A maximum depth for flat-bottom v-carving.
-        target_depth: Option<f64>,
+        /// The flat depth and engine settings.
+        #[serde(flatten)]
+        config: VCarveConfig,
         tool_index: usize,
         /// The curves to apply this operation to.
         targets: OperationTarget,
         /// Optional: A second tool for clearing large areas.
         clearance_tool_index: Option<usize>,
+        /// Feed and speed overrides for the V-bit; the clearance tool uses its own.
+        #[serde(default)]
+        feeds: FeedSettings,
+        /// Optional: Carve the mirrored plug of an inlay instead of a pocket. The flat
+        /// depth is then the depth of the flat around the plug.
+        #[serde(default)]
+        inlay_plug: Option<InlayPlug>,
     },
+    /// Drilling a hole at the centre of each circle or point marker.
+    Drill {
+        /// The depth of every hole.
+        target_depth: f64,
+        tool_index: usize,
+        /// The circles or point markers to drill.
+        targets: OperationTarget,
+        /// Optional: Retract to clear chips after drilling this much deeper.
+        #[serde(default)]
+        peck_depth: Option<f64>,
+        /// Optional: Seconds to pause at the bottom of each hole.
+        #[serde(default)]
+        dwell: Option<f64>,
+        /// Feed and speed overrides for the tool; holes are drilled at the plunge rate.
+        #[serde(default)]
+        feeds: FeedSettings,
+    },
+    /// Helical boring of circles larger than the tool.
+    Bore {
+        /// The depth of every hole.
+        target_depth: f64,
+        tool_index: usize,
+        /// The circles to bore.
+        targets: OperationTarget,
+        /// Inside bores a hole the size of each circle; outside leaves a boss of it.
+        cut_side: CutSide,
+        /// How far the helix descends per turn.
+        pitch: f64,
+        /// Climb or conventional milling.
+        #[serde(default)]
+        direction: CutDirection,
+        /// Feed and speed overrides for the tool.
+        #[serde(default)]
+        feeds: FeedSettings,
+    },
+}
+
+impl Operation {
+    /// The board this operation cuts.
+    pub fn board(&self) -> Board {
+        match self {
+            Operation::VCarve {
+                inlay_plug: Some(_),
+                ..
+            } => Board::InlayPlug,
+            _ => Board::Stock,
+        }
+    }
+}
+
+/// A piece of material the project cuts. Each board is a separate program on the machine,
+/// so it is exported, simulated and verified on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Board {
+    /// The project's stock.
+    #[default]
+    Stock,
+    /// The second board inlay plugs are carved from, the same size as the stock.
+    InlayPlug,
+}
+
+impl std::str::FromStr for Board {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "stock" => Ok(Board::Stock),
+            "plug" | "inlay-plug" => Ok(Board::InlayPlug),
+            other => Err(anyhow::anyhow!(
+                "unknown board `{other}`; use stock or plug"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Board::Stock => "stock",
+            Board::InlayPlug => "inlay plug board",
+        })
+    }
+}
+
+/// A single Z level of a multi-pass toolpath.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    /// The Z height of this level (the stock top is Z = 0).
+    pub z: f64,
+    /// Whether this is the final finishing pass at full depth.
+    pub finishing: bool,
+}
+
+impl DepthLevel {
+    /// Plans the Z levels from the stock top down to `target_depth`.
+    ///
+    /// Roughing levels are spaced evenly so that none is deeper than `pass_depth`
+    /// below the previous one. When `finish_depth` is set, roughing stops that far
+    /// above the target and a final finishing level is cut at full depth. A
+    /// non-positive `pass_depth` means a single pass.
+    pub fn schedule(target_depth: f64, pass_depth: f64, finish_depth: Option<f64>) -> Vec<Self> {
+        let target_depth = target_depth.max(0.0);
+        let finish_depth = finish_depth
+            .filter(|finish| *finish > 0.0 && *finish < target_depth)
+            .map(|finish| {
+                if pass_depth > 0.0 {
+                    finish.min(pass_depth)
+                } else {
+                    finish
+                }
+            });
+
+        let rough_depth = target_depth - finish_depth.unwrap_or(0.0);
+        let steps = if pass_depth > 0.0 {
+            ((rough_depth / pass_depth) - 1e-9).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+
+        let mut levels: Vec<Self> = (1..=steps)
+            .map(|step| Self {
+                z: -rough_depth * step as f64 / steps as f64,
+                finishing: false,
+            })
+            .collect();
+        if finish_depth.is_some() {
+            levels.push(Self {
+                z: -target_depth,
+                finishing: true,
+            });
+        }
+        levels
+    }
+}
+
+/// A canned drilling cycle, repeated for every hole of a toolpath.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrillCycle {
+    /// Depth of each peck before retracting to clear chips; `None` drills in one feed.
+    #[serde(default)]
+    pub peck_depth: Option<f64>,
+    /// Seconds to pause at the bottom of the hole.
+    #[serde(default)]
+    pub dwell: Option<f64>,
 }
 
 /// Represents a complete, continuous 3D tool movement path.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Toolpath {
     /// A list of (X, Y, Z) coordinates.
     pub paths: Vec<Vec<(f64, f64, f64)>>,
-    // Metadata could include feed rates, spindle speeds, etc.
+    /// The Z levels this toolpath steps through, from the stock top down.
+    /// Empty when the paths are not organised into levels (e.g. V-carve creases).
+    #[serde(default)]
+    pub levels: Vec<DepthLevel>,
+    /// Index into `levels` for each entry of `paths`; empty when `levels` is.
+    #[serde(default)]
+    pub path_levels: Vec<usize>,
+    /// Centres of the holding tabs left along the path, in XY.
+    #[serde(default)]
+    pub tabs: Vec<(f64, f64)>,
+    /// Feeds and speed to cut with. Each move takes the rate its [`SegmentFeed`] picks.
+    #[serde(default)]
+    pub feeds: FeedSettings,
+    /// When set, every path is one hole: a feed straight down from its first point (the
+    /// retract plane) to its last (the bottom), cut with this cycle.
+    #[serde(default)]
+    pub drill: Option<DrillCycle>,
+    /// Circular arcs fitted to each entry of `paths`, in order along the path. Empty when
+    /// none were fitted, and cleared when the paths are reordered; the points stay, so
+    /// previews and checks can ignore the arcs.
+    #[serde(default)]
+    pub arcs: Vec<Vec<PathArc>>,
+    /// The rate each move of each entry of `paths` is cut at, one per point after the
+    /// first. Empty for paths whose moves all take their rate from their direction.
+    #[serde(default)]
+    pub segment_feeds: Vec<Vec<SegmentFeed>>,
+}
+
+/// Which of a toolpath's feed rates a move is cut at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentFeed {
+    /// Chosen from the move's direction: vertical descents plunge, other descents ramp
+    /// and everything else cuts.
+    #[default]
+    Auto,
+    Cut,
+    Plunge,
+    Ramp,
+}
+
+impl SegmentFeed {
+    /// The rate to move from `from` to `to` at, resolving `Auto` by direction.
+    pub fn resolve(self, from: (f64, f64, f64), to: (f64, f64, f64)) -> Self {
+        if self != SegmentFeed::Auto {
+            return self;
+        }
+        let moves_xy = to.0 != from.0 || to.1 != from.1;
+        match (to.2 < from.2, moves_xy) {
+            (true, true) => SegmentFeed::Ramp,
+            (true, false) => SegmentFeed::Plunge,
+            (false, _) => SegmentFeed::Cut,
+        }
+    }
+}
+
+/// A circular arc standing in for a run of points along a toolpath path.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PathArc {
+    /// Index of the point the arc starts at.
+    pub start: usize,
+    /// Index of the point the arc ends at; the points in between lie on the arc.
+    pub end: usize,
+    /// Centre of the arc in XY.
+    pub center: (f64, f64),
+    pub clockwise: bool,
+}
+
+impl Toolpath {
+    /// Creates a toolpath without depth level information.
+    pub fn new(paths: Vec<Vec<(f64, f64, f64)>>) -> Self {
+        Self {
+            paths,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the depth level of the path at `path_index`, if levels are recorded.
+    pub fn level_of(&self, path_index: usize) -> Option<&DepthLevel> {
+        self.path_levels
+            .get(path_index)
+            .and_then(|level| self.levels.get(*level))
+    }
+
+    /// The rate of the move to point `point` of path `path`, never `Auto`.
+    pub fn segment_feed(&self, path: usize, point: usize) -> SegmentFeed {
+        let points = &self.paths[path];
+        let feed = match self.segment_feeds.get(path) {
+            Some(feeds) if feeds.len() + 1 == points.len() => feeds[point - 1],
+            _ => SegmentFeed::Auto,
+        };
+        feed.resolve(points[point - 1], points[point])
+    }
+
+    /// The recorded rates of the moves of path `path`, `Auto` where none was recorded.
+    pub fn path_segment_feeds(&self, path: usize) -> Vec<SegmentFeed> {
+        let moves = self.paths[path].len().saturating_sub(1);
+        match self.segment_feeds.get(path) {
+            Some(feeds) if feeds.len() == moves => feeds.clone(),
+            _ => vec![SegmentFeed::Auto; moves],
+        }
+    }
+
+    /// Records the rates of the moves of path `path`.
+    pub fn set_segment_feeds(&mut self, path: usize, feeds: Vec<SegmentFeed>) {
+        if self.segment_feeds.len() <= path {
+            self.segment_feeds.resize(path + 1, Vec::new());
+        }
+        self.segment_feeds[path] = feeds;
+    }
+
+    /// Appends the paths of `other`, merging depth levels that share a Z height.
+    ///
+    /// Level information is dropped if either side has paths without levels.
+    pub fn append(&mut self, other: Toolpath) {
+        self.tabs.extend_from_slice(&other.tabs);
+        if !other.segment_feeds.is_empty() {
+            self.segment_feeds.resize(self.paths.len(), Vec::new());
+            self.segment_feeds.extend(other.segment_feeds);
+        }
+        if self.arcs.len() == self.paths.len() && other.arcs.len() == other.paths.len() {
+            self.arcs.extend(other.arcs);
+        } else {
+            self.arcs.clear();
+        }
+        let self_levelled = self.paths.is_empty() || self.path_levels.len() == self.paths.len();
+        let other_levelled = other.paths.is_empty() || other.path_levels.len() == other.paths.len();
+        if !(self_levelled && other_levelled) {
+            self.paths.extend(other.paths);
+            self.levels.clear();
+            self.path_levels.clear();
+            return;
+        }
+
+        let remap: Vec<usize> = other
+            .levels
+            .iter()
+            .map(|level| {
+                match self.levels.iter().position(|existing| {
+                    (existing.z - level.z).abs() < 1e-9 && existing.finishing == level.finishing
+                }) {
+                    Some(index) => index,
+                    None => {
+                        self.levels.push(*level);
+                        self.levels.len() - 1
+                    }
+                }
+            })
+            .collect();
+        self.paths.extend(other.paths);
+        self.path_levels
+            .extend(other.path_levels.into_iter().map(|level| remap[level]));
+    }
 }
 
 /// Represents the final, machine-specific G-code output.
@@ -104,10 +725,52 @@ mod tests {
             stepover: 0.4,
             pass_depth: 5.0,
             tool_type: ToolType::Endmill { diameter: 6.0 },
+            feeds: FeedSettings::default(),
+            flute_length: None,
+            units: Units::Millimeters,
         };
         assert_eq!(tool.diameter, 6.0);
     }
 
+    #[test]
+    fn test_tool_without_feeds_deserializes() {
+        let json = r#"{"name":"6mm Endmill","diameter":6.0,"stepover":0.4,"pass_depth":5.0,"tool_type":{"Endmill":{"diameter":6.0}}}"#;
+        let tool: Tool = serde_json::from_str(json).unwrap();
+        assert_eq!(tool.feeds, FeedSettings::default());
+        assert_eq!(tool.units, Units::Millimeters);
+    }
+
+    #[test]
+    fn test_units_convert_and_parse() {
+        assert_eq!(Units::Inches.to_mm(0.25), 6.35);
+        assert!((Units::Inches.from_mm(6.35) - 0.25).abs() < 1e-12);
+        assert_eq!(Units::Millimeters.to_mm(6.35), 6.35);
+        assert_eq!("in".parse::<Units>().unwrap(), Units::Inches);
+        assert_eq!(" Metric ".parse::<Units>().unwrap(), Units::Millimeters);
+        assert!("furlongs".parse::<Units>().is_err());
+        assert_eq!(Units::Inches.gcode(), "G20");
+        assert_eq!(Units::Millimeters.to_string(), "mm");
+    }
+
+    #[test]
+    fn test_feed_settings_override() {
+        let tool = FeedSettings {
+            feed_rate: Some(1200.0),
+            plunge_rate: Some(300.0),
+            ramp_rate: None,
+            spindle_rpm: Some(18000.0),
+        };
+        let op = FeedSettings {
+            feed_rate: Some(800.0),
+            ..Default::default()
+        };
+        let merged = op.or(tool);
+        assert_eq!(merged.feed_rate, Some(800.0));
+        assert_eq!(merged.plunge_rate, Some(300.0));
+        assert_eq!(merged.ramp_rate, None);
+        assert_eq!(merged.spindle_rpm, Some(18000.0));
+    }
+
     #[test]
     fn test_operation_construction() {
         let curve_id = CurveId::new();
@@ -116,6 +779,12 @@ mod tests {
             cut_side: CutSide::Outside,
             tool_index: 0,
             targets: OperationTarget::Curves(vec![curve_id]),
+            finish_pass_depth: None,
+            tabs: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
         };
         match op {
             Operation::Profile { target_depth, .. } => {
@@ -127,11 +796,97 @@ mod tests {
 
     #[test]
     fn test_toolpath_construction() {
-        let toolpath = Toolpath {
-            paths: vec![vec![(0.0, 0.0, -5.0), (100.0, 0.0, -5.0)]],
-        };
+        let toolpath = Toolpath::new(vec![vec![(0.0, 0.0, -5.0), (100.0, 0.0, -5.0)]]);
         assert_eq!(toolpath.paths.len(), 1);
         assert_eq!(toolpath.paths[0].len(), 2);
+        assert!(toolpath.level_of(0).is_none());
+    }
+
+    #[test]
+    fn test_depth_schedule_even_steps() {
+        let levels = DepthLevel::schedule(10.0, 3.0, None);
+        assert_eq!(levels.len(), 4);
+        assert!((levels[0].z + 2.5).abs() < 1e-9);
+        assert!((levels[3].z + 10.0).abs() < 1e-9);
+        assert!(levels.iter().all(|level| !level.finishing));
+
+        let mut previous = 0.0;
+        for level in &levels {
+            assert!(previous - level.z <= 3.0 + 1e-9);
+            previous = level.z;
+        }
+    }
+
+    #[test]
+    fn test_depth_schedule_exact_multiple_and_single_pass() {
+        assert_eq!(DepthLevel::schedule(6.0, 3.0, None).len(), 2);
+        assert_eq!(DepthLevel::schedule(5.0, 5.0, None).len(), 1);
+
+        let single = DepthLevel::schedule(5.0, 0.0, None);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].z, -5.0);
+    }
+
+    #[test]
+    fn test_depth_schedule_with_finishing_pass() {
+        let levels = DepthLevel::schedule(10.0, 3.0, Some(0.5));
+        let last = levels.last().unwrap();
+        assert!(last.finishing);
+        assert_eq!(last.z, -10.0);
+
+        let roughing = &levels[..levels.len() - 1];
+        assert_eq!(roughing.len(), 4);
+        assert!((roughing[3].z + 9.5).abs() < 1e-9);
+
+        // A finishing allowance as deep as the whole cut is ignored.
+        let levels = DepthLevel::schedule(2.0, 3.0, Some(2.0));
+        assert_eq!(levels.len(), 1);
+        assert!(!levels[0].finishing);
+    }
+
+    #[test]
+    fn test_toolpath_append_merges_levels() {
+        let levels = DepthLevel::schedule(4.0, 2.0, None);
+        let make = |x: f64| Toolpath {
+            paths: vec![vec![(x, 0.0, -2.0)], vec![(x, 0.0, -4.0)]],
+            levels: levels.clone(),
+            path_levels: vec![0, 1],
+            ..Default::default()
+        };
+
+        let mut combined = Toolpath::default();
+        combined.append(make(0.0));
+        combined.append(make(10.0));
+        assert_eq!(combined.levels.len(), 2);
+        assert_eq!(combined.path_levels, vec![0, 1, 0, 1]);
+        assert_eq!(combined.level_of(3).unwrap().z, -4.0);
+
+        combined.append(Toolpath::new(vec![vec![(0.0, 0.0, 0.0)]]));
+        assert!(combined.levels.is_empty());
+        assert!(combined.path_levels.is_empty());
+        assert_eq!(combined.paths.len(), 5);
+    }
+
+    #[test]
+    fn test_vcarve_config_loads_from_target_depth() {
+        // Projects saved before the engine settings existed only have `target_depth`.
+        let json = r#"{"VCarve": {"target_depth": 4.0, "tool_index": 0,
+            "targets": {"Curves": []}, "clearance_tool_index": null}}"#;
+        let operation: Operation = serde_json::from_str(json).unwrap();
+        let Operation::VCarve { config, .. } = &operation else {
+            panic!("expected a V-carve");
+        };
+        assert_eq!(
+            *config,
+            VCarveConfig {
+                flat_depth: Some(4.0),
+                ..VCarveConfig::default()
+            }
+        );
+
+        let saved = serde_json::to_value(&operation).unwrap();
+        assert_eq!(saved["VCarve"]["target_depth"], 4.0);
+        assert_eq!(saved["VCarve"]["pruning_angle_threshold_deg"], 25.0);
     }
 
     #[test]
@@ -1,4 +1,5 @@
-use crate::types::{Tool, ToolType};
+use crate::geometry::offset::is_point_inside;
+use crate::types::{InlayPlug, Tool, ToolType, VCarveConfig};
 use anyhow::{anyhow, Result};
 use boostvoronoi::{
     Builder, Diagram, Edge,
@@ -6,27 +7,26 @@ use boostvoronoi::{
 };
 use cavalier_contours::polyline::{Polyline, PlineSource, PlineSourceMut, PlineVertex};
 use cavalier_contours::core::math::Vector2;
-use clipper2::{inflate, EndType, JoinType, Path as CPath, PathType as CPathType, Polygon as CPolygon, Polygons as CPolygons, Vertex as CVertex};
+use clipper2::{difference, inflate, EndType, JoinType, Path as CPath, PathType as CPathType, Polygon as CPolygon, Polygons as CPolygons, Vertex as CVertex};
 use glam::DVec2;
+use std::collections::{HashMap, HashSet};
 
 const SCALE: f64 = 1000.0; // Microns
 
-/// Configuration for the V-Carve operation
-#[derive(Debug, Clone)]
-pub struct VCarveConfig {
-    pub bit_angle_deg: f64,
-    pub max_cut_depth: f64,
-    pub pruning_angle_threshold_deg: f64,
-}
+/// Flat border left around an inlay plug beyond the foot of its walls (mm).
+const PLUG_MARGIN: f64 = 3.0;
+
+/// Crease segment ends closer than this are treated as the same medial-axis node (mm).
+const NODE_TOLERANCE: f64 = 1e-6;
 
 /// Represents the different types of paths generated by the hybrid engine
 #[derive(Debug, Clone)]
 pub enum PathType {
     /// Derived from Voronoi. Z varies.
-    /// Used for corners and narrow sections.
+    /// Used for corners and narrow sections. One branch of the medial axis, running
+    /// between branch points or free ends.
     Crease {
-        start: [f64; 3],
-        end: [f64; 3],
+        path: Vec<[f64; 3]>,
     },
     /// Derived from Cavalier Contours Offset. Z is constant (max_depth).
     /// Used for wide "pocket" areas.
@@ -76,7 +76,11 @@ pub fn generate_vcarve_toolpath(
     tool: &Tool,
     max_depth: Option<f64>,
 ) -> Result<Vec<PathType>> {
-    let result = generate_vcarve_toolpath_with_debug(polygons, tool, max_depth, false)?;
+    let config = VCarveConfig {
+        flat_depth: max_depth,
+        ..VCarveConfig::default()
+    };
+    let result = generate_vcarve_toolpath_with_debug(polygons, tool, &config, false)?;
     Ok(result.paths)
 }
 
@@ -84,9 +88,16 @@ pub fn generate_vcarve_toolpath(
 pub fn generate_vcarve_toolpath_with_debug(
     polygons: &[CarvePolygon],
     tool: &Tool,
-    max_depth: Option<f64>,
+    config: &VCarveConfig,
     collect_debug: bool,
 ) -> Result<VCarveResult> {
+    if config.sampling_resolution <= 0.0 {
+        return Err(anyhow!("V-carve sampling resolution must be positive"));
+    }
+    if !(0.0..90.0).contains(&config.pruning_angle_threshold_deg) {
+        return Err(anyhow!("V-carve pruning angle must be between 0 and 90 degrees"));
+    }
+    let max_depth = config.flat_depth;
     let vbit_angle = match tool.tool_type {
         ToolType::VBit { angle_degrees } => angle_degrees,
         _ => return Err(anyhow!("V-carve requires a V-bit tool")),
@@ -102,10 +113,7 @@ pub fn generate_vcarve_toolpath_with_debug(
         f64::MAX
     };
 
-    // Config for pruning (hardcoded or derived)
-    // Default to ~25 degrees as requested
-    let pruning_angle_threshold_deg: f64 = 25.0;
-    let pruning_threshold = pruning_angle_threshold_deg.to_radians().cos();
+    let pruning_threshold = config.pruning_angle_threshold_deg.to_radians().cos();
 
     let mut output_paths = Vec::new();
     let mut debug_output = if collect_debug {
@@ -120,6 +128,10 @@ pub fn generate_vcarve_toolpath_with_debug(
     let mut shape_polylines = Vec::new(); // For point-in-poly check and offsetting
 
     for poly in polygons {
+        if is_below_feature_size(&poly.outer, config.min_feature_size) {
+            continue;
+        }
+
         // Convert outer
         let mut outer_pl = create_clean_polyline(&poly.outer);
         // Ensure CCW
@@ -132,7 +144,11 @@ pub fn generate_vcarve_toolpath_with_debug(
         }
 
         // Convert holes
-        for hole in &poly.holes {
+        let holes = poly
+            .holes
+            .iter()
+            .filter(|hole| !is_below_feature_size(hole, config.min_feature_size));
+        for hole in holes {
             let mut hole_pl = create_clean_polyline(hole);
             // Ensure CW
             if hole_pl.area() > 0.0 {
@@ -230,6 +246,7 @@ pub fn generate_vcarve_toolpath_with_debug(
     // 2. Iterate Edges and Prune (The Spine)
     // If pocket boundary collapsed, we need to generate constant-depth paths as fallback
     let fallback_to_constant_depth = max_depth.is_some() && !pocket_boundary_generated;
+    let mut crease_segments = Vec::new();
     
     for edge_rc in diagram.edges() {
         let edge = edge_rc.get();
@@ -291,37 +308,289 @@ pub fn generate_vcarve_toolpath_with_debug(
             max_depth_value, 
             &shape_polylines,
             fallback_to_constant_depth,
+            config.sampling_resolution,
         );
         
-        // Convert samples to PathType::Crease
+        // Collect crease segments; they are chained into branches once every edge is in
         for samples in sample_chains {
-            if samples.len() >= 2 {
-                for i in 0..samples.len() - 1 {
-                    let p1 = samples[i];
-                    let p2 = samples[i+1];
-                    output_paths.push(PathType::Crease {
-                        start: [p1.0, p1.1, p1.2],
-                        end: [p2.0, p2.1, p2.2],
-                    });
-                    
-                    // Record crease path for debug
-                    if let Some(ref mut debug) = debug_output {
-                        debug.crease_paths.push([
-                            [p1.0, p1.1, p1.2],
-                            [p2.0, p2.1, p2.2],
-                        ]);
-                    }
+            for pair in samples.windows(2) {
+                let p1 = [pair[0].0, pair[0].1, pair[0].2];
+                let p2 = [pair[1].0, pair[1].1, pair[1].2];
+                crease_segments.push([p1, p2]);
+
+                // Record crease path for debug
+                if let Some(ref mut debug) = debug_output {
+                    debug.crease_paths.push([p1, p2]);
                 }
             }
         }
     }
 
+    output_paths.extend(
+        chain_crease_segments(&crease_segments)
+            .into_iter()
+            .map(|path| PathType::Crease { path }),
+    );
+
     Ok(VCarveResult {
         paths: output_paths,
         debug: debug_output,
     })
 }
 
+/// The area to V-carve around the plug of an inlay.
+///
+/// The plug is the mirror image of `polygons` (flipped left to right about the centre of
+/// their bounds, so it lands in the same place on the plug board), shrunk by the glue gap.
+/// The result is a rectangle around it with the plug cut out, wide enough for the bit to
+/// reach `flat_depth` all the way round.
+pub fn inlay_plug_polygons(
+    polygons: &[CarvePolygon],
+    tool: &Tool,
+    plug: &InlayPlug,
+    flat_depth: f64,
+) -> Result<Vec<CarvePolygon>> {
+    let vbit_angle = match tool.tool_type {
+        ToolType::VBit { angle_degrees } => angle_degrees,
+        _ => return Err(anyhow!("V-carve requires a V-bit tool")),
+    };
+    if plug.start_depth < 0.0 {
+        return Err(anyhow!("Plug start depth must not be negative"));
+    }
+    if plug.glue_gap < 0.0 {
+        return Err(anyhow!("Glue gap must not be negative"));
+    }
+    if flat_depth <= plug.start_depth {
+        return Err(anyhow!("Plug flat depth must be deeper than its start depth"));
+    }
+
+    let points = polygons.iter().flat_map(|poly| poly.outer.iter());
+    let (min_x, max_x) = points
+        .clone()
+        .fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    if min_x > max_x {
+        return Err(anyhow!("Inlay requires at least one closed polygon"));
+    }
+
+    // Mirroring reverses each ring, so reverse it back to keep outers and holes apart.
+    let mirror = |ring: &[(f64, f64)]| {
+        let points: Vec<CVertex> = ring
+            .iter()
+            .rev()
+            .map(|p| CVertex::new(min_x + max_x - p.0, p.1))
+            .collect();
+        CPath::new(points, true)
+    };
+    let plug_paths = polygons
+        .iter()
+        .flat_map(|poly| std::iter::once(&poly.outer).chain(poly.holes.iter()))
+        .map(|ring| mirror(ring))
+        .collect();
+    let mut plug_shape = CPolygons::new(vec![CPolygon::new(plug_paths, CPathType::Clip)]);
+    if plug.glue_gap > 0.0 {
+        plug_shape = inflate(
+            plug_shape,
+            -plug.glue_gap,
+            JoinType::Round,
+            EndType::ClosedPolygon,
+            2.0,  // Miter limit (unused for Round joins)
+            0.01, // Arc tolerance
+        );
+    }
+
+    let wall_width = (flat_depth - plug.start_depth) * (vbit_angle.to_radians() / 2.0).tan();
+    let margin = 2.0 * wall_width + PLUG_MARGIN;
+    let border = CPath::new(
+        vec![
+            CVertex::new(min_x - margin, min_y - margin),
+            CVertex::new(max_x + margin, min_y - margin),
+            CVertex::new(max_x + margin, max_y + margin),
+            CVertex::new(min_x - margin, max_y + margin),
+        ],
+        true,
+    );
+    let background = difference(
+        CPolygons::new(vec![CPolygon::new(vec![border], CPathType::Subject)]),
+        plug_shape,
+    );
+
+    // Clipper winds outer boundaries counter-clockwise and holes clockwise.
+    let mut outers = Vec::new();
+    let mut holes = Vec::new();
+    for polygon in background.polygons() {
+        for path in polygon.paths() {
+            let ring: Vec<(f64, f64)> = path.vertices().iter().map(|v| (v.x(), v.y())).collect();
+            if ring.len() < 3 {
+                continue;
+            }
+            if ring_area(&ring) > 0.0 {
+                outers.push(ring);
+            } else {
+                holes.push(ring);
+            }
+        }
+    }
+
+    // Each hole belongs to the smallest outer boundary around it.
+    let mut carve_polygons: Vec<CarvePolygon> = outers
+        .into_iter()
+        .map(|outer| CarvePolygon { outer, holes: Vec::new() })
+        .collect();
+    for hole in holes {
+        let owner = carve_polygons
+            .iter_mut()
+            .filter(|poly| is_point_inside(&hole[0], &poly.outer))
+            .min_by(|a, b| ring_area(&a.outer).total_cmp(&ring_area(&b.outer)));
+        if let Some(owner) = owner {
+            owner.holes.push(hole);
+        }
+    }
+    Ok(carve_polygons)
+}
+
+fn ring_area(ring: &[(f64, f64)]) -> f64 {
+    let count = ring.len();
+    (0..count)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % count]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Whether a ring fits inside a `min_feature_size` square, so it is too small to carve.
+fn is_below_feature_size(ring: &[(f64, f64)], min_feature_size: f64) -> bool {
+    if min_feature_size <= 0.0 || ring.is_empty() {
+        return false;
+    }
+    let (mut min, mut max) = (ring[0], ring[0]);
+    for p in ring {
+        min = (min.0.min(p.0), min.1.min(p.1));
+        max = (max.0.max(p.0), max.1.max(p.1));
+    }
+    max.0 - min.0 < min_feature_size && max.1 - min.1 < min_feature_size
+}
+
+/// Stitch crease segments into polylines along the medial-axis graph.
+///
+/// Every Voronoi edge is sampled along with its twin, so a segment traced both ways is
+/// carved once. Polylines split only where three or more branches meet, and they are
+/// ordered (and reversed where needed) so that each starts where the last one ended
+/// whenever the graph allows, keeping the tool down across branch points.
+fn chain_crease_segments(segments: &[[[f64; 3]; 2]]) -> Vec<Vec<[f64; 3]>> {
+    // Snap segment ends to a grid so samples of the same point meet.
+    let mut nodes: Vec<[f64; 3]> = Vec::new();
+    let mut node_ids: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+    for segment in segments {
+        let [a, b] = segment.map(|p| {
+            let key = (
+                (p[0] / NODE_TOLERANCE).round() as i64,
+                (p[1] / NODE_TOLERANCE).round() as i64,
+            );
+            *node_ids.entry(key).or_insert_with(|| {
+                nodes.push(p);
+                nodes.len() - 1
+            })
+        });
+        if a != b && seen.insert((a.min(b), a.max(b))) {
+            edges.push((a, b));
+        }
+    }
+    let mut adjacency = vec![Vec::new(); nodes.len()];
+    for (index, &(a, b)) in edges.iter().enumerate() {
+        adjacency[a].push(index);
+        adjacency[b].push(index);
+    }
+
+    // Walk each branch from a branch point or free end to the next one, then pick up
+    // closed loops, which have neither.
+    let mut used = vec![false; edges.len()];
+    let walk = |start: usize, mut edge: usize, used: &mut [bool]| {
+        let mut branch = vec![start];
+        let mut node = start;
+        loop {
+            used[edge] = true;
+            let (a, b) = edges[edge];
+            node = if a == node { b } else { a };
+            branch.push(node);
+            if adjacency[node].len() != 2 {
+                break;
+            }
+            match adjacency[node].iter().find(|&&next| !used[next]) {
+                Some(&next) => edge = next,
+                None => break,
+            }
+        }
+        branch
+    };
+    let mut branches = Vec::new();
+    for (node, incident) in adjacency.iter().enumerate() {
+        if incident.len() != 2 {
+            for &edge in incident {
+                if !used[edge] {
+                    branches.push(walk(node, edge, &mut used));
+                }
+            }
+        }
+    }
+    for edge in 0..edges.len() {
+        if !used[edge] {
+            branches.push(walk(edges[edge].0, edge, &mut used));
+        }
+    }
+
+    let mut ends = vec![Vec::new(); nodes.len()];
+    for (index, branch) in branches.iter().enumerate() {
+        ends[branch[0]].push(index);
+        ends[branch[branch.len() - 1]].push(index);
+    }
+
+    // Carry on from where the last branch ended; failing that, jump to the nearest
+    // free end so the next run can reach as far as possible.
+    let mut done = vec![false; branches.len()];
+    let mut ordered = Vec::with_capacity(branches.len());
+    let mut at: Option<usize> = None;
+    for _ in 0..branches.len() {
+        let next = at.and_then(|node| ends[node].iter().copied().find(|&index| !done[index]));
+        let (index, start) = match (next, at) {
+            (Some(index), Some(node)) => (index, node),
+            _ => {
+                let from = at.map(|node| nodes[node]);
+                let free_end = |node: usize| {
+                    ends[node].iter().filter(|&&index| !done[index]).count() == 1
+                };
+                let distance = |node: usize| {
+                    from.map_or(0.0, |p| (nodes[node][0] - p[0]).hypot(nodes[node][1] - p[1]))
+                };
+                (0..branches.len())
+                    .filter(|&index| !done[index])
+                    .flat_map(|index| {
+                        let branch = &branches[index];
+                        [(index, branch[0]), (index, branch[branch.len() - 1])]
+                    })
+                    .min_by(|a, b| {
+                        (!free_end(a.1))
+                            .cmp(&!free_end(b.1))
+                            .then(distance(a.1).total_cmp(&distance(b.1)))
+                    })
+                    .expect("an unfinished branch remains")
+            }
+        };
+        done[index] = true;
+        let mut branch = branches[index].clone();
+        if branch[0] != start {
+            branch.reverse();
+        }
+        at = branch.last().copied();
+        ordered.push(branch.into_iter().map(|node| nodes[node]).collect());
+    }
+    ordered
+}
+
 /// Extract 2D coordinates from a Voronoi edge for debug visualization
 fn extract_edge_coords(edge: &Edge, diagram: &Diagram<F>) -> Option<[[f64; 2]; 2]> {
     let v0_idx = edge.vertex0()?;
@@ -337,8 +606,8 @@ fn extract_edge_coords(edge: &Edge, diagram: &Diagram<F>) -> Option<[[f64; 2]; 2
     let p0 = v0_rc.get();
     let p1 = v1_rc.get();
     
-    let start = [p0.x() as f64 / SCALE, p0.y() as f64 / SCALE];
-    let end = [p1.x() as f64 / SCALE, p1.y() as f64 / SCALE];
+    let start = [p0.x() / SCALE, p0.y() / SCALE];
+    let end = [p1.x() / SCALE, p1.y() / SCALE];
     
     Some([start, end])
 }
@@ -351,8 +620,7 @@ fn create_clean_polyline(points: &[(f64, f64)]) -> Polyline {
     let mut last = points[0];
     pline.add_vertex(PlineVertex::new(last.0, last.1, 0.0));
     
-    for i in 1..points.len() {
-        let curr = points[i];
+    for &curr in points.iter().skip(1) {
         if (curr.0 - last.0).abs() > 1e-6 || (curr.1 - last.1).abs() > 1e-6 {
             pline.add_vertex(PlineVertex::new(curr.0, curr.1, 0.0));
             last = curr;
@@ -381,9 +649,8 @@ fn create_clean_polyline(points: &[(f64, f64)]) -> Polyline {
     let mut unique_points = Vec::new();
     unique_points.push(points[0]);
     
-    for i in 1..points.len() {
+    for &curr in points.iter().skip(1) {
         let prev = unique_points.last().unwrap();
-        let curr = points[i];
         if (curr.0 - prev.0).abs() > 1e-6 || (curr.1 - prev.1).abs() > 1e-6 {
              unique_points.push(curr);
         }
@@ -531,6 +798,7 @@ fn sample_edge_to_3d(
     max_depth: f64, 
     polylines: &[Polyline],
     fallback_to_constant_depth: bool,
+    sampling_resolution: f64,
 ) -> Vec<Vec<(f64, f64, f64)>> {
     let v0_idx = edge.vertex0();
     if v0_idx.is_none() {
@@ -564,8 +832,8 @@ fn sample_edge_to_3d(
     let p0 = v0_rc.unwrap().get();
     let p1 = v1_rc.unwrap().get();
     
-    let start = DVec2::new(p0.x() as f64 / SCALE, p0.y() as f64 / SCALE);
-    let end = DVec2::new(p1.x() as f64 / SCALE, p1.y() as f64 / SCALE);
+    let start = DVec2::new(p0.x() / SCALE, p0.y() / SCALE);
+    let end = DVec2::new(p1.x() / SCALE, p1.y() / SCALE);
     
     let max_radius = max_depth * tan_half_angle;
     
@@ -608,7 +876,7 @@ fn sample_edge_to_3d(
     } else {
         // Curved edge (Parabolic)
         // Subdivide.
-        let count = 10; 
+        let count = ((end - start).length() / sampling_resolution).ceil().max(1.0) as usize;
         for i in 0..=count {
              let t = i as f64 / count as f64;
              // Linear approximation for now
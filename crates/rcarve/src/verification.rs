@@ -0,0 +1,220 @@
+use crate::project::{Project, ToolpathArtifact};
+use crate::simulation::Heightmap;
+use crate::toolpath_generation::operation_cut_order;
+use crate::types::{Board, Tool, Toolpath};
+use crate::ToolLibrary;
+
+/// Finest grid the stock is checked on (mm).
+const VERIFY_RESOLUTION: f64 = 0.5;
+
+/// Larger stock is checked on a coarser grid of about this many cells.
+const VERIFY_CELLS: f64 = 1_000_000.0;
+
+/// Depth differences smaller than this are rounding, not a problem (mm).
+const DEPTH_TOLERANCE: f64 = 0.01;
+
+/// What checking one operation's toolpaths found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Verification {
+    pub warnings: Vec<String>,
+    /// Whether a problem would damage the tool or the work, so the toolpath must not run.
+    pub fatal: bool,
+}
+
+impl Verification {
+    fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    fn fail(&mut self, warning: String) {
+        self.warnings.push(warning);
+        self.fatal = true;
+    }
+}
+
+/// Replay every generated toolpath in cutting order on a simulated stock and check it.
+///
+/// Rapids that drop into uncut stock and cuts deeper below the material around the tool
+/// than its flutes reach are fatal. Cutting below the stock into the spoilboard is only a
+/// warning, since through cuts often go a little deeper on purpose. Each board starts from
+/// fresh stock. Returns one result per operation; operations without a toolpath come back
+/// empty.
+pub fn verify_toolpaths(project: &Project, tools: &ToolLibrary) -> Vec<Verification> {
+    let mut results = vec![Verification::default(); project.operations.len()];
+    let stock = &project.stock;
+    let resolution = VERIFY_RESOLUTION.max((stock.width * stock.height / VERIFY_CELLS).sqrt());
+
+    for board in [Board::Stock, Board::InlayPlug] {
+        let Ok(mut heightmap) = Heightmap::new(stock, resolution) else {
+            return results;
+        };
+        for index in operation_cut_order(project) {
+            if project.operations[index].board() != board {
+                continue;
+            }
+            let Some(artifact) = project.toolpath_for_operation(index) else {
+                continue;
+            };
+            for (toolpath, tool_index) in artifact_passes(artifact, project, index) {
+                if let Some(tool) = tools.tools.get(tool_index) {
+                    verify_pass(&mut heightmap, toolpath, tool, &mut results[index]);
+                }
+            }
+        }
+    }
+    results
+}
+
+/// The toolpaths an artifact cuts, with the tool each uses.
+pub(crate) fn artifact_passes<'a>(
+    artifact: &'a ToolpathArtifact,
+    project: &Project,
+    index: usize,
+) -> Vec<(&'a Toolpath, usize)> {
+    if artifact.passes.is_empty() {
+        let summaries = project.operation_summaries();
+        return summaries
+            .get(index)
+            .map(|summary| vec![(&artifact.toolpath, summary.primary_tool_index)])
+            .unwrap_or_default();
+    }
+    artifact
+        .passes
+        .iter()
+        .map(|pass| (&pass.toolpath, pass.tool_index))
+        .collect()
+}
+
+/// Check one pass against the stock as cut so far, then cut it.
+fn verify_pass(
+    heightmap: &mut Heightmap,
+    toolpath: &Toolpath,
+    tool: &Tool,
+    verification: &mut Verification,
+) {
+    // Drill cycles rapid down to their retract plane above each hole.
+    if toolpath.drill.is_some() {
+        let radius = tool.diameter / 2.0;
+        let into_stock = toolpath
+            .paths
+            .iter()
+            .filter_map(|path| path.first())
+            .find(|p| {
+                heightmap
+                    .highest_within(p.0, p.1, radius)
+                    .is_some_and(|z| z > p.2 + DEPTH_TOLERANCE)
+            });
+        if let Some(p) = into_stock {
+            verification.fail(format!(
+                "Rapid move into uncut stock at X{:.2} Y{:.2}; raise the retract height.",
+                p.0, p.1
+            ));
+        }
+    }
+
+    let lowest = toolpath
+        .paths
+        .iter()
+        .flatten()
+        .map(|p| p.2)
+        .fold(f64::INFINITY, f64::min);
+    if lowest < heightmap.bottom - DEPTH_TOLERANCE {
+        verification.warn(format!(
+            "Cuts {:.2}mm below the stock into the spoilboard.",
+            heightmap.bottom - lowest
+        ));
+    }
+
+    // The deepest the tip runs below the material under or just beside the tool, and
+    // where: the walls of a slot cut on an earlier level rub the shank as much as the
+    // material being cut now.
+    let mut deepest: Option<(f64, (f64, f64))> = None;
+    let swept = heightmap.cut_inspecting(toolpath, tool, |stock, tip, radius| {
+        if let Some(z) = stock.highest_within(tip.0, tip.1, radius + stock.resolution) {
+            let depth = z - tip.2;
+            if deepest.is_none_or(|(deepest, _)| depth > deepest) {
+                deepest = Some((depth, (tip.0, tip.1)));
+            }
+        }
+    });
+    if swept.is_err() {
+        return;
+    }
+    if let (Some(flute_length), Some((depth, at))) = (tool.flute_length, deepest) {
+        if depth > flute_length + DEPTH_TOLERANCE {
+            verification.fail(format!(
+                "{} cuts {:.2}mm into the material with {:.2}mm flutes at X{:.2} Y{:.2}; \
+                 the shank would rub.",
+                tool.name, depth, flute_length, at.0, at.1
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::StockSpec;
+    use crate::types::{DrillCycle, FeedSettings, ToolType, Units};
+
+    fn endmill(flute_length: Option<f64>) -> Tool {
+        Tool {
+            name: "3mm Endmill".to_string(),
+            diameter: 3.0,
+            stepover: 0.4,
+            pass_depth: 3.0,
+            tool_type: ToolType::Endmill { diameter: 3.0 },
+            feeds: FeedSettings::default(),
+            flute_length,
+            units: Units::Millimeters,
+        }
+    }
+
+    fn check(toolpath: &Toolpath, tool: &Tool) -> Verification {
+        let mut heightmap = Heightmap::new(&StockSpec::new(20.0, 20.0, 6.0), 0.5).unwrap();
+        let mut verification = Verification::default();
+        verify_pass(&mut heightmap, toolpath, tool, &mut verification);
+        verification
+    }
+
+    #[test]
+    fn test_flutes_must_reach_the_material_being_cut() {
+        let slot = |z: f64| vec![(5.0, 10.0, z), (15.0, 10.0, z)];
+        let deep = Toolpath::new(vec![slot(-4.0)]);
+        assert!(check(&deep, &endmill(None)).warnings.is_empty());
+        assert!(check(&deep, &endmill(Some(4.0))).warnings.is_empty());
+
+        let result = check(&deep, &endmill(Some(3.0)));
+        assert!(result.fatal);
+        assert!(result.warnings[0].contains("4.00mm into the material"));
+
+        // Stepping down in levels does not help: the shank still runs between the walls
+        // of the slot cut above it.
+        let levels = Toolpath::new(vec![slot(-2.0), slot(-4.0)]);
+        let result = check(&levels, &endmill(Some(3.0)));
+        assert!(result.fatal);
+        assert!(result.warnings[0].contains("4.00mm into the material"));
+        assert_eq!(check(&levels, &endmill(Some(4.0))), Verification::default());
+    }
+
+    #[test]
+    fn test_rapids_into_stock_and_spoilboard_cuts() {
+        let mut holes = Toolpath::new(vec![
+            vec![(5.0, 5.0, 1.0), (5.0, 5.0, -3.0)],
+            vec![(15.0, 5.0, -1.0), (15.0, 5.0, -3.0)],
+        ]);
+        holes.drill = Some(DrillCycle::default());
+        let result = check(&holes, &endmill(None));
+        assert!(result.fatal);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("X15.00 Y5.00"));
+
+        let through = Toolpath::new(vec![vec![(5.0, 10.0, -6.5), (15.0, 10.0, -6.5)]]);
+        let result = check(&through, &endmill(Some(10.0)));
+        assert!(!result.fatal);
+        assert_eq!(
+            result.warnings,
+            vec!["Cuts 0.50mm below the stock into the spoilboard.".to_string()]
+        );
+    }
+}
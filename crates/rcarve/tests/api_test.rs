@@ -26,6 +26,9 @@ fn test_multiple_operations_profile_and_pocket() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![profile_shape, pocket_outer];
@@ -40,11 +43,23 @@ fn test_multiple_operations_profile_and_pocket() {
             cut_side: CutSide::Outside,
             tool_index: 0,
             targets: OperationTarget::Curves(vec![curve_id_0]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
+            tabs: None,
         },
         Operation::Pocket {
             target_depth: 5.0,
             tool_index: 0,
             target: OperationTarget::Curves(vec![curve_id_1]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            feeds: FeedSettings::default(),
+            strategy: PocketStrategy::Offset,
+            rest_tool_index: None,
         },
     ];
 
@@ -99,6 +114,9 @@ fn test_multiple_operations_two_profiles() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![shape1, shape2];
@@ -111,12 +129,24 @@ fn test_multiple_operations_two_profiles() {
             cut_side: CutSide::Outside,
             tool_index: 0,
             targets: OperationTarget::Curves(vec![curve_id_0]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
+            tabs: None,
         },
         Operation::Profile {
             target_depth: 5.0,
             cut_side: CutSide::Outside,
             tool_index: 0,
             targets: OperationTarget::Curves(vec![curve_id_1]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
+            tabs: None,
         },
     ];
 
@@ -162,6 +192,9 @@ fn test_multiple_operations_different_tools() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let tool2 = Tool {
@@ -170,6 +203,9 @@ fn test_multiple_operations_different_tools() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 3.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![profile_shape, pocket_outer];
@@ -182,11 +218,23 @@ fn test_multiple_operations_different_tools() {
             cut_side: CutSide::Outside,
             tool_index: 0, // Use first tool (6mm)
             targets: OperationTarget::Curves(vec![curve_id_0]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            leads: None,
+            feeds: FeedSettings::default(),
+            tabs: None,
         },
         Operation::Pocket {
             target_depth: 5.0,
             tool_index: 1, // Use second tool (3mm)
             target: OperationTarget::Curves(vec![curve_id_1]),
+            finish_pass_depth: None,
+            entry: EntryStrategy::Plunge,
+            direction: CutDirection::Climb,
+            feeds: FeedSettings::default(),
+            strategy: PocketStrategy::Offset,
+            rest_tool_index: None,
         },
     ];
 
@@ -208,3 +256,573 @@ fn test_multiple_operations_different_tools() {
         gcode.lines.join("\n")
     );
 }
+
+fn endmill(name: &str, diameter: f64) -> Tool {
+    Tool {
+        name: name.to_string(),
+        diameter,
+        stepover: 0.4,
+        pass_depth: 3.0,
+        tool_type: ToolType::Endmill { diameter },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    }
+}
+
+fn two_tool_project() -> (Project, ToolLibrary) {
+    let mut project = Project::new("Sign", StockSpec::new(200.0, 100.0, 6.0));
+    let outline = project.shapes.create_circle((50.0, 50.0), 30.0);
+    let pocket = project.shapes.create_circle((150.0, 50.0), 20.0);
+    let profile = |tool_index, curve| Operation::Profile {
+        target_depth: 3.0,
+        cut_side: CutSide::Outside,
+        tool_index,
+        targets: OperationTarget::Curves(vec![curve]),
+        finish_pass_depth: None,
+        tabs: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+    };
+    project.add_operation(Operation::Pocket {
+        target_depth: 3.0,
+        tool_index: 1,
+        target: OperationTarget::Curves(vec![pocket]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+        strategy: PocketStrategy::Offset,
+        rest_tool_index: None,
+    });
+    project.add_operation(profile(1, pocket));
+    project.add_operation(profile(0, outline));
+
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("6mm Endmill", 6.0));
+    tools.add_tool(endmill("3mm Endmill", 3.0));
+    (project, tools)
+}
+
+#[test]
+fn test_export_gcode_follows_operation_order() {
+    let (mut project, tools) = two_tool_project();
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports.iter().all(|report| report.error.is_none()));
+
+    let gcode = export_gcode(&project, &tools, &Grbl).expect("export");
+    let position = |needle: &str| {
+        gcode
+            .lines
+            .iter()
+            .position(|line| line == needle)
+            .unwrap_or_else(|| panic!("missing {needle}"))
+    };
+
+    assert_eq!(gcode.lines[0], "G90");
+    let pocket = position("(Operation 1 - Pocket)");
+    let inner_profile = position("(Operation 2 - Profile [outside])");
+    let outer_profile = position("(Operation 3 - Profile [outside])");
+    assert!(pocket < inner_profile && inner_profile < outer_profile);
+
+    // Operations 1 and 2 share the 3mm tool, so there are only two tool changes.
+    let changes: Vec<usize> = gcode
+        .lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("(T"))
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(gcode.lines[changes[0]], "(T2 3mm Endmill)");
+    assert_eq!(gcode.lines[changes[1]], "(T1 6mm Endmill)");
+    assert!(pocket < changes[0] && changes[1] > inner_profile && changes[1] > outer_profile);
+    assert_eq!(gcode.lines.last().unwrap(), "M2");
+}
+
+#[test]
+fn test_inch_project_exports_g20_program() {
+    let (mut project, tools) = two_tool_project();
+    generate_toolpaths_for_operations(&mut project, &tools);
+    let metric = export_gcode(&project, &tools, &Grbl).unwrap();
+    project.units = Units::Inches;
+    let imperial = export_gcode(&project, &tools, &Grbl).unwrap();
+
+    assert_eq!(metric.lines[1], "G21");
+    assert_eq!(imperial.lines[1], "G20");
+    assert_eq!(metric.lines.len(), imperial.lines.len());
+    let word = |line: &str, letter: char| {
+        line.split(' ')
+            .find_map(|word| word.strip_prefix(letter)?.parse::<f64>().ok())
+    };
+    // The same moves, every coordinate scaled down to inches.
+    for (mm, inch) in metric.lines.iter().zip(&imperial.lines).skip(2) {
+        assert_eq!(mm.split(' ').next(), inch.split(' ').next());
+        for letter in ['X', 'Y', 'I', 'J'] {
+            if let Some(value) = word(mm, letter) {
+                let converted = word(inch, letter).unwrap();
+                assert!((value / 25.4 - converted).abs() < 1e-4, "{mm} vs {inch}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pocket_rings_are_linked_without_retracting() {
+    let (mut project, tools) = two_tool_project();
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports[0].retracts_saved > 0);
+
+    // Every offset ring of the single-level pocket is cut in one tool-down move.
+    let artifact = project.operation_states[0].artifact.as_ref().unwrap();
+    assert_eq!(artifact.passes[0].toolpath.paths.len(), 1);
+    assert_eq!(artifact.passes[0].toolpath.path_levels, vec![0]);
+}
+
+#[test]
+fn test_simulated_profile_removes_its_volume() {
+    let mut project = Project::new("Disc", StockSpec::new(40.0, 40.0, 6.0));
+    let circle = project.shapes.create_circle((20.0, 20.0), 10.0);
+    project.add_operation(Operation::Profile {
+        target_depth: 2.0,
+        cut_side: CutSide::Outside,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![circle]),
+        finish_pass_depth: None,
+        tabs: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+    });
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("3mm Endmill", 3.0));
+
+    // Nothing is cut until toolpaths are generated.
+    let heightmap = simulate_project(&project, &tools, 0.1, Board::Stock).expect("simulate");
+    assert_eq!(heightmap.removed_volume(), 0.0);
+
+    // The tool sweeps a 3mm wide ring around the disc.
+    generate_toolpaths_for_operations(&mut project, &tools);
+    let heightmap = simulate_project(&project, &tools, 0.1, Board::Stock).expect("simulate");
+    let annulus = std::f64::consts::PI * (13.0 * 13.0 - 10.0 * 10.0) * 2.0;
+    assert!((heightmap.removed_volume() - annulus).abs() / annulus < 0.02);
+    assert_eq!(heightmap.height_at(20.0, 31.5), Some(-2.0));
+    assert_eq!(heightmap.height_at(20.0, 20.0), Some(0.0));
+    assert_eq!(heightmap.height_at(20.0, 34.0), Some(0.0));
+}
+
+#[test]
+fn test_features_inside_a_profile_are_cut_before_it() {
+    let mut project = Project::new("Frame", StockSpec::new(200.0, 100.0, 6.0));
+    let outline = project.shapes.create_circle((50.0, 50.0), 30.0);
+    let window = project.shapes.create_circle((50.0, 50.0), 10.0);
+    let elsewhere = project.shapes.create_circle((150.0, 50.0), 20.0);
+    let profile = |cut_side, curve| Operation::Profile {
+        target_depth: 3.0,
+        cut_side,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![curve]),
+        finish_pass_depth: None,
+        tabs: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+    };
+    project.add_operation(profile(CutSide::Outside, outline));
+    project.add_operation(profile(CutSide::Inside, window));
+    project.add_operation(profile(CutSide::Outside, elsewhere));
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("3mm Endmill", 3.0));
+    generate_toolpaths_for_operations(&mut project, &tools);
+
+    assert_eq!(operation_cut_order(&project), vec![1, 0, 2]);
+    let gcode = export_gcode(&project, &tools, &Grbl).expect("export");
+    let position = |needle: &str| gcode.lines.iter().position(|line| line.starts_with(needle));
+    assert!(position("(Operation 2 ") < position("(Operation 1 "));
+
+    project.cut_order = CutOrder::Listed;
+    assert_eq!(operation_cut_order(&project), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_point_marker_drills_inside_a_profile_are_cut_before_it() {
+    let mut project = Project::new("Shelf", StockSpec::new(200.0, 100.0, 18.0));
+    let outline = project.shapes.create_circle((50.0, 50.0), 30.0);
+    let inside = project.shapes.create_point((40.0, 50.0));
+    let outside = project.shapes.create_point((150.0, 50.0));
+    project.add_operation(Operation::Profile {
+        target_depth: 18.0,
+        cut_side: CutSide::Outside,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![outline]),
+        finish_pass_depth: None,
+        tabs: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+    });
+    let drill = |targets| Operation::Drill {
+        target_depth: 10.0,
+        tool_index: 0,
+        targets: OperationTarget::Curves(targets),
+        peck_depth: None,
+        dwell: None,
+        feeds: FeedSettings::default(),
+    };
+    project.add_operation(drill(vec![outside]));
+    project.add_operation(drill(vec![inside]));
+
+    assert_eq!(operation_cut_order(&project), vec![1, 2, 0]);
+}
+
+#[test]
+fn test_export_gcode_refuses_stale_or_invalid_toolpaths() {
+    let (mut project, tools) = two_tool_project();
+    let err = export_gcode(&project, &tools, &Grbl).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Operation 1 - Pocket has no up-to-date toolpath"),
+        "{err}"
+    );
+
+    generate_toolpaths_for_operations(&mut project, &tools);
+    project.mark_operation_dirty(2);
+    let err = export_gcode(&project, &tools, &Grbl).unwrap_err();
+    assert!(err.to_string().starts_with("Operation 3"), "{err}");
+
+    generate_toolpaths_for_operations(&mut project, &tools);
+    let mut artifact = project.toolpath_for_operation(1).unwrap().clone();
+    artifact.is_valid = false;
+    artifact.warnings = vec!["gouges the neighbouring part".to_string()];
+    project.attach_toolpath(1, artifact).unwrap();
+    let err = export_gcode(&project, &tools, &Grbl).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Operation 2 - Profile (outside) has an invalid toolpath: gouges the neighbouring part"
+    );
+}
+
+#[test]
+fn test_generated_toolpaths_are_checked_against_the_stock() {
+    let (mut project, mut tools) = two_tool_project();
+    tools.tools[0].flute_length = Some(2.0);
+    project.stock.thickness = 2.5;
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+
+    // Cutting into the spoilboard is worth a warning but still runs.
+    assert!(matches!(
+        reports[0].status,
+        ToolpathStatus::Ready {
+            warning_count: 1,
+            ..
+        }
+    ));
+    assert_eq!(
+        reports[0].warnings,
+        vec!["Cuts 0.50mm below the stock into the spoilboard.".to_string()]
+    );
+
+    // A 3mm deep cut with 2mm flutes rubs the shank, so it is refused.
+    assert!(matches!(
+        project.operation_states[2].status(),
+        ToolpathStatus::Invalid { .. }
+    ));
+    assert!(matches!(reports[2].status, ToolpathStatus::Invalid { .. }));
+    let err = export_gcode(&project, &tools, &Grbl).unwrap_err();
+    assert!(err.to_string().contains("the shank would rub"), "{err}");
+}
+
+#[test]
+fn test_project_estimates_machining_time() {
+    let (mut project, tools) = two_tool_project();
+    let estimate = estimate_project(&project);
+    assert_eq!(estimate.operations, vec![None, None, None]);
+    assert_eq!(estimate.total, MachiningEstimate::default());
+
+    generate_toolpaths_for_operations(&mut project, &tools);
+    project.machine.hourly_rate = Some(90.0);
+    let estimate = estimate_project(&project);
+    let operations: Vec<MachiningEstimate> =
+        estimate.operations.iter().flatten().copied().collect();
+    assert_eq!(operations.len(), 3);
+    assert!(operations
+        .iter()
+        .all(|op| op.seconds > 0.0 && op.plunges > 0));
+
+    // The 3mm tool cuts the pocket and its profile, then the 6mm tool the outline.
+    let tool_changes: Vec<usize> = operations.iter().map(|op| op.tool_changes).collect();
+    assert_eq!(tool_changes, vec![1, 0, 1]);
+    let seconds: f64 = operations.iter().map(|op| op.seconds).sum();
+    assert!((estimate.total.seconds - seconds).abs() < 1e-6);
+    assert!(estimate.total.seconds > 2.0 * project.machine.tool_change_seconds);
+    let cost = estimate.cost.unwrap();
+    assert!((cost - estimate.total.seconds / 40.0).abs() < 1e-6);
+
+    // Faster machines finish sooner.
+    project.machine.acceleration *= 4.0;
+    assert!(estimate_project(&project).total.seconds < estimate.total.seconds);
+
+    // Stale toolpaths are not what would run.
+    project.mark_operation_dirty(2);
+    let estimate = estimate_project(&project);
+    assert!(estimate.operations[..2].iter().all(Option::is_some));
+    assert_eq!(estimate.operations[2], None);
+}
+
+#[test]
+fn test_drill_operation_exports_canned_or_expanded_cycles() {
+    let mut project = Project::new("Shelf side", StockSpec::new(300.0, 100.0, 18.0));
+    let pin = project.shapes.create_circle((20.0, 37.0), 2.5);
+    let dowel = project.shapes.create_point((52.0, 37.0));
+    let outline = project.shapes.create_line((0.0, 0.0), (10.0, 0.0));
+    let drill = |targets: Vec<CurveId>| Operation::Drill {
+        target_depth: 12.0,
+        tool_index: 0,
+        targets: OperationTarget::Curves(targets),
+        peck_depth: Some(5.0),
+        dwell: None,
+        feeds: FeedSettings::default(),
+    };
+    project.add_operation(drill(vec![pin, dowel]));
+    project.add_operation(drill(vec![outline]));
+
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("5mm Brad point", 5.0));
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports[0].error.is_none() && reports[0].warnings.is_empty());
+    let error = reports[1].error.as_deref().unwrap();
+    assert!(error.contains("not a circle or point marker"), "{error}");
+
+    project.remove_operation(1).unwrap();
+    let canned = export_gcode(&project, &tools, &LinuxCnc).unwrap();
+    let cycles: Vec<&String> = canned
+        .lines
+        .iter()
+        .filter(|line| line.starts_with("G98"))
+        .collect();
+    assert_eq!(
+        cycles,
+        vec![
+            "G98 G83 X20.0000 Y37.0000 Z-12.0000 R1.0000 Q5.0000 F100.0",
+            "G98 G83 X52.0000 Y37.0000 Z-12.0000 R1.0000 Q5.0000",
+        ]
+    );
+    assert!(canned.lines.contains(&"G80".to_string()));
+
+    // GRBL has no canned cycles, so each peck is written out.
+    let expanded = export_gcode(&project, &tools, &Grbl).unwrap();
+    let plunges = expanded
+        .lines
+        .iter()
+        .filter(|line| line.starts_with("G1 Z"))
+        .count();
+    assert_eq!(plunges, 6);
+    assert!(!expanded.lines.iter().any(|line| line.starts_with("G8")));
+}
+
+#[test]
+fn test_bore_operation_fits_imported_svg_circles() {
+    let mut project = Project::new("Knob", StockSpec::new(100.0, 100.0, 18.0));
+    let svg = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/circle.svg");
+    let import = project.import_svg(svg).unwrap();
+    let bore = |cut_side: CutSide| Operation::Bore {
+        target_depth: 3.0,
+        tool_index: 0,
+        targets: OperationTarget::Curves(import.curve_ids.clone()),
+        cut_side,
+        pitch: 1.0,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+    };
+    project.add_operation(bore(CutSide::Inside));
+    project.add_operation(bore(CutSide::Outside));
+
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("10mm Endmill", 10.0));
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports.iter().all(|report| report.error.is_none()));
+
+    // The SVG circle arrives as Bézier curves, but is bored as the 16mm circle it draws.
+    for (index, expected) in [(0, 3.0), (1, 13.0)] {
+        let path = &project
+            .toolpath_for_operation(index)
+            .unwrap()
+            .toolpath
+            .paths[0];
+        let (sum_x, sum_y) = path
+            .iter()
+            .fold((0.0, 0.0), |sum, p| (sum.0 + p.0, sum.1 + p.1));
+        let centre = (sum_x / path.len() as f64, sum_y / path.len() as f64);
+        for p in path {
+            let radius = (p.0 - centre.0).hypot(p.1 - centre.1);
+            assert!((radius - expected).abs() < 0.05, "{radius} vs {expected}");
+        }
+    }
+}
+
+#[test]
+fn test_curves_export_as_arcs() {
+    let mut project = Project::new("Disc", StockSpec::new(60.0, 40.0, 6.0));
+    let disc = project.shapes.create_circle((20.0, 20.0), 10.0);
+    let hole = project.shapes.create_circle((48.0, 20.0), 3.0);
+    project.add_operation(Operation::Profile {
+        target_depth: 2.0,
+        cut_side: CutSide::Outside,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![disc]),
+        finish_pass_depth: None,
+        tabs: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+    });
+    project.add_operation(Operation::Bore {
+        target_depth: 3.0,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![hole]),
+        cut_side: CutSide::Inside,
+        pitch: 1.0,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+    });
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(endmill("3mm Endmill", 3.0));
+    generate_toolpaths_for_operations(&mut project, &tools);
+
+    let gcode = export_gcode(&project, &tools, &Grbl).unwrap();
+    let mut position = (0.0, 0.0, 0.0);
+    let mut arcs = Vec::new();
+    let mut helical = false;
+    for line in &gcode.lines {
+        let word = |letter: char| {
+            line.split(' ')
+                .find_map(|word| word.strip_prefix(letter)?.parse::<f64>().ok())
+        };
+        if line.starts_with("G2 ") || line.starts_with("G3 ") {
+            arcs.push((word('X').unwrap(), word('Y').unwrap()));
+            helical |= word('Z') != Some(position.2);
+        }
+        position = (
+            word('X').unwrap_or(position.0),
+            word('Y').unwrap_or(position.1),
+            word('Z').unwrap_or(position.2),
+        );
+    }
+
+    // Each circle is a handful of arcs ending on it rather than hundreds of lines, and the
+    // bore's helix descends along its arcs.
+    assert!(!arcs.is_empty() && arcs.len() < 40, "{} arcs", arcs.len());
+    for (x, y) in arcs {
+        let (centre, expected) = if x < 34.0 { (20.0, 11.5) } else { (48.0, 1.5) };
+        let radius = (x - centre).hypot(y - 20.0);
+        assert!((radius - expected).abs() < 0.25, "{radius} vs {expected}");
+    }
+    assert!(helical);
+    assert!(gcode.lines.iter().filter(|l| l.starts_with("G1 X")).count() < 40);
+}
+
+#[test]
+fn test_inlay_adds_a_pocket_and_a_mirrored_plug() {
+    let mut project = Project::new("Coaster", StockSpec::new(100.0, 100.0, 18.0));
+    let disc = project.shapes.create_circle((30.0, 40.0), 10.0);
+    let pocket = Operation::VCarve {
+        config: VCarveConfig {
+            flat_depth: Some(3.0),
+            ..VCarveConfig::default()
+        },
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![disc]),
+        clearance_tool_index: Some(1),
+        feeds: FeedSettings::default(),
+        inlay_plug: None,
+    };
+    let plug = InlayPlug {
+        start_depth: 1.5,
+        glue_gap: 0.1,
+    };
+    let bottoms_out = InlayPlug {
+        start_depth: 3.0,
+        ..plug
+    };
+    let error = project
+        .add_inlay(pocket.clone(), bottoms_out, 5.0)
+        .unwrap_err();
+    assert!(error.to_string().contains("bottoms out"), "{error}");
+    assert!(project.operations.is_empty());
+    assert_eq!(project.add_inlay(pocket, plug, 4.0).unwrap(), (0, 1));
+
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(Tool {
+        name: "90deg V-bit".to_string(),
+        diameter: 12.0,
+        stepover: 0.0,
+        pass_depth: 0.0,
+        tool_type: ToolType::VBit {
+            angle_degrees: 90.0,
+        },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    });
+    tools.add_tool(endmill("3mm Endmill", 3.0));
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports.iter().all(|report| report.error.is_none()));
+
+    let z_range = |index: usize, kind: ToolpathPassKind| {
+        let artifact = project.toolpath_for_operation(index).unwrap();
+        let pass = artifact
+            .passes
+            .iter()
+            .find(|pass| pass.kind == kind)
+            .unwrap();
+        pass.toolpath
+            .paths
+            .iter()
+            .flatten()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+                (lo.min(p.2), hi.max(p.2))
+            })
+    };
+
+    // The pocket is carved from the stock top down to its flat.
+    let (deepest, _) = z_range(0, ToolpathPassKind::Finish);
+    assert!((deepest + 3.0).abs() < 1e-6);
+    assert!((z_range(0, ToolpathPassKind::Clearance).0 + 3.0).abs() < 1e-6);
+
+    // The plug's walls start at the start depth and the flat around it is cleared.
+    let (deepest, shallowest) = z_range(1, ToolpathPassKind::Finish);
+    assert!((deepest + 4.0).abs() < 1e-6);
+    assert!(shallowest <= -1.5 + 1e-6);
+    assert!((z_range(1, ToolpathPassKind::Clearance).0 + 4.0).abs() < 1e-6);
+
+    let kinds: Vec<OperationKind> = project
+        .operation_summaries()
+        .iter()
+        .map(|summary| summary.kind)
+        .collect();
+    assert_eq!(kinds, vec![OperationKind::VCarve, OperationKind::InlayPlug]);
+
+    // The plug comes from a second board: it is verified against fresh stock and written
+    // as a program of its own.
+    assert!(project
+        .operation_states
+        .iter()
+        .all(|state| matches!(state.status(), ToolpathStatus::Ready { .. })));
+    assert_eq!(project.boards(), vec![Board::Stock, Board::InlayPlug]);
+    let error = export_gcode(&project, &tools, &Grbl).unwrap_err();
+    assert!(error.to_string().contains("separate programs"), "{error}");
+    let has = |board: Board, comment: &str| {
+        let gcode = export_board_gcode(&project, &tools, &Grbl, board).unwrap();
+        gcode.lines.iter().any(|line| line.contains(comment))
+    };
+    assert!(has(Board::Stock, "Operation 1 - V-Carve"));
+    assert!(!has(Board::Stock, "Operation 2"));
+    assert!(has(Board::InlayPlug, "Operation 2 - V-Carve inlay plug"));
+    assert!(!has(Board::InlayPlug, "Operation 1"));
+}
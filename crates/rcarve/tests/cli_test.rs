@@ -0,0 +1,239 @@
+use rcarve::*;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn rcarve(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rcarve"))
+        .args(args)
+        .output()
+        .expect("run rcarve")
+}
+
+fn json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|err| {
+        panic!(
+            "stdout is not JSON ({err}): {}\nstderr: {}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    })
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rcarve-cli-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_library(dir: &Path) -> String {
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(Tool {
+        name: "3mm Endmill".to_string(),
+        diameter: 3.0,
+        stepover: 0.4,
+        pass_depth: 1.5,
+        tool_type: ToolType::Endmill { diameter: 3.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    });
+    let path = dir.join("tools.json");
+    tools.save_to_path(&path).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_cli_batch_workflow() {
+    let dir = scratch_dir("batch");
+    let project = dir.join("badge.rcproj").to_string_lossy().to_string();
+    let output_path = dir.join("badge.nc");
+    let tools = write_library(&dir);
+    let svg = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/circle.svg");
+
+    let created = rcarve(&[
+        "new",
+        &project,
+        "--width",
+        "100",
+        "--height",
+        "50",
+        "--thickness=6",
+    ]);
+    assert!(created.status.success());
+    assert_eq!(json(&created)["summary"]["name"], "badge");
+
+    let imported = rcarve(&["import", &project, svg]);
+    assert!(imported.status.success());
+    assert_eq!(json(&imported)["curves"].as_array().unwrap().len(), 1);
+
+    let added = rcarve(&["add", &project, "profile", "--tool", "1", "--depth", "3"]);
+    assert!(added.status.success());
+    assert_eq!(json(&added)["operation"], 1);
+
+    // Exporting before generating is refused.
+    let stale = rcarve(&["gcode", &project, "--tools", &tools]);
+    assert_eq!(stale.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&stale.stderr).contains("no up-to-date toolpath"));
+
+    let generated = rcarve(&["generate", &project, "--tools", &tools]);
+    assert!(generated.status.success());
+    assert_eq!(json(&generated)["operations"][0]["ok"], true);
+
+    let exported = rcarve(&[
+        "gcode",
+        &project,
+        "--tools",
+        &tools,
+        "--post",
+        "linuxcnc",
+        "--output",
+        &output_path.to_string_lossy(),
+    ]);
+    assert!(exported.status.success());
+    assert_eq!(json(&exported)["postprocessor"], "linuxcnc");
+
+    let gcode = std::fs::read_to_string(&output_path).unwrap();
+    assert!(gcode.contains("(Operation 1 - Profile [outside])"));
+    assert!(gcode.contains("T1 M6"));
+    assert!(gcode.trim_end().ends_with("M2"));
+
+    let estimated = rcarve(&["estimate", &project, "--hourly-rate", "60"]);
+    assert!(estimated.status.success());
+    let estimate = json(&estimated);
+    assert_eq!(estimate["operations"].as_array().unwrap().len(), 1);
+    assert_eq!(estimate["total"]["tool_changes"], 1);
+    let seconds = estimate["total"]["seconds"].as_f64().unwrap();
+    assert!((estimate["cost"].as_f64().unwrap() - seconds / 60.0).abs() < 1e-9);
+    let saved = Project::load_from_path(&project).unwrap();
+    assert_eq!(saved.machine.hourly_rate, Some(60.0));
+
+    let mesh_path = dir.join("badge.obj");
+    let simulated = rcarve(&[
+        "simulate",
+        &project,
+        "--tools",
+        &tools,
+        "--resolution",
+        "1",
+        "--output",
+        &mesh_path.to_string_lossy(),
+    ]);
+    assert!(simulated.status.success());
+    let summary = json(&simulated);
+    assert_eq!(summary["grid"], serde_json::json!([100, 50]));
+    assert!(summary["removed_volume"].as_f64().unwrap() > 0.0);
+    assert_eq!(summary["deepest_cut"], 3.0);
+    let mesh = std::fs::read_to_string(&mesh_path).unwrap();
+    assert_eq!(
+        mesh.lines().filter(|line| line.starts_with("v ")).count(),
+        5000
+    );
+}
+
+#[test]
+fn test_cli_inch_project() {
+    let dir = scratch_dir("inch");
+    let project = dir.join("coaster.rcproj").to_string_lossy().to_string();
+    let output_path = dir.join("coaster.nc").to_string_lossy().to_string();
+    let tools = write_library(&dir);
+    let svg = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/circle.svg");
+
+    let created = rcarve(&[
+        "new",
+        &project,
+        "--units",
+        "in",
+        "--width",
+        "4",
+        "--height",
+        "2",
+        "--thickness",
+        "0.25",
+    ]);
+    assert!(created.status.success());
+    assert!(rcarve(&["import", &project, svg]).status.success());
+    let added = rcarve(&["add", &project, "profile", "--tool", "1", "--depth", "0.1"]);
+    assert!(added.status.success());
+
+    // Lengths given on the command line are stored in millimetres.
+    let saved = Project::load_from_path(&project).unwrap();
+    assert_eq!(saved.units, Units::Inches);
+    assert!((saved.stock.width - 101.6).abs() < 1e-9);
+    assert!((saved.stock.thickness - 6.35).abs() < 1e-9);
+    match &saved.operations[0] {
+        Operation::Profile { target_depth, .. } => assert!((target_depth - 2.54).abs() < 1e-9),
+        other => panic!("unexpected operation {other:?}"),
+    }
+
+    assert!(rcarve(&["generate", &project, "--tools", &tools])
+        .status
+        .success());
+    let exported = rcarve(&[
+        "gcode",
+        &project,
+        "--tools",
+        &tools,
+        "--output",
+        &output_path,
+    ]);
+    assert!(exported.status.success());
+    assert_eq!(json(&exported)["units"], "Inches");
+    let gcode = std::fs::read_to_string(&output_path).unwrap();
+    assert!(gcode.lines().any(|line| line == "G20"));
+
+    let metric = rcarve(&[
+        "gcode",
+        &project,
+        "--tools",
+        &tools,
+        "--units",
+        "mm",
+        "--output",
+        &output_path,
+    ]);
+    assert!(metric.status.success());
+    let gcode = std::fs::read_to_string(&output_path).unwrap();
+    assert!(gcode.lines().any(|line| line == "G21"));
+    assert_eq!(
+        Project::load_from_path(&project).unwrap().units,
+        Units::Inches
+    );
+}
+
+#[test]
+fn test_cli_failures_exit_non_zero() {
+    let dir = scratch_dir("failures");
+    let project = dir.join("empty.rcproj").to_string_lossy().to_string();
+
+    assert_eq!(rcarve(&["frobnicate"]).status.code(), Some(2));
+    assert_eq!(rcarve(&["new", &project]).status.code(), Some(2));
+    assert_eq!(
+        rcarve(&["new", &project, "--width", "1", "--height", "1", "--depth", "1"])
+            .status
+            .code(),
+        Some(2)
+    );
+
+    let created = rcarve(&[
+        "new",
+        &project,
+        "--width",
+        "10",
+        "--height",
+        "10",
+        "--thickness",
+        "3",
+    ]);
+    assert!(created.status.success());
+
+    // No imports to target, and a missing project file.
+    let add = rcarve(&["add", &project, "pocket", "--tool", "1", "--depth", "1"]);
+    assert_eq!(add.status.code(), Some(1));
+    let error: serde_json::Value =
+        serde_json::from_slice(add.stderr.split(|b| *b == b'\n').next().unwrap()).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("no imports"));
+
+    let missing = dir.join("missing.rcproj").to_string_lossy().to_string();
+    assert_eq!(rcarve(&["generate", &missing]).status.code(), Some(1));
+}
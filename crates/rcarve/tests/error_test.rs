@@ -18,6 +18,9 @@ fn test_invalid_tool_index() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![square];
@@ -28,6 +31,12 @@ fn test_invalid_tool_index() {
         cut_side: CutSide::Outside,
         tool_index: 99, // Invalid index
         targets: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+        tabs: None,
     }];
 
     let result = generate_toolpaths(polygons, tools, operations);
@@ -64,6 +73,9 @@ fn test_invalid_polygon_index() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![square]; // Only one polygon (index 0)
@@ -74,6 +86,12 @@ fn test_invalid_polygon_index() {
         cut_side: CutSide::Outside,
         tool_index: 0,
         targets: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+        tabs: None,
     }];
 
     let result = generate_toolpaths(polygons, tools, operations);
@@ -110,6 +128,9 @@ fn test_empty_operations_list() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![square];
@@ -145,6 +166,9 @@ fn test_pocket_invalid_polygon_index() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![outer]; // Only one polygon (index 0)
@@ -154,6 +178,12 @@ fn test_pocket_invalid_polygon_index() {
         target_depth: 5.0,
         tool_index: 0,
         target: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+        strategy: PocketStrategy::Offset,
+        rest_tool_index: None,
     }];
 
     let result = generate_toolpaths(polygons, tools, operations);
@@ -181,6 +211,9 @@ fn test_pocket_invalid_island_index() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![outer]; // Only one polygon (index 0)
@@ -191,6 +224,12 @@ fn test_pocket_invalid_island_index() {
         target_depth: 5.0,
         tool_index: 0,
         target: OperationTarget::Curves(vec![curve_id_0, curve_id_1]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+        strategy: PocketStrategy::Offset,
+        rest_tool_index: None,
     }];
 
     let result = generate_toolpaths(polygons, tools, operations);
@@ -0,0 +1,24 @@
+G90
+G21
+G17
+G40 G49 G80
+G64 P0.01
+G0 Z10.0
+(Profile [outside])
+(T1 6mm Endmill)
+T1 M6
+G43 H1
+(Level 1/2 Z-1.5000)
+G0 X0.0000 Y0.0000
+G1 Z-1.5000 F100.0
+G1 X12.5000 Y0.0000
+G1 X12.5000 Y8.2500
+G0 Z10.0
+(Level 2/2 Z-3.0000)
+G0 X0.0000 Y0.0000
+G1 Z-3.0000 F100.0
+G1 X12.5000 Y0.0000
+G1 X12.5000 Y8.2500
+G0 Z10.0
+M5
+M2
@@ -0,0 +1,24 @@
+N10 G90
+N20 G21
+N30 G17
+N40 G40 G49
+N50 G0 Z10.0
+(Profile [outside])
+(T1 6mm Endmill)
+N60 M5
+N70 T1 M6
+N80 G43 H1
+(Level 1/2 Z-1.5000)
+N90 G0 X0.0000 Y0.0000
+N100 G1 Z-1.5000 F100.0
+N110 G1 X12.5000 Y0.0000
+N120 G1 X12.5000 Y8.2500
+N130 G0 Z10.0
+(Level 2/2 Z-3.0000)
+N140 G0 X0.0000 Y0.0000
+N150 G1 Z-3.0000 F100.0
+N160 G1 X12.5000 Y0.0000
+N170 G1 X12.5000 Y8.2500
+N180 G0 Z10.0
+N190 M5
+N200 M30
@@ -0,0 +1,21 @@
+G90
+G21
+G0 Z10.0
+; Profile (outside)
+; T1 6mm Endmill
+M5
+M0 Insert T1 6mm Endmill
+; Level 1/2 Z-1.500
+G0 X0.000 Y0.000
+G1 Z-1.500 F100.0
+G1 X12.500 Y0.000
+G1 X12.500 Y8.250
+G0 Z10.0
+; Level 2/2 Z-3.000
+G0 X0.000 Y0.000
+G1 Z-3.000 F100.0
+G1 X12.500 Y0.000
+G1 X12.500 Y8.250
+G0 Z10.0
+M5
+M84
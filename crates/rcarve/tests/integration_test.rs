@@ -18,6 +18,9 @@ fn test_square_profile() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     // Operation: Outside profile, 5mm depth (Section 2.1, line 152)
@@ -17,6 +17,9 @@ fn test_simple_pocket() {
         stepover: 0.4, // 40% = 2.4mm stepover
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let toolpath = generate_pocket_toolpath(&outer, &[], &tool, 5.0)
@@ -69,6 +72,7 @@ fn test_simple_pocket() {
 }
 
 #[test]
+#[allow(clippy::manual_range_contains)]
 fn test_pocket_with_island() {
     // Square pocket with a square island in the center
     let outer = vec![
@@ -94,6 +98,9 @@ fn test_pocket_with_island() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let toolpath = generate_pocket_toolpath(&outer, &[island], &tool, 5.0)
@@ -150,6 +157,9 @@ fn test_pocket_via_high_level_api() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![outer];
@@ -159,6 +169,12 @@ fn test_pocket_via_high_level_api() {
         target_depth: 5.0,
         tool_index: 0,
         target: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        feeds: FeedSettings::default(),
+        strategy: PocketStrategy::Offset,
+        rest_tool_index: None,
     }];
 
     let gcode = generate_toolpaths(polygons, tools, operations)
@@ -3,7 +3,7 @@ use rcarve::*;
 #[test]
 fn test_postprocessor_empty_toolpath() {
     // Test post-processor with empty toolpath (no paths)
-    let toolpath = Toolpath { paths: vec![] };
+    let toolpath = Toolpath::new(vec![]);
 
     let gcode = post_process_grbl(&toolpath);
 
@@ -24,12 +24,10 @@ fn test_postprocessor_empty_toolpath() {
 #[test]
 fn test_postprocessor_multiple_paths() {
     // Test post-processor with multiple paths
-    let toolpath = Toolpath {
-        paths: vec![
-            vec![(0.0, 0.0, -5.0), (10.0, 0.0, -5.0), (10.0, 10.0, -5.0)],
-            vec![(20.0, 20.0, -5.0), (30.0, 20.0, -5.0), (30.0, 30.0, -5.0)],
-        ],
-    };
+    let toolpath = Toolpath::new(vec![
+        vec![(0.0, 0.0, -5.0), (10.0, 0.0, -5.0), (10.0, 10.0, -5.0)],
+        vec![(20.0, 20.0, -5.0), (30.0, 20.0, -5.0), (30.0, 30.0, -5.0)],
+    ]);
 
     let gcode = post_process_grbl(&toolpath);
 
@@ -59,9 +57,7 @@ fn test_postprocessor_multiple_paths() {
 #[test]
 fn test_postprocessor_single_point_path() {
     // Test post-processor with a path containing only one point
-    let toolpath = Toolpath {
-        paths: vec![vec![(10.0, 20.0, -5.0)]],
-    };
+    let toolpath = Toolpath::new(vec![vec![(10.0, 20.0, -5.0)]]);
 
     let gcode = post_process_grbl(&toolpath);
 
@@ -101,13 +97,11 @@ fn test_postprocessor_single_point_path() {
 #[test]
 fn test_postprocessor_empty_paths_skipped() {
     // Test post-processor with empty paths (should be skipped)
-    let toolpath = Toolpath {
-        paths: vec![
-            vec![], // Empty path - should be skipped
-            vec![(0.0, 0.0, -5.0), (10.0, 0.0, -5.0)],
-            vec![], // Another empty path - should be skipped
-        ],
-    };
+    let toolpath = Toolpath::new(vec![
+        vec![], // Empty path - should be skipped
+        vec![(0.0, 0.0, -5.0), (10.0, 0.0, -5.0)],
+        vec![], // Another empty path - should be skipped
+    ]);
 
     let gcode = post_process_grbl(&toolpath);
 
@@ -129,3 +123,392 @@ fn test_postprocessor_empty_paths_skipped() {
         "Should have one entry move (empty paths should be skipped)"
     );
 }
+
+/// A short two-level program with a tool change, shared by the golden-file tests.
+fn golden_program(post: &dyn PostProcessor) -> GCode {
+    let tool = Tool {
+        name: "6mm Endmill".to_string(),
+        diameter: 6.0,
+        stepover: 0.4,
+        pass_depth: 1.5,
+        tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    };
+    let levels = DepthLevel::schedule(3.0, 1.5, None);
+    let toolpath = Toolpath {
+        paths: levels
+            .iter()
+            .map(|level| {
+                vec![
+                    (0.0, 0.0, level.z),
+                    (12.5, 0.0, level.z),
+                    (12.5, 8.25, level.z),
+                ]
+            })
+            .collect(),
+        path_levels: (0..levels.len()).collect(),
+        levels,
+        ..Default::default()
+    };
+
+    let mut writer = GCodeWriter::new(post);
+    writer.comment("Profile (outside)");
+    writer.tool_change(1, &tool, &toolpath.feeds);
+    writer.toolpath(&toolpath);
+    writer.finish()
+}
+
+fn assert_golden(post: &dyn PostProcessor, expected: &str) {
+    let actual = golden_program(post).lines.join("\n");
+    assert_eq!(
+        actual,
+        expected.trim_end(),
+        "{} output changed",
+        post.name()
+    );
+}
+
+#[test]
+fn test_golden_grbl() {
+    assert_golden(&Grbl, include_str!("golden/grbl.nc"));
+}
+
+#[test]
+fn test_golden_linuxcnc() {
+    assert_golden(&LinuxCnc, include_str!("golden/linuxcnc.nc"));
+}
+
+#[test]
+fn test_golden_mach3() {
+    assert_golden(&Mach3, include_str!("golden/mach3.nc"));
+}
+
+#[test]
+fn test_golden_marlin() {
+    assert_golden(&Marlin, include_str!("golden/marlin.nc"));
+}
+
+#[test]
+fn test_postprocessor_feeds_and_spindle() {
+    let toolpath = Toolpath {
+        paths: vec![vec![
+            (0.0, 0.0, -1.0),
+            (10.0, 0.0, -2.0),
+            (10.0, 0.0, -3.0),
+            (10.0, 10.0, -3.0),
+            (0.0, 10.0, -3.0),
+        ]],
+        feeds: FeedSettings {
+            feed_rate: Some(1200.0),
+            plunge_rate: Some(300.0),
+            ramp_rate: Some(600.0),
+            spindle_rpm: Some(18000.0),
+        },
+        ..Default::default()
+    };
+
+    let gcode = post_process_grbl(&toolpath);
+    let body: Vec<&str> = gcode.lines[4..].iter().map(String::as_str).collect();
+    assert_eq!(
+        body,
+        vec![
+            "M3 S18000",
+            "G0 X0.0000 Y0.0000",
+            "G1 Z-1.0000 F300.0",
+            "G1 X10.0000 Y0.0000 Z-2.0000 F600.0",
+            "G1 X10.0000 Y0.0000 Z-3.0000 F300.0",
+            "G1 X10.0000 Y10.0000 F1200.0",
+            "G1 X0.0000 Y10.0000",
+            "G0 Z10.0",
+            "M5",
+            "M2",
+        ]
+    );
+
+    // The spindle is only started once while its speed stays the same.
+    let mut writer = GCodeWriter::new(&Grbl);
+    writer.toolpath(&toolpath);
+    writer.toolpath(&toolpath);
+    let gcode = writer.finish();
+    assert_eq!(
+        gcode.lines.iter().filter(|l| l.starts_with("M3")).count(),
+        1
+    );
+}
+
+#[test]
+fn test_postprocessor_segment_feeds() {
+    let mut toolpath = Toolpath {
+        paths: vec![vec![
+            (0.0, 0.0, 0.0),
+            (10.0, 0.0, -1.0),
+            (10.0, 5.0, -1.0),
+            (10.0, 5.0, -2.0),
+            (0.0, 5.0, -3.0),
+        ]],
+        feeds: FeedSettings {
+            feed_rate: Some(1200.0),
+            plunge_rate: Some(300.0),
+            ramp_rate: Some(600.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    toolpath.set_segment_feeds(
+        0,
+        vec![
+            SegmentFeed::Ramp,
+            SegmentFeed::Cut,
+            SegmentFeed::Auto,
+            SegmentFeed::Cut,
+        ],
+    );
+
+    // Recorded rates win over the direction of the move; `Auto` still goes by it.
+    let gcode = post_process_grbl(&toolpath);
+    let body: Vec<&str> = gcode.lines[6..10].iter().map(String::as_str).collect();
+    assert_eq!(
+        body,
+        vec![
+            "G1 X10.0000 Y0.0000 Z-1.0000 F600.0",
+            "G1 X10.0000 Y5.0000 F1200.0",
+            "G1 X10.0000 Y5.0000 Z-2.0000 F300.0",
+            "G1 X0.0000 Y5.0000 Z-3.0000 F1200.0",
+        ]
+    );
+}
+
+#[test]
+fn test_tool_change_uses_the_operation_spindle_speed() {
+    let post =
+        TemplatePost::parse("tool_change = \"M6 T{tool_number} S{spindle_rpm}\"", "atc").unwrap();
+    let mut tool = Tool::endmill("6mm Endmill", 6.0, 0.4, 1.5);
+    tool.feeds.spindle_rpm = Some(12000.0);
+    let feeds = FeedSettings {
+        spindle_rpm: Some(18000.0),
+        ..Default::default()
+    };
+
+    let mut writer = GCodeWriter::new(&post);
+    writer.tool_change(1, &tool, &feeds);
+    writer.tool_change(2, &tool, &FeedSettings::default());
+    let gcode = writer.finish();
+    assert!(gcode.lines.contains(&"M6 T1 S18000".to_string()));
+    assert!(gcode.lines.contains(&"M6 T2 S12000".to_string()));
+}
+
+#[test]
+fn test_postprocessor_arcs() {
+    let end = (10.0, 0.0, -1.0);
+    assert_eq!(
+        Grbl.arc(true, end, (5.0, 0.0)).as_deref(),
+        Some("G2 X10.0000 Y0.0000 Z-1.0000 I5.0000 J0.0000")
+    );
+    assert_eq!(
+        Marlin.arc(false, end, (5.0, 0.0)).as_deref(),
+        Some("G3 X10.000 Y0.000 Z-1.000 I5.000 J0.000")
+    );
+}
+
+#[test]
+fn test_fitted_arcs_export_as_g2_g3() {
+    // A quarter circle flattened into lines, clockwise from (-10, 0) to (0, 10).
+    let quarter: Vec<(f64, f64, f64)> = (0..=8)
+        .map(|i| {
+            let angle = std::f64::consts::PI * (1.0 - i as f64 / 16.0);
+            (10.0 * angle.cos(), 10.0 * angle.sin(), -1.0)
+        })
+        .collect();
+    let mut toolpath = Toolpath {
+        paths: vec![quarter],
+        feeds: FeedSettings {
+            feed_rate: Some(1200.0),
+            plunge_rate: Some(300.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(fit_arcs(&mut toolpath, 0.01, 0.25), 1);
+
+    // The arc line has no feed word, so the new rate goes on a line to the next point.
+    let gcode = post_process_grbl(&toolpath);
+    let body: Vec<&str> = gcode.lines[4..].iter().map(String::as_str).collect();
+    assert_eq!(
+        body,
+        vec![
+            "G0 X-10.0000 Y0.0000",
+            "G1 Z-1.0000 F300.0",
+            "G1 X-9.8079 Y1.9509 F1200.0",
+            "G2 X0.0000 Y10.0000 Z-1.0000 I9.8079 J-1.9509",
+            "G0 Z10.0",
+            "M5",
+            "M2",
+        ]
+    );
+
+    // Dialects without arcs cut the points as lines.
+    let lines = TemplatePost::parse("arcs = false", "lines").unwrap();
+    let mut writer = GCodeWriter::new(&lines);
+    writer.toolpath(&toolpath);
+    let gcode = writer.finish();
+    assert!(!gcode.lines.iter().any(|l| l.starts_with("G2 ")));
+    assert_eq!(
+        gcode.lines.iter().filter(|l| l.starts_with("G1 X")).count(),
+        8
+    );
+}
+
+#[test]
+fn test_postprocessor_feeds_print_at_fixed_precision() {
+    // 7 in/min stored in mm/min is not exact in binary.
+    let toolpath = Toolpath {
+        paths: vec![vec![(0.0, 0.0, -1.0), (10.0, 0.0, -1.0)]],
+        feeds: FeedSettings {
+            feed_rate: Some(7.0 * 25.4),
+            plunge_rate: Some(0.1 + 0.2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let gcode = post_process_grbl(&toolpath);
+    assert!(gcode.lines.contains(&"G1 Z-1.0000 F0.3".to_string()));
+    assert!(gcode
+        .lines
+        .contains(&"G1 X10.0000 Y0.0000 F177.8".to_string()));
+
+    let post = TemplatePost::parse("feed_decimals = 0", "whole").unwrap();
+    let mut writer = GCodeWriter::new(&post);
+    writer.toolpath(&toolpath);
+    let gcode = writer.finish();
+    assert!(gcode
+        .lines
+        .contains(&"G1 X10.0000 Y0.0000 F178".to_string()));
+}
+
+#[test]
+fn test_postprocessor_inch_output() {
+    let toolpath = Toolpath {
+        paths: vec![vec![
+            (0.0, 0.0, -2.54),
+            (25.4, 0.0, -2.54),
+            (25.4, 12.7, -2.54),
+        ]],
+        feeds: FeedSettings {
+            feed_rate: Some(1000.0),
+            plunge_rate: Some(254.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut writer = GCodeWriter::with_units(&Grbl, Units::Inches);
+    writer.toolpath(&toolpath);
+    let gcode = writer.finish();
+    assert_eq!(
+        gcode.lines,
+        vec![
+            "G90",
+            "G20",
+            "G17",
+            "G0 Z0.4",
+            "G0 X0.0000 Y0.0000",
+            "G1 Z-0.1000 F10.0",
+            "G1 X1.0000 Y0.0000 F39.4",
+            "G1 X1.0000 Y0.5000",
+            "G0 Z0.4",
+            "M5",
+            "M2",
+        ]
+    );
+}
+
+#[test]
+fn test_find_postprocessor_by_name() {
+    for post in builtin_postprocessors() {
+        let found = find_postprocessor(&post.name().to_uppercase()).unwrap();
+        assert_eq!(found.name(), post.name());
+    }
+    assert!(find_postprocessor("fanuc").is_none());
+}
+
+#[test]
+fn test_user_postprocessor_from_directory() {
+    let dir = std::env::temp_dir().join(format!("rcarve-posts-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("shop.toml"),
+        "header = \"\"\"\nG90 G21\nM7 (dust shoe down)\n\"\"\"\nsafe_z = 25\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("broken.post"),
+        "decimals = 3\nrapid = \"G0 X{x} Y{w}\"\n",
+    )
+    .unwrap();
+
+    let post = resolve_postprocessor("shop", Some(&dir)).unwrap();
+    let gcode = post_process(post.as_ref(), &Toolpath::new(vec![]));
+    assert_eq!(
+        gcode.lines[..3],
+        ["G90 G21", "M7 (dust shoe down)", "G0 Z25.0000"]
+    );
+
+    let err = resolve_postprocessor("broken", Some(&dir)).err().unwrap();
+    let message = format!("{err:#}");
+    assert!(message.contains("broken.post"), "{message}");
+    assert!(
+        message.contains("line 2: unknown variable `{w}`"),
+        "{message}"
+    );
+
+    assert!(resolve_postprocessor("grbl", Some(&dir)).is_ok());
+    assert!(resolve_postprocessor("missing", Some(&dir)).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_postprocessor_drill_cycles() {
+    let toolpath = |peck_depth, dwell| Toolpath {
+        paths: vec![vec![(5.0, 5.0, 1.0), (5.0, 5.0, -6.0)]],
+        feeds: FeedSettings {
+            plunge_rate: Some(200.0),
+            ..Default::default()
+        },
+        drill: Some(DrillCycle { peck_depth, dwell }),
+        ..Default::default()
+    };
+    let body = |post: &dyn PostProcessor, toolpath: &Toolpath| {
+        let header = post.header(&PostVariables::default()).len() + 1;
+        let gcode = post_process(post, toolpath);
+        gcode.lines[header..gcode.lines.len() - 2].to_vec()
+    };
+
+    assert_eq!(
+        body(&LinuxCnc, &toolpath(None, Some(0.5))),
+        vec![
+            "G98 G82 X5.0000 Y5.0000 Z-6.0000 R1.0000 P0.5 F200.0",
+            "G80"
+        ]
+    );
+    assert_eq!(
+        body(&Grbl, &toolpath(Some(4.0), Some(0.5))),
+        vec![
+            "G0 X5.0000 Y5.0000",
+            "G0 Z1.0000",
+            "G1 Z-3.0000 F200.0",
+            "G0 Z1.0000",
+            "G0 Z-2.5000",
+            "G1 Z-6.0000 F200.0",
+            "G4 P0.5",
+            "G0 Z10.0",
+        ]
+    );
+    // G83 cannot dwell, so LinuxCNC gets the same expanded moves.
+    assert_eq!(
+        body(&LinuxCnc, &toolpath(Some(4.0), Some(0.5))),
+        body(&Grbl, &toolpath(Some(4.0), Some(0.5)))
+    );
+    assert_eq!(Marlin.dwell(0.5), "G4 S0.5");
+}
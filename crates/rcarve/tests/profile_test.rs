@@ -17,6 +17,9 @@ fn test_profile_inside_cut() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let toolpath = generate_profile_toolpath(&square, &tool, &CutSide::Inside, 5.0)
@@ -72,6 +75,9 @@ fn test_profile_via_high_level_api() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![square];
@@ -82,6 +88,12 @@ fn test_profile_via_high_level_api() {
         cut_side: CutSide::Outside,
         tool_index: 0,
         targets: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+        tabs: None,
     }];
 
     let gcode = generate_toolpaths(polygons, tools, operations)
@@ -115,6 +127,9 @@ fn test_profile_on_line_cut() {
         stepover: 0.4,
         pass_depth: 5.0,
         tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let toolpath = generate_profile_toolpath(&square, &tool, &CutSide::OnLine, 5.0)
@@ -145,3 +160,67 @@ fn test_profile_on_line_cut() {
 
     println!("Generated G-code for on-line cut:\n{}", gcode_str);
 }
+
+#[test]
+fn test_profile_tabs_through_project() {
+    let mut project = Project::new("Tabs", StockSpec::new(100.0, 100.0, 6.0));
+    let curve_id = project.shapes.create_circle((50.0, 50.0), 30.0);
+    project.add_operation(Operation::Profile {
+        target_depth: 6.0,
+        cut_side: CutSide::Outside,
+        tool_index: 0,
+        targets: OperationTarget::Curves(vec![curve_id]),
+        finish_pass_depth: None,
+        entry: EntryStrategy::Plunge,
+        direction: CutDirection::Climb,
+        leads: None,
+        feeds: FeedSettings::default(),
+        tabs: Some(TabSettings {
+            placement: TabPlacement::Count(3),
+            width: 5.0,
+            height: 2.0,
+            positions: Vec::new(),
+        }),
+    });
+
+    let mut tools = ToolLibrary::new();
+    tools.add_tool(Tool {
+        name: "6mm Endmill".to_string(),
+        diameter: 6.0,
+        stepover: 0.4,
+        pass_depth: 3.0,
+        tool_type: ToolType::Endmill { diameter: 6.0 },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    });
+
+    let reports = generate_toolpaths_for_operations(&mut project, &tools);
+    assert!(reports[0].error.is_none(), "{:?}", reports[0].error);
+
+    let artifact = project.toolpath_for_operation(0).expect("artifact");
+    assert_eq!(artifact.toolpath.tabs.len(), 3);
+
+    // Only the bottom level (Z-6) dips below the tab top (Z-4).
+    let bottom = artifact.toolpath.paths.last().unwrap();
+    assert!(bottom.iter().any(|p| p.2 == -4.0));
+    let top = &artifact.toolpath.paths[0];
+    assert!(top.iter().all(|p| p.2 == -3.0));
+
+    // Tab settings and positions survive a save/load round trip.
+    let json = serde_json::to_string(&project).unwrap();
+    let restored: Project = serde_json::from_str(&json).unwrap();
+    match &restored.operations[0] {
+        Operation::Profile { tabs, .. } => assert_eq!(tabs.as_ref().unwrap().width, 5.0),
+        _ => panic!("Expected Profile operation"),
+    }
+    assert_eq!(
+        restored
+            .toolpath_for_operation(0)
+            .unwrap()
+            .toolpath
+            .tabs
+            .len(),
+        3
+    );
+}
@@ -1,5 +1,20 @@
 use rcarve::*;
 
+/// Converts V-carve path segments into a plain toolpath for post-processing.
+fn to_toolpath(paths: Vec<PathType>) -> Toolpath {
+    let paths = paths
+        .into_iter()
+        .map(|path| match path {
+            PathType::Crease { path } => path.into_iter().map(|p| (p[0], p[1], p[2])).collect(),
+            PathType::PocketBoundary { path, depth } => path
+                .into_iter()
+                .map(|p| (p[0], p[1], -depth.abs()))
+                .collect(),
+        })
+        .collect();
+    Toolpath::new(paths)
+}
+
 #[test]
 fn test_vcarve_integration() {
     // Test V-carve through the full pipeline: geometry → toolpath → G-code
@@ -25,6 +40,9 @@ fn test_vcarve_integration() {
         tool_type: ToolType::VBit {
             angle_degrees: 60.0,
         },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let toolpath = generate_vcarve_toolpath(
@@ -35,6 +53,7 @@ fn test_vcarve_integration() {
         &tool,
         None,
     )
+    .map(to_toolpath)
     .expect("Failed to generate V-carve toolpath");
 
     // Post-process to G-code
@@ -75,6 +94,7 @@ fn test_vcarve_integration() {
 
 #[test]
 #[ignore = "generate_toolpaths deprecated"]
+#[allow(clippy::unnecessary_unwrap)]
 fn test_vcarve_via_high_level_api() {
     // Test V-carve through the high-level generate_toolpaths API
     let shape = vec![
@@ -98,16 +118,24 @@ fn test_vcarve_via_high_level_api() {
         tool_type: ToolType::VBit {
             angle_degrees: 60.0,
         },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let polygons = vec![shape.clone()];
     let tools = vec![tool];
     let curve_id = CurveId::new();
     let operations = vec![Operation::VCarve {
-        target_depth: Some(5.0), // Max depth of 5mm
+        config: VCarveConfig {
+            flat_depth: Some(5.0), // Max depth of 5mm
+            ..VCarveConfig::default()
+        },
         tool_index: 0,
         targets: OperationTarget::Curves(vec![curve_id]),
         clearance_tool_index: None,
+        feeds: FeedSettings::default(),
+        inlay_plug: None,
     }];
 
     let result = generate_toolpaths(polygons, tools, operations);
@@ -126,6 +154,7 @@ fn test_vcarve_via_high_level_api() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_unwrap)]
 fn test_vcarve_with_flat_depth() {
     // Test V-carve with flat depth (max depth limit)
     let shape = vec![
@@ -147,6 +176,9 @@ fn test_vcarve_with_flat_depth() {
         tool_type: ToolType::VBit {
             angle_degrees: 90.0,
         },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
     };
 
     let max_depth = Some(3.0); // Limit to 3mm depth
@@ -160,7 +192,7 @@ fn test_vcarve_with_flat_depth() {
     );
 
     if result.is_ok() {
-        let toolpath = result.unwrap();
+        let toolpath = to_toolpath(result.unwrap());
         // Verify all depths are limited to max_depth
         for path in &toolpath.paths {
             for point in path {
@@ -169,3 +201,220 @@ fn test_vcarve_with_flat_depth() {
         }
     }
 }
+
+#[test]
+fn test_inlay_plug_polygons_mirror_the_design() {
+    // An L with a square counter cut out of its corner.
+    let design = CarvePolygon {
+        outer: vec![
+            (0.0, 0.0),
+            (30.0, 0.0),
+            (30.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 30.0),
+            (0.0, 30.0),
+        ],
+        holes: vec![vec![(2.0, 2.0), (2.0, 6.0), (6.0, 6.0), (6.0, 2.0)]],
+    };
+    let tool = Tool {
+        name: "60deg V-bit".to_string(),
+        diameter: 0.0,
+        stepover: 0.0,
+        pass_depth: 0.0,
+        tool_type: ToolType::VBit {
+            angle_degrees: 60.0,
+        },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    };
+    let plug = InlayPlug {
+        start_depth: 2.0,
+        glue_gap: 0.1,
+    };
+
+    let background = inlay_plug_polygons(&[design], &tool, &plug, 4.0).unwrap();
+    assert_eq!(background.len(), 2);
+    let near = |ring: &[(f64, f64)], x: f64, y: f64| {
+        ring.iter()
+            .any(|p| (p.0 - x).abs() < 0.01 && (p.1 - y).abs() < 0.01)
+    };
+
+    // The plug is the L flipped left to right and shrunk by the glue gap, cut out of a
+    // border wide enough for the bit to reach the flat.
+    let border = background
+        .iter()
+        .find(|poly| poly.holes.len() == 1)
+        .unwrap();
+    let margin = 2.0 * 2.0 * 30f64.to_radians().tan() + 3.0;
+    assert!(near(&border.outer, -margin, -margin));
+    assert!(near(&border.outer, 30.0 + margin, 30.0 + margin));
+    let plug_outline = &border.holes[0];
+    assert!(near(plug_outline, 29.9, 29.9));
+    assert!(near(plug_outline, 0.1, 9.9));
+    assert!(!near(plug_outline, 0.1, 29.9));
+
+    // The counter becomes an island of background inside the plug, grown by the gap.
+    let counter = background
+        .iter()
+        .find(|poly| poly.holes.is_empty())
+        .unwrap();
+    let xs = counter.outer.iter().map(|p| p.0);
+    let min_x = xs.clone().fold(f64::MAX, f64::min);
+    let max_x = xs.fold(f64::MIN, f64::max);
+    assert!((min_x - 23.9).abs() < 0.01 && (max_x - 28.1).abs() < 0.01);
+
+    let too_shallow = InlayPlug {
+        start_depth: 4.0,
+        glue_gap: 0.0,
+    };
+    let design = CarvePolygon {
+        outer: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+        holes: Vec::new(),
+    };
+    assert!(inlay_plug_polygons(&[design], &tool, &too_shallow, 4.0).is_err());
+}
+
+#[test]
+fn test_vcarve_config_tunes_pruning_and_small_features() {
+    let tool = Tool {
+        name: "60deg V-bit".to_string(),
+        diameter: 0.0,
+        stepover: 0.0,
+        pass_depth: 0.0,
+        tool_type: ToolType::VBit {
+            angle_degrees: 60.0,
+        },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    };
+    // A gable whose two roof edges meet at a shallow angle.
+    let gable = CarvePolygon {
+        outer: vec![
+            (0.0, 0.0),
+            (30.0, 0.0),
+            (30.0, 10.0),
+            (15.0, 14.0),
+            (0.0, 10.0),
+        ],
+        holes: Vec::new(),
+    };
+    let speck = CarvePolygon {
+        outer: vec![(40.0, 0.0), (44.0, 0.0), (44.0, 2.0), (40.0, 2.0)],
+        holes: Vec::new(),
+    };
+    let polygons = [gable, speck];
+    let carve = |config: VCarveConfig| {
+        generate_vcarve_toolpath_with_debug(&polygons, &tool, &config, true).unwrap()
+    };
+
+    // A wider pruning angle drops more of the medial axis.
+    let narrow = carve(VCarveConfig {
+        pruning_angle_threshold_deg: 5.0,
+        ..VCarveConfig::default()
+    });
+    let wide = carve(VCarveConfig {
+        pruning_angle_threshold_deg: 80.0,
+        ..VCarveConfig::default()
+    });
+    let pruned = |result: &VCarveResult| result.debug.as_ref().unwrap().pruned_edges.len();
+    assert!(pruned(&wide) > pruned(&narrow));
+
+    // Outlines smaller than the minimum feature size are not carved at all.
+    let in_speck = |result: &VCarveResult| {
+        result.paths.iter().any(|path| match path {
+            PathType::Crease { path } => path[0][0] > 35.0,
+            PathType::PocketBoundary { path, .. } => path[0][0] > 35.0,
+        })
+    };
+    assert!(in_speck(&carve(VCarveConfig::default())));
+    assert!(!in_speck(&carve(VCarveConfig {
+        min_feature_size: 5.0,
+        ..VCarveConfig::default()
+    })));
+
+    assert!(generate_vcarve_toolpath_with_debug(
+        &polygons,
+        &tool,
+        &VCarveConfig {
+            sampling_resolution: 0.0,
+            ..VCarveConfig::default()
+        },
+        false,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_vcarve_creases_chain_between_branch_points() {
+    let tool = Tool {
+        name: "60deg V-bit".to_string(),
+        diameter: 0.0,
+        stepover: 0.0,
+        pass_depth: 0.0,
+        tool_type: ToolType::VBit {
+            angle_degrees: 60.0,
+        },
+        feeds: FeedSettings::default(),
+        flute_length: None,
+        units: Units::Millimeters,
+    };
+    // A T, whose medial axis branches where the stem meets the bar.
+    let tee = CarvePolygon {
+        outer: vec![
+            (16.0, 0.0),
+            (24.0, 0.0),
+            (24.0, 22.0),
+            (40.0, 22.0),
+            (40.0, 30.0),
+            (0.0, 30.0),
+            (0.0, 22.0),
+            (16.0, 22.0),
+        ],
+        holes: Vec::new(),
+    };
+    let result =
+        generate_vcarve_toolpath_with_debug(&[tee], &tool, &VCarveConfig::default(), true).unwrap();
+    let creases: Vec<Vec<[f64; 3]>> = result
+        .paths
+        .iter()
+        .filter_map(|path| match path {
+            PathType::Crease { path } => Some(path.clone()),
+            PathType::PocketBoundary { .. } => None,
+        })
+        .collect();
+    let segment_count = result.debug.unwrap().crease_paths.len();
+    assert!(!creases.is_empty());
+    assert!(creases.len() * 2 < segment_count);
+
+    // Each stretch of the medial axis is carved once, even though Voronoi edges come in twins.
+    let key = |p: [f64; 3]| ((p[0] * 1e4).round() as i64, (p[1] * 1e4).round() as i64);
+    let mut carved = std::collections::HashSet::new();
+    let mut degree = std::collections::HashMap::new();
+    for path in &creases {
+        for pair in path.windows(2) {
+            let (a, b) = (key(pair[0]), key(pair[1]));
+            assert!(carved.insert((a.min(b), a.max(b))), "segment carved twice");
+            *degree.entry(a).or_insert(0) += 1;
+            *degree.entry(b).or_insert(0) += 1;
+        }
+    }
+
+    // Polylines only break where the axis branches or ends.
+    for path in &creases {
+        for &point in &path[1..path.len() - 1] {
+            assert_eq!(degree[&key(point)], 2);
+        }
+        for end in [path[0], path[path.len() - 1]] {
+            assert_ne!(degree[&key(end)], 2);
+        }
+    }
+
+    // Branches are ordered so most start where the previous one ended.
+    let continued = creases
+        .windows(2)
+        .filter(|pair| key(pair[0][pair[0].len() - 1]) == key(pair[1][0]))
+        .count();
+    assert!(continued * 2 >= creases.len() - 1);
+}
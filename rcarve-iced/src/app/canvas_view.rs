@@ -1,6 +1,5 @@
 use iced::widget::canvas::{self, Program};
 use iced::{Color, Point, Rectangle, Renderer, Theme, event, keyboard, mouse};
-use kurbo::Affine;
 use ulid::Ulid;
 
 use super::CameraState;
@@ -264,13 +263,14 @@ pub struct CanvasImport {
     pub polylines: Vec<Vec<Point>>, // Already flattened curves
     pub selected: bool,
     pub bounds: Rectangle,
-    pub transform: Affine,
 }
 
 #[derive(Debug, Clone)]
 pub struct CanvasToolpath {
     pub meta: CanvasToolpathMeta,
     pub segments: Vec<Vec<Point>>,
+    /// Holding tab centres in world coordinates
+    pub tabs: Vec<Point>,
 }
 
 #[derive(Debug, Clone)]
@@ -324,16 +324,18 @@ impl Bounds {
     }
 }
 
+/// Flattened 2D segments keyed by operation index.
+pub type SegmentCache = std::collections::HashMap<usize, Vec<Vec<(f32, f32)>>>;
+
 pub fn build_scene(
     project: &OpenProject,
     selected: Option<Ulid>,
     visible_toolpaths: &HashSet<usize>,
     highlighted_toolpath: Option<usize>,
-    cached_segments: &std::collections::HashMap<usize, Vec<Vec<(f32, f32)>>>,
-    debug_polygons: Option<&std::collections::HashMap<usize, Vec<Vec<(f32, f32)>>>>,
+    cached_segments: &SegmentCache,
+    debug_polygons: Option<&SegmentCache>,
     vcarve_debug: Option<CanvasVCarveDebug>,
 ) -> Option<CanvasScene> {
-    let mut bounds: Option<Bounds> = None;
     let mut imports = Vec::new();
     let mut toolpaths = Vec::new();
 
@@ -354,9 +356,8 @@ pub fn build_scene(
         stock_rect.y + stock_rect.height,
     );
 
-    let mut b = Bounds::new(stock_min);
-    b.include(stock_max);
-    bounds = Some(b);
+    let mut bounds = Bounds::new(stock_min);
+    bounds.include(stock_max);
 
     for import in &project.data.imported_svgs {
         let mut polylines = Vec::new();
@@ -404,7 +405,6 @@ pub fn build_scene(
                     width: import_bounds.width(),
                     height: import_bounds.height(),
                 },
-                transform: import.transform,
             });
         }
     }
@@ -414,9 +414,9 @@ pub fn build_scene(
         if !visible_toolpaths.contains(&index) {
             continue;
         }
-        if state.artifact.is_none() {
+        let Some(artifact) = &state.artifact else {
             continue;
-        }
+        };
 
         let mut segments = Vec::new();
         let raw_segments = match cached_segments.get(&index) {
@@ -442,6 +442,13 @@ pub fn build_scene(
             continue;
         }
 
+        let tabs = artifact
+            .toolpath
+            .tabs
+            .iter()
+            .map(|&(x, y)| Point::new(x as f32, y as f32))
+            .collect();
+
         let color = toolpath_color(index);
         toolpaths.push(CanvasToolpath {
             meta: CanvasToolpathMeta {
@@ -450,6 +457,7 @@ pub fn build_scene(
                 highlighted: highlighted_toolpath == Some(index),
             },
             segments,
+            tabs,
         });
     }
 
@@ -457,11 +465,6 @@ pub fn build_scene(
         return None;
     }
 
-    let mut bounds = match bounds {
-        Some(b) => b,
-        None => return None,
-    };
-
     let mut debug_paths = Vec::new();
     if let Some(polygons) = debug_polygons {
         for (index, polys) in polygons {
@@ -535,6 +538,24 @@ fn draw_scene(
     
     if overlay_only {
         let (scale, offset) = calculate_transform(size, &scene.bounds, camera);
+
+        // Draw holding tab markers
+        for toolpath in &scene.toolpaths {
+            for tab in &toolpath.tabs {
+                let center = world_to_screen(*tab, &scene.bounds, scale, offset);
+                let marker = canvas::Path::rectangle(
+                    Point::new(center.x - 4.0, center.y - 4.0),
+                    iced::Size::new(8.0, 8.0),
+                );
+                frame.fill(&marker, toolpath.meta.color);
+                frame.stroke(
+                    &marker,
+                    canvas::Stroke::default()
+                        .with_color(Color::WHITE)
+                        .with_width(1.0),
+                );
+            }
+        }
         
         // Draw selection handles and manipulation gizmos
         for import in &scene.imports {
@@ -40,11 +40,19 @@ impl Stock3D {
 
 #[derive(Debug, Clone)]
 pub struct Toolpath3D {
-    pub segments: Vec<Vec<(f32, f32, f32)>>,
+    pub segments: Vec<ToolpathSegment3D>,
     pub color: Color,
     pub highlighted: bool,
 }
 
+/// A single toolpath polyline with a brightness factor for its depth level.
+#[derive(Debug, Clone)]
+pub struct ToolpathSegment3D {
+    pub points: Vec<(f32, f32, f32)>,
+    /// Multiplier applied to the toolpath color (1.0 = full brightness).
+    pub shade: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Curve3D {
     pub segments: Vec<Vec<(f32, f32, f32)>>,
@@ -304,19 +312,22 @@ impl Scene3DPrimitive {
     }
 
     fn generate_toolpath_lines(toolpath: &Toolpath3D, vertices: &mut Vec<Vertex3D>) {
-        let color = if toolpath.highlighted {
-            [toolpath.color.r, toolpath.color.g, toolpath.color.b, 1.0]
-        } else {
-            [toolpath.color.r, toolpath.color.g, toolpath.color.b, 0.8]
-        };
+        let alpha = if toolpath.highlighted { 1.0 } else { 0.8 };
 
         for segment in &toolpath.segments {
-            if segment.len() < 2 {
+            let points = &segment.points;
+            if points.len() < 2 {
                 continue;
             }
-            for i in 0..segment.len() - 1 {
-                let (x1, y1, z1) = segment[i];
-                let (x2, y2, z2) = segment[i + 1];
+            let color = [
+                toolpath.color.r * segment.shade,
+                toolpath.color.g * segment.shade,
+                toolpath.color.b * segment.shade,
+                alpha,
+            ];
+            for i in 0..points.len() - 1 {
+                let (x1, y1, z1) = points[i];
+                let (x2, y2, z2) = points[i + 1];
                 vertices.push(Vertex3D {
                     position: [x1, y1, z1],
                     color,
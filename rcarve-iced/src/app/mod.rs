@@ -3,7 +3,10 @@ use iced::widget::{button, canvas, center, checkbox, column, container, pick_lis
 use iced::{Alignment, Element, Length, Subscription, Task};
 use kurbo::Affine;
 use rcarve::ids::CurveId;
-use rcarve::{CutSide, StockSpec, ToolLibrary, ToolpathArtifact, ToolpathGenerationReport};
+use rcarve::{
+    CutDirection, CutSide, Operation, StockSpec, ToolLibrary, ToolpathArtifact,
+    ToolpathGenerationReport, Units,
+};
 use rfd::AsyncFileDialog;
 use std::fmt;
 use std::{
@@ -28,15 +31,18 @@ use canvas_view::{WorkspaceCanvas, build_scene};
 use canvas_view_3d::Workspace3DView;
 use canvas_view_wgpu::WorkspaceView3D;
 use imports_panel::imports_view;
-use operation_form::{OperationForm, OperationKindForm};
+use operation_form::{
+    EntryMode, InlayMode, LeadMode, OperationForm, OperationKindForm, PocketMode, TabMode,
+    VCarveConfigForm,
+};
 use operations_panel::operations_view;
 use project::{
     OpenProject, ProjectError, create_new_project, import_svg_into_project, infer_project_name,
     load_project_from_path, projects_directory,
 };
 use stock_form::StockForm;
-use tool_form::{ToolForm, ToolKind};
-use util::{format_dimension, format_origin_label, modal_overlay};
+use tool_form::{FeedField, FeedsForm, ToolForm, ToolKind};
+use util::{format_length, format_origin_label, modal_overlay};
 
 pub fn run() -> iced::Result {
     iced::application(App::title, App::update, App::view)
@@ -61,6 +67,7 @@ pub enum Message {
     StockThicknessChanged(String),
     StockMaterialChanged(String),
     StockOriginChanged(String),
+    StockUnitsChanged(Units),
     SaveStock,
     SelectTab(SidebarTab),
     DeleteImport(ulid::Ulid),
@@ -72,14 +79,21 @@ pub enum Message {
     ToolDiameterChanged(String),
     ToolStepoverChanged(String),
     ToolPassDepthChanged(String),
+    ToolFluteLengthChanged(String),
     ToolVBitAngleChanged(String),
     ToolTypeChanged(ToolKind),
+    ToolUnitsChanged(Units),
+    ToolFeedChanged(FeedField, String),
     SaveTool,
     GenerateToolpaths,
+    ExportGcode(rcarve::Board),
+    GcodeExported(Result<PathBuf, ProjectError>),
     ClearToolpath(usize),
     ToggleToolpathVisibility(usize),
     HoverOperation(Option<usize>),
     ToggleDebugPolygons,
+    ToggleInsideOutOrder(bool),
+    #[allow(dead_code)] // Debug hook; not bound to any control yet.
     LogOperationPolygons(usize),
     AddOperation,
     EditOperation(usize),
@@ -88,9 +102,34 @@ pub enum Message {
     OperationKindChanged(OperationKindForm),
     OperationDepthChanged(String),
     OperationCutSideChanged(CutSide),
+    OperationCutDirectionChanged(CutDirection),
     OperationToolChanged(usize),
     OperationClearanceToolChanged(Option<usize>),
     OperationVCarveDepthChanged(String),
+    OperationPeckDepthChanged(String),
+    OperationDwellChanged(String),
+    OperationPitchChanged(String),
+    OperationInlayToggled(bool),
+    OperationPlugStartDepthChanged(String),
+    OperationPlugFlatDepthChanged(String),
+    OperationGlueGapChanged(String),
+    OperationFinishPassChanged(String),
+    OperationTabModeChanged(TabMode),
+    OperationTabAmountChanged(String),
+    OperationTabWidthChanged(String),
+    OperationTabHeightChanged(String),
+    OperationTabPositionsChanged(String),
+    OperationEntryModeChanged(EntryMode),
+    OperationEntryAngleChanged(String),
+    OperationHelixRadiusChanged(String),
+    OperationLeadModeChanged(LeadMode),
+    OperationLeadLengthChanged(String),
+    OperationLeadOverlapChanged(String),
+    OperationFeedChanged(FeedField, String),
+    OperationPocketModeChanged(PocketMode),
+    OperationRasterAngleChanged(String),
+    OperationRasterBoundaryToggled(bool),
+    OperationRestToolChanged(Option<usize>),
     RefreshOperationSelection,
     SaveOperation,
     CanvasZoom(f32),
@@ -116,7 +155,11 @@ pub enum Message {
     Toggle3DStockMode,
     Toggle3DCurves,
     // V-carve debug settings modal
-    OpenVCarveSettings,
+    OpenVCarveSettings(Option<usize>),
+    VCarvePruningAngleChanged(String),
+    VCarveSamplingResolutionChanged(String),
+    VCarveMinFeatureSizeChanged(String),
+    SaveVCarveSettings,
     CloseVCarveSettings,
     ToggleCreasePaths(bool),
     TogglePocketBoundaryPaths(bool),
@@ -141,8 +184,9 @@ pub struct DragState {
     pub anchor_point: Option<iced::Point>, // For scaling - the opposite corner that stays fixed
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SidebarTab {
+    #[default]
     Stock,
     Imports,
     Tools,
@@ -150,11 +194,8 @@ pub enum SidebarTab {
     View3D,
 }
 
-impl Default for SidebarTab {
-    fn default() -> Self {
-        SidebarTab::Stock
-    }
-}
+/// Flattened 3D toolpath segments keyed by operation index.
+type SegmentCache3D = HashMap<usize, Vec<canvas_view_3d::ToolpathSegment3D>>;
 
 #[derive(Default)]
 pub struct App {
@@ -182,7 +223,7 @@ pub struct App {
     visible_toolpaths: HashSet<usize>,
     highlighted_toolpath: Option<usize>,
     toolpath_segments: HashMap<usize, Vec<Vec<(f32, f32)>>>,
-    toolpath_segments_3d: HashMap<usize, Vec<Vec<(f32, f32, f32)>>>,
+    toolpath_segments_3d: SegmentCache3D,
     show_debug_polygons: bool,
     debug_polygons: HashMap<usize, Vec<Vec<(f32, f32)>>>,
     drag_state: Option<DragState>,
@@ -191,6 +232,9 @@ pub struct App {
     // V-carve debug settings
     show_vcarve_settings_modal: bool,
     vcarve_debug_settings: VCarveDebugSettings,
+    /// The V-carve whose engine settings the V-carve settings modal edits, if any.
+    vcarve_settings_operation: Option<usize>,
+    vcarve_config_form: VCarveConfigForm,
     vcarve_debug_edges: HashMap<usize, VCarveDebugEdges>,
 }
 
@@ -338,9 +382,8 @@ impl App {
     const HANDLE_WIDTH: f32 = 64.0;
 
     fn new() -> (Self, Task<Message>) {
-        let mut app = Self::default();
-        app.tool_library = match ToolLibrary::default_library_path()
-            .and_then(|path| ToolLibrary::load_from_path(path))
+        let tool_library = match ToolLibrary::default_library_path()
+            .and_then(ToolLibrary::load_from_path)
         {
             Ok(library) => library,
             Err(error) => {
@@ -348,16 +391,20 @@ impl App {
                 ToolLibrary::new()
             }
         };
+        let mut app = Self {
+            tool_library,
+            ..Self::default()
+        };
 
-        if let Some(default_path) = default_project_path() {
-            if default_path.exists() {
-                match load_project_from_path(default_path) {
-                    Ok(project) => {
-                        let _ = app.handle_project_loaded(Ok(project));
-                    }
-                    Err(error) => {
-                        eprintln!("Failed to load default project: {error:?}");
-                    }
+        if let Some(default_path) = default_project_path()
+            && default_path.exists()
+        {
+            match load_project_from_path(default_path) {
+                Ok(project) => {
+                    let _ = app.handle_project_loaded(Ok(project));
+                }
+                Err(error) => {
+                    eprintln!("Failed to load default project: {error:?}");
                 }
             }
         }
@@ -431,7 +478,7 @@ impl App {
             }
             Message::EditStock => {
                 if let Some(project) = &self.project {
-                    self.stock_form = StockForm::from_stock(project.stock());
+                    self.stock_form = StockForm::from_stock(project.stock(), project.units());
                     self.show_stock_modal = true;
                 }
                 Task::none()
@@ -461,11 +508,16 @@ impl App {
                 self.stock_form.origin = value;
                 Task::none()
             }
+            Message::StockUnitsChanged(units) => {
+                self.stock_form.set_units(units);
+                Task::none()
+            }
             Message::SaveStock => {
                 if let Some(project) = self.project.as_mut() {
                     match self.stock_form.parse() {
                         Ok(stock) => {
                             project.data.stock = stock;
+                            project.data.units = self.stock_form.units;
                             match project.save() {
                                 Ok(()) => {
                                     self.stock_form.error = None;
@@ -507,6 +559,7 @@ impl App {
             }
             Message::AddTool => {
                 self.tool_form = ToolForm::new();
+                self.tool_form.set_units(self.project_units());
                 self.editing_tool_index = None;
                 self.show_tool_modal = true;
                 Task::none()
@@ -553,6 +606,11 @@ impl App {
                 self.tool_form.pass_depth_error = None;
                 Task::none()
             }
+            Message::ToolFluteLengthChanged(value) => {
+                self.tool_form.flute_length = value;
+                self.tool_form.flute_length_error = None;
+                Task::none()
+            }
             Message::ToolVBitAngleChanged(value) => {
                 self.tool_form.vbit_angle = value;
                 self.tool_form.vbit_angle_error = None;
@@ -563,6 +621,15 @@ impl App {
                 self.tool_form.vbit_angle_error = None;
                 Task::none()
             }
+            Message::ToolUnitsChanged(units) => {
+                self.tool_form.set_units(units);
+                Task::none()
+            }
+            Message::ToolFeedChanged(field, value) => {
+                self.tool_form.feeds.set(field, value);
+                self.tool_form.feeds_error = None;
+                Task::none()
+            }
             Message::SaveTool => {
                 match self.tool_form.validate() {
                     Ok(tool) => {
@@ -644,6 +711,39 @@ impl App {
                 }
                 Task::none()
             }
+            Message::ExportGcode(board) => {
+                let Some(project) = self.project.as_ref() else {
+                    return Task::none();
+                };
+                match rcarve::export_board_gcode(
+                    &project.data,
+                    &self.tool_library,
+                    &rcarve::Grbl,
+                    board,
+                ) {
+                    Ok(gcode) => {
+                        let mut name = infer_project_name(&project.path);
+                        if board == rcarve::Board::InlayPlug {
+                            name.push_str("-plug");
+                        }
+                        Task::perform(export_gcode_dialog(name, gcode), Message::GcodeExported)
+                    }
+                    Err(error) => {
+                        eprintln!("Cannot export G-code: {error}");
+                        Task::none()
+                    }
+                }
+            }
+            Message::GcodeExported(result) => {
+                match result {
+                    Ok(path) => eprintln!("Wrote G-code to {}", path.display()),
+                    Err(ProjectError::DialogClosed) => {}
+                    Err(ProjectError::Io(message) | ProjectError::Parse(message)) => {
+                        eprintln!("Failed to write G-code: {message}");
+                    }
+                }
+                Task::none()
+            }
             Message::ClearToolpath(index) => {
                 if let Some(project) = self.project.as_mut() {
                     if let Err(error) = project.data.remove_toolpath_for_operation(index) {
@@ -661,12 +761,11 @@ impl App {
                     self.visible_toolpaths.remove(&index);
                 } else {
                     // Only allow if toolpath exists
-                    if let Some(project) = &self.project {
-                        if let Some(state) = project.data.operation_states.get(index) {
-                            if state.artifact.is_some() {
-                                self.visible_toolpaths.insert(index);
-                            }
-                        }
+                    if let Some(project) = &self.project
+                        && let Some(state) = project.data.operation_states.get(index)
+                        && state.artifact.is_some()
+                    {
+                        self.visible_toolpaths.insert(index);
                     }
                 }
                 Task::none()
@@ -681,6 +780,19 @@ impl App {
                 self.sync_debug_polygons();
                 Task::none()
             }
+            Message::ToggleInsideOutOrder(enabled) => {
+                if let Some(project) = self.project.as_mut() {
+                    project.data.cut_order = if enabled {
+                        rcarve::CutOrder::InsideOut
+                    } else {
+                        rcarve::CutOrder::Listed
+                    };
+                    if let Err(error) = project.save() {
+                        eprintln!("Failed to save project: {error}");
+                    }
+                }
+                Task::none()
+            }
             Message::LogOperationPolygons(index) => {
                 if let Some(project) = &self.project {
                     match rcarve::polygons_for_operation(&project.data, index) {
@@ -712,7 +824,7 @@ impl App {
             }
             Message::AddOperation => {
                 let selection = self.current_curve_selection();
-                self.operation_form = OperationForm::new();
+                self.operation_form = OperationForm::with_units(self.project_units());
                 self.operation_form.update_selection(&selection);
                 self.operation_form.error = None;
                 self.show_operation_modal = true;
@@ -720,12 +832,13 @@ impl App {
                 Task::none()
             }
             Message::EditOperation(index) => {
-                if let Some(project) = &self.project {
-                    if let Some(operation) = project.data.operations.get(index) {
-                        self.operation_form = OperationForm::from_operation(operation);
-                        self.editing_operation_index = Some(index);
-                        self.show_operation_modal = true;
-                    }
+                if let Some(project) = &self.project
+                    && let Some(operation) = project.data.operations.get(index)
+                {
+                    self.operation_form =
+                        OperationForm::from_operation(operation, project.units());
+                    self.editing_operation_index = Some(index);
+                    self.show_operation_modal = true;
                 }
                 Task::none()
             }
@@ -751,8 +864,8 @@ impl App {
                 self.operation_form.kind = kind;
                 self.operation_form.cut_side = match kind {
                     OperationKindForm::Profile => CutSide::Outside,
-                    OperationKindForm::Pocket => CutSide::Inside,
-                    OperationKindForm::VCarve => CutSide::OnLine,
+                    OperationKindForm::Pocket | OperationKindForm::Bore => CutSide::Inside,
+                    OperationKindForm::VCarve | OperationKindForm::Drill => CutSide::OnLine,
                 };
                 self.operation_form.error = None;
                 Task::none()
@@ -767,6 +880,11 @@ impl App {
                 self.operation_form.error = None;
                 Task::none()
             }
+            Message::OperationCutDirectionChanged(direction) => {
+                self.operation_form.cut_direction = direction;
+                self.operation_form.error = None;
+                Task::none()
+            }
             Message::OperationToolChanged(index) => {
                 self.operation_form.tool_index = Some(index);
                 self.operation_form.error = None;
@@ -782,6 +900,130 @@ impl App {
                 self.operation_form.error = None;
                 Task::none()
             }
+            Message::OperationPeckDepthChanged(value) => {
+                self.operation_form.peck_depth = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationDwellChanged(value) => {
+                self.operation_form.dwell = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationPitchChanged(value) => {
+                self.operation_form.pitch = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationInlayToggled(enabled) => {
+                self.operation_form.inlay = if enabled {
+                    InlayMode::Pair
+                } else {
+                    InlayMode::Off
+                };
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationPlugStartDepthChanged(value) => {
+                self.operation_form.plug_start_depth = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationPlugFlatDepthChanged(value) => {
+                self.operation_form.plug_flat_depth = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationGlueGapChanged(value) => {
+                self.operation_form.glue_gap = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationFinishPassChanged(value) => {
+                self.operation_form.finish_pass_depth = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationTabModeChanged(mode) => {
+                self.operation_form.tab_mode = mode;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationTabAmountChanged(value) => {
+                self.operation_form.tab_amount = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationTabWidthChanged(value) => {
+                self.operation_form.tab_width = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationTabHeightChanged(value) => {
+                self.operation_form.tab_height = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationTabPositionsChanged(value) => {
+                self.operation_form.tab_positions = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationEntryModeChanged(mode) => {
+                self.operation_form.entry_mode = mode;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationEntryAngleChanged(value) => {
+                self.operation_form.entry_angle = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationHelixRadiusChanged(value) => {
+                self.operation_form.helix_radius = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationLeadModeChanged(mode) => {
+                self.operation_form.lead_mode = mode;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationLeadLengthChanged(value) => {
+                self.operation_form.lead_length = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationLeadOverlapChanged(value) => {
+                self.operation_form.lead_overlap = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationFeedChanged(field, value) => {
+                self.operation_form.feeds.set(field, value);
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationPocketModeChanged(mode) => {
+                self.operation_form.pocket_mode = mode;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationRasterAngleChanged(value) => {
+                self.operation_form.raster_angle = value;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationRasterBoundaryToggled(enabled) => {
+                self.operation_form.raster_boundary_pass = enabled;
+                self.operation_form.error = None;
+                Task::none()
+            }
+            Message::OperationRestToolChanged(index) => {
+                self.operation_form.rest_tool_index = index;
+                self.operation_form.error = None;
+                Task::none()
+            }
             Message::RefreshOperationSelection => {
                 let selection = self.current_curve_selection();
                 self.operation_form.update_selection(&selection);
@@ -805,6 +1047,12 @@ impl App {
                         if let Some(project) = self.project.as_mut() {
                             let result = if let Some(index) = self.editing_operation_index {
                                 project.data.update_operation(index, operation)
+                            } else if self.operation_form.inlay == InlayMode::Pair {
+                                let (plug, flat_depth) = self
+                                    .operation_form
+                                    .inlay_plug()
+                                    .expect("validated with the pocket");
+                                project.data.add_inlay(operation, plug, flat_depth).map(|_| ())
                             } else {
                                 project.data.add_operation(operation);
                                 Ok(())
@@ -839,7 +1087,7 @@ impl App {
                 } else {
                     1.0 / ZOOM_FACTOR
                 };
-                self.camera.zoom = (self.camera.zoom * zoom_delta).max(0.1).min(100.0);
+                self.camera.zoom = (self.camera.zoom * zoom_delta).clamp(0.1, 100.0);
                 Task::none()
             }
             Message::CanvasPanStart(point) => {
@@ -866,85 +1114,82 @@ impl App {
                 import_center,
                 anchor_point,
             } => {
-                if let Some(project) = &self.project {
-                    if let Some(import_id) = self.selected_import {
-                        if let Some(import) = project
-                            .data
-                            .imported_svgs
-                            .iter()
-                            .find(|i| i.id == import_id)
-                        {
-                            self.drag_state = Some(DragState {
-                                start_cursor_pos: cursor_position,
-                                start_transform: import.transform,
-                                import_center,
-                                mode,
-                                anchor_point,
-                            });
-                        }
-                    }
+                if let Some(project) = &self.project
+                    && let Some(import_id) = self.selected_import
+                    && let Some(import) = project
+                        .data
+                        .imported_svgs
+                        .iter()
+                        .find(|i| i.id == import_id)
+                {
+                    self.drag_state = Some(DragState {
+                        start_cursor_pos: cursor_position,
+                        start_transform: import.transform,
+                        import_center,
+                        mode,
+                        anchor_point,
+                    });
                 }
                 Task::none()
             }
             Message::CanvasDragUpdate(cursor_position) => {
-                if let Some(drag_state) = &self.drag_state {
-                    if let Some(project) = self.project.as_mut() {
-                        if let Some(import_id) = self.selected_import {
-                            let new_transform = match drag_state.mode {
-                                DragMode::Translate => {
-                                    let dx = cursor_position.x - drag_state.start_cursor_pos.x;
-                                    let dy = cursor_position.y - drag_state.start_cursor_pos.y;
-                                    let translation = Affine::translate((dx as f64, dy as f64));
-                                    translation * drag_state.start_transform
-                                }
-                                DragMode::Rotate => {
-                                    let center = drag_state.import_center;
-                                    let start_angle = (drag_state.start_cursor_pos.y - center.y)
-                                        .atan2(drag_state.start_cursor_pos.x - center.x);
-                                    let current_angle = (cursor_position.y - center.y)
-                                        .atan2(cursor_position.x - center.x);
-                                    let delta_angle = current_angle - start_angle;
+                if let Some(drag_state) = &self.drag_state
+                    && let Some(project) = self.project.as_mut()
+                    && let Some(import_id) = self.selected_import
+                {
+                    let new_transform = match drag_state.mode {
+                        DragMode::Translate => {
+                            let dx = cursor_position.x - drag_state.start_cursor_pos.x;
+                            let dy = cursor_position.y - drag_state.start_cursor_pos.y;
+                            let translation = Affine::translate((dx as f64, dy as f64));
+                            translation * drag_state.start_transform
+                        }
+                        DragMode::Rotate => {
+                            let center = drag_state.import_center;
+                            let start_angle = (drag_state.start_cursor_pos.y - center.y)
+                                .atan2(drag_state.start_cursor_pos.x - center.x);
+                            let current_angle = (cursor_position.y - center.y)
+                                .atan2(cursor_position.x - center.x);
+                            let delta_angle = current_angle - start_angle;
                                     
-                                    let rotation = Affine::rotate_about(
-                                        delta_angle as f64,
-                                        kurbo::Point::new(center.x as f64, center.y as f64),
-                                    );
-                                    rotation * drag_state.start_transform
-                                }
-                                DragMode::Scale => {
-                                    if let Some(anchor) = drag_state.anchor_point {
-                                        // Calculate distances from cursor to anchor point
-                                        let dx_current = cursor_position.x - anchor.x;
-                                        let dy_current = cursor_position.y - anchor.y;
-                                        let current_distance = (dx_current * dx_current + dy_current * dy_current).sqrt();
+                            let rotation = Affine::rotate_about(
+                                delta_angle as f64,
+                                kurbo::Point::new(center.x as f64, center.y as f64),
+                            );
+                            rotation * drag_state.start_transform
+                        }
+                        DragMode::Scale => {
+                            if let Some(anchor) = drag_state.anchor_point {
+                                // Calculate distances from cursor to anchor point
+                                let dx_current = cursor_position.x - anchor.x;
+                                let dy_current = cursor_position.y - anchor.y;
+                                let current_distance = (dx_current * dx_current + dy_current * dy_current).sqrt();
                                         
-                                        let dx_start = drag_state.start_cursor_pos.x - anchor.x;
-                                        let dy_start = drag_state.start_cursor_pos.y - anchor.y;
-                                        let start_distance = (dx_start * dx_start + dy_start * dy_start).sqrt();
+                                let dx_start = drag_state.start_cursor_pos.x - anchor.x;
+                                let dy_start = drag_state.start_cursor_pos.y - anchor.y;
+                                let start_distance = (dx_start * dx_start + dy_start * dy_start).sqrt();
                                         
-                                        // Calculate scale factor (uniform scaling)
-                                        let scale_factor = if start_distance > 0.0 {
-                                            (current_distance / start_distance) as f64
-                                        } else {
-                                            1.0
-                                        };
+                                // Calculate scale factor (uniform scaling)
+                                let scale_factor = if start_distance > 0.0 {
+                                    (current_distance / start_distance) as f64
+                                } else {
+                                    1.0
+                                };
                                         
-                                        // Apply scale around the anchor point
-                                        let anchor_kurbo = kurbo::Point::new(anchor.x as f64, anchor.y as f64);
-                                        let scale_transform = Affine::translate(anchor_kurbo.to_vec2())
-                                            * Affine::scale(scale_factor)
-                                            * Affine::translate(-anchor_kurbo.to_vec2());
-                                        scale_transform * drag_state.start_transform
-                                    } else {
-                                        drag_state.start_transform
-                                    }
-                                }
-                            };
-
-                            if let Err(e) = project.data.update_import_transform(import_id, new_transform) {
-                                eprintln!("Failed to update transform: {}", e);
+                                // Apply scale around the anchor point
+                                let anchor_kurbo = kurbo::Point::new(anchor.x as f64, anchor.y as f64);
+                                let scale_transform = Affine::translate(anchor_kurbo.to_vec2())
+                                    * Affine::scale(scale_factor)
+                                    * Affine::translate(-anchor_kurbo.to_vec2());
+                                scale_transform * drag_state.start_transform
+                            } else {
+                                drag_state.start_transform
                             }
                         }
+                    };
+
+                    if let Err(e) = project.data.update_import_transform(import_id, new_transform) {
+                        eprintln!("Failed to update transform: {}", e);
                     }
                 }
                 Task::none()
@@ -1020,12 +1265,69 @@ impl App {
                 Task::none()
             }
             // V-carve debug settings
-            Message::OpenVCarveSettings => {
+            Message::OpenVCarveSettings(index) => {
+                let operation = index.and_then(|index| {
+                    self.project.as_ref()?.data.operations.get(index)
+                });
+                let config = match operation {
+                    Some(Operation::VCarve { config, .. }) => Some(*config),
+                    _ => None,
+                };
+                self.vcarve_settings_operation = config.and(index);
+                self.vcarve_config_form = VCarveConfigForm::from_config(
+                    &config.unwrap_or_default(),
+                    self.project_units(),
+                );
                 self.show_vcarve_settings_modal = true;
                 Task::none()
             }
+            Message::VCarvePruningAngleChanged(value) => {
+                self.vcarve_config_form.pruning_angle = value;
+                self.vcarve_config_form.error = None;
+                Task::none()
+            }
+            Message::VCarveSamplingResolutionChanged(value) => {
+                self.vcarve_config_form.sampling_resolution = value;
+                self.vcarve_config_form.error = None;
+                Task::none()
+            }
+            Message::VCarveMinFeatureSizeChanged(value) => {
+                self.vcarve_config_form.min_feature_size = value;
+                self.vcarve_config_form.error = None;
+                Task::none()
+            }
+            Message::SaveVCarveSettings => {
+                let (Some(index), Some(project)) =
+                    (self.vcarve_settings_operation, self.project.as_mut())
+                else {
+                    return Task::none();
+                };
+                let Some(Operation::VCarve { config, .. }) = project.data.operations.get(index)
+                else {
+                    return Task::none();
+                };
+                let Ok(new_config) = self.vcarve_config_form.apply(config) else {
+                    return Task::none();
+                };
+                let mut operation = project.data.operations[index].clone();
+                if let Operation::VCarve { config, .. } = &mut operation {
+                    *config = new_config;
+                }
+                if let Err(error) = project.data.update_operation(index, operation) {
+                    eprintln!("Failed to store operation: {error}");
+                } else if let Err(error) = project.save() {
+                    eprintln!("Failed to save project: {error}");
+                } else {
+                    self.sync_visible_toolpaths();
+                    self.sync_debug_polygons();
+                }
+                self.show_vcarve_settings_modal = false;
+                self.vcarve_settings_operation = None;
+                Task::none()
+            }
             Message::CloseVCarveSettings => {
                 self.show_vcarve_settings_modal = false;
+                self.vcarve_settings_operation = None;
                 Task::none()
             }
             Message::ToggleCreasePaths(enabled) => {
@@ -1057,7 +1359,7 @@ impl App {
     ) -> Task<Message> {
         match result {
             Ok(project) => {
-                self.stock_form = StockForm::from_stock(project.stock());
+                self.stock_form = StockForm::from_stock(project.stock(), project.units());
                 self.selected_import = project.imports.first().map(|import| import.id);
                 // Reset cameras to default view when loading new project
                 self.camera = CameraState::default();
@@ -1125,7 +1427,7 @@ impl App {
             let operation_entries = project.data.operations_with_status();
 
             let tab_content: Element<'_, Message> = match self.current_tab {
-                SidebarTab::Stock => stock_tab_view(project.stock()),
+                SidebarTab::Stock => stock_tab_view(project.stock(), project.units()),
                 SidebarTab::Imports => {
                     imports_view(&project.imports, self.selected_import, self.importing_svg)
                 }
@@ -1136,6 +1438,8 @@ impl App {
                     &self.visible_toolpaths,
                     self.generating_toolpaths,
                     self.show_debug_polygons,
+                    project.data.cut_order == rcarve::CutOrder::InsideOut,
+                    rcarve::estimate_project(&project.data),
                 ),
                 SidebarTab::View3D => view_3d_tab_view(self.show_3d_stock_wireframe, self.show_3d_curves),
             };
@@ -1456,13 +1760,12 @@ impl App {
     }
 
     fn sync_selected_curves(&mut self) {
-        if let Some(project) = &self.project {
-            if let Some(import_id) = self.selected_import {
-                if let Some(import) = project.imports.iter().find(|import| import.id == import_id) {
-                    self.selected_curves = import.curve_ids.clone();
-                    return;
-                }
-            }
+        if let Some(project) = &self.project
+            && let Some(import_id) = self.selected_import
+            && let Some(import) = project.imports.iter().find(|import| import.id == import_id)
+        {
+            self.selected_curves = import.curve_ids.clone();
+            return;
         }
         self.selected_curves.clear();
     }
@@ -1534,6 +1837,15 @@ impl App {
 
         let mut collect_toolpath = |toolpath: &rcarve::Toolpath| {
             for path in &toolpath.paths {
+                if toolpath.drill.is_some() {
+                    // Holes are vertical, so mark each one with a cross.
+                    if let Some(&(x, y, _)) = path.first() {
+                        let (x, y) = (x as f32, y as f32);
+                        segments.push(vec![(x - 1.0, y - 1.0), (x + 1.0, y + 1.0)]);
+                        segments.push(vec![(x - 1.0, y + 1.0), (x + 1.0, y - 1.0)]);
+                    }
+                    continue;
+                }
                 if path.len() < 2 {
                     continue;
                 }
@@ -1558,21 +1870,30 @@ impl App {
         segments
     }
 
-    fn flatten_toolpath_segments_3d(artifact: &ToolpathArtifact) -> Vec<Vec<(f32, f32, f32)>> {
+    fn flatten_toolpath_segments_3d(
+        artifact: &ToolpathArtifact,
+    ) -> Vec<canvas_view_3d::ToolpathSegment3D> {
         let mut segments = Vec::new();
 
         let mut collect_toolpath = |toolpath: &rcarve::Toolpath| {
-            for path in &toolpath.paths {
+            // Dim the upper roughing levels so the final depth stands out
+            let roughing_levels = toolpath.levels.iter().filter(|l| !l.finishing).count();
+            for (index, path) in toolpath.paths.iter().enumerate() {
                 if path.len() < 2 {
                     continue;
                 }
-                let segment: Vec<(f32, f32, f32)> = path
+                let shade = match toolpath.level_of(index) {
+                    Some(level) if !level.finishing && roughing_levels > 1 => {
+                        let level_index = toolpath.path_levels[index] as f32;
+                        0.4 + 0.6 * level_index / (roughing_levels - 1) as f32
+                    }
+                    _ => 1.0,
+                };
+                let points: Vec<(f32, f32, f32)> = path
                     .iter()
                     .map(|(x, y, z)| (*x as f32, *y as f32, *z as f32))
                     .collect();
-                if segment.len() >= 2 {
-                    segments.push(segment);
-                }
+                segments.push(canvas_view_3d::ToolpathSegment3D { points, shade });
             }
         };
 
@@ -1593,6 +1914,7 @@ impl App {
         } else {
             "Add Tool"
         };
+        let units = self.tool_form.units;
 
         let mut content = column![
             text(title).size(24),
@@ -1603,7 +1925,7 @@ impl App {
                 self.tool_form.name_error.as_deref()
             ),
             text_input_row(
-                "Diameter (mm)",
+                &format!("Diameter ({units})"),
                 &self.tool_form.diameter,
                 Message::ToolDiameterChanged,
                 self.tool_form.diameter_error.as_deref()
@@ -1615,12 +1937,27 @@ impl App {
                 self.tool_form.stepover_error.as_deref()
             ),
             text_input_row(
-                "Pass depth (mm)",
+                &format!("Pass depth ({units})"),
                 &self.tool_form.pass_depth,
                 Message::ToolPassDepthChanged,
                 self.tool_form.pass_depth_error.as_deref()
             ),
+            text_input_row(
+                &format!("Flute length ({units}, optional)"),
+                &self.tool_form.flute_length,
+                Message::ToolFluteLengthChanged,
+                self.tool_form.flute_length_error.as_deref()
+            ),
             tool_type_picker(&self.tool_form),
+            column![
+                text("Units").size(12),
+                pick_list(
+                    [Units::Millimeters, Units::Inches],
+                    Some(units),
+                    Message::ToolUnitsChanged
+                ),
+            ]
+            .spacing(4),
         ]
         .spacing(16);
 
@@ -1633,6 +1970,14 @@ impl App {
             ));
         }
 
+        content = content.push(feeds_section(
+            "Feeds and speeds (optional)",
+            &self.tool_form.feeds,
+            units,
+            Message::ToolFeedChanged,
+            self.tool_form.feeds_error.as_deref(),
+        ));
+
         content = content.push(
             row![
                 button("Cancel").on_press(Message::CloseToolModal),
@@ -1655,6 +2000,7 @@ impl App {
         } else {
             "Add Operation"
         };
+        let units = self.operation_form.units;
 
         let type_selector = row![
             operation_type_button(
@@ -1672,6 +2018,16 @@ impl App {
                 OperationKindForm::VCarve,
                 self.operation_form.kind
             ),
+            operation_type_button(
+                "Drill",
+                OperationKindForm::Drill,
+                self.operation_form.kind
+            ),
+            operation_type_button(
+                "Bore",
+                OperationKindForm::Bore,
+                self.operation_form.kind
+            ),
         ]
         .spacing(8);
 
@@ -1689,18 +2045,106 @@ impl App {
         .align_y(Alignment::Center);
 
         let depth_field: Element<'_, Message> = match self.operation_form.kind {
-            OperationKindForm::VCarve => text_input_row(
-                "Max depth (optional, mm)",
-                &self.operation_form.vcarve_max_depth,
-                Message::OperationVCarveDepthChanged,
-                None,
-            ),
-            _ => text_input_row(
-                "Depth (mm)",
-                &self.operation_form.depth,
-                Message::OperationDepthChanged,
-                None,
-            ),
+            OperationKindForm::VCarve => {
+                let inlay = self.operation_form.inlay;
+                let mut fields = column![].spacing(12);
+                if inlay != InlayMode::Plug {
+                    fields = fields.push(text_input_row(
+                        &if inlay == InlayMode::Pair {
+                            format!("Pocket depth ({units})")
+                        } else {
+                            format!("Max depth (optional, {units})")
+                        },
+                        &self.operation_form.vcarve_max_depth,
+                        Message::OperationVCarveDepthChanged,
+                        None,
+                    ));
+                }
+                if self.editing_operation_index.is_none() {
+                    fields = fields.push(
+                        checkbox("Inlay: also carve the mirrored plug", inlay == InlayMode::Pair)
+                            .on_toggle(Message::OperationInlayToggled),
+                    );
+                }
+                if inlay != InlayMode::Off {
+                    fields = fields.push(column![
+                        text_input_row(
+                            &format!("Plug start depth ({units})"),
+                            &self.operation_form.plug_start_depth,
+                            Message::OperationPlugStartDepthChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            &format!("Plug flat depth ({units})"),
+                            &self.operation_form.plug_flat_depth,
+                            Message::OperationPlugFlatDepthChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            &format!("Glue gap (optional, {units})"),
+                            &self.operation_form.glue_gap,
+                            Message::OperationGlueGapChanged,
+                            None,
+                        ),
+                    ]
+                    .spacing(12));
+                }
+                fields.into()
+            }
+            OperationKindForm::Drill => column![
+                text_input_row(
+                    &format!("Depth ({units})"),
+                    &self.operation_form.depth,
+                    Message::OperationDepthChanged,
+                    None,
+                ),
+                text_input_row(
+                    &format!("Peck depth (optional, {units})"),
+                    &self.operation_form.peck_depth,
+                    Message::OperationPeckDepthChanged,
+                    None,
+                ),
+                text_input_row(
+                    "Dwell at bottom (optional, seconds)",
+                    &self.operation_form.dwell,
+                    Message::OperationDwellChanged,
+                    None,
+                ),
+            ]
+            .spacing(12)
+            .into(),
+            OperationKindForm::Bore => column![
+                text_input_row(
+                    &format!("Depth ({units})"),
+                    &self.operation_form.depth,
+                    Message::OperationDepthChanged,
+                    None,
+                ),
+                text_input_row(
+                    &format!("Helix pitch ({units} per turn)"),
+                    &self.operation_form.pitch,
+                    Message::OperationPitchChanged,
+                    None,
+                ),
+            ]
+            .spacing(12)
+            .into(),
+            _ => column![
+                text_input_row(
+                    &format!("Depth ({units})"),
+                    &self.operation_form.depth,
+                    Message::OperationDepthChanged,
+                    None,
+                ),
+                text_input_row(
+                    &format!("Finishing pass (optional, {units})"),
+                    &self.operation_form.finish_pass_depth,
+                    Message::OperationFinishPassChanged,
+                    None,
+                ),
+            ]
+            .spacing(12)
+            .into(),
         };
 
         let tool_options: Vec<ToolOption> = self
@@ -1749,7 +2193,10 @@ impl App {
         ]
         .spacing(16);
 
-        if self.operation_form.kind == OperationKindForm::Profile {
+        if matches!(
+            self.operation_form.kind,
+            OperationKindForm::Profile | OperationKindForm::Bore
+        ) {
             content = content.push(
                 column![
                     text("Cut side").size(12),
@@ -1764,6 +2211,213 @@ impl App {
             );
         }
 
+        if self.operation_form.kind == OperationKindForm::Profile {
+            let tab_mode = self.operation_form.tab_mode;
+            let mut tabs_section = column![
+                text("Holding tabs").size(12),
+                row![
+                    tab_mode_button("None", TabMode::None, tab_mode),
+                    tab_mode_button("Count", TabMode::Count, tab_mode),
+                    tab_mode_button("Spacing", TabMode::Spacing, tab_mode),
+                ]
+                .spacing(8),
+            ]
+            .spacing(4);
+
+            if tab_mode != TabMode::None {
+                let amount_label = if tab_mode == TabMode::Count {
+                    "Tab count".to_string()
+                } else {
+                    format!("Tab spacing ({units})")
+                };
+                tabs_section = tabs_section.push(
+                    column![
+                        text_input_row(
+                            &amount_label,
+                            &self.operation_form.tab_amount,
+                            Message::OperationTabAmountChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            &format!("Tab width ({units})"),
+                            &self.operation_form.tab_width,
+                            Message::OperationTabWidthChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            &format!("Tab height ({units})"),
+                            &self.operation_form.tab_height,
+                            Message::OperationTabHeightChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            "Manual positions (optional, 0-1, comma separated)",
+                            &self.operation_form.tab_positions,
+                            Message::OperationTabPositionsChanged,
+                            None,
+                        ),
+                    ]
+                    .spacing(12),
+                );
+            }
+
+            content = content.push(tabs_section);
+
+            let lead_mode = self.operation_form.lead_mode;
+            let mut leads_section = column![
+                text("Leads").size(12),
+                row![
+                    lead_mode_button("None", LeadMode::None, lead_mode),
+                    lead_mode_button("Arc", LeadMode::Arc, lead_mode),
+                    lead_mode_button("Line", LeadMode::Line, lead_mode),
+                ]
+                .spacing(8),
+            ]
+            .spacing(4);
+
+            if lead_mode != LeadMode::None {
+                let length_label = if lead_mode == LeadMode::Arc {
+                    format!("Lead radius ({units})")
+                } else {
+                    format!("Lead length ({units})")
+                };
+                leads_section = leads_section.push(
+                    column![
+                        text_input_row(
+                            &length_label,
+                            &self.operation_form.lead_length,
+                            Message::OperationLeadLengthChanged,
+                            None,
+                        ),
+                        text_input_row(
+                            &format!("Overlap ({units})"),
+                            &self.operation_form.lead_overlap,
+                            Message::OperationLeadOverlapChanged,
+                            None,
+                        ),
+                    ]
+                    .spacing(12),
+                );
+            }
+
+            content = content.push(leads_section);
+        }
+
+        if self.operation_form.kind == OperationKindForm::Pocket {
+            let pocket_mode = self.operation_form.pocket_mode;
+            let mut strategy_section = column![
+                text("Strategy").size(12),
+                row![
+                    pocket_mode_button("Offset", PocketMode::Offset, pocket_mode),
+                    pocket_mode_button("Raster", PocketMode::Raster, pocket_mode),
+                ]
+                .spacing(8),
+            ]
+            .spacing(4);
+
+            if pocket_mode == PocketMode::Raster {
+                strategy_section = strategy_section.push(
+                    column![
+                        text_input_row(
+                            "Raster angle (degrees)",
+                            &self.operation_form.raster_angle,
+                            Message::OperationRasterAngleChanged,
+                            None,
+                        ),
+                        checkbox(
+                            "Finish with a boundary pass",
+                            self.operation_form.raster_boundary_pass
+                        )
+                        .on_toggle(Message::OperationRasterBoundaryToggled),
+                    ]
+                    .spacing(12),
+                );
+            }
+
+            if !tool_options.is_empty() {
+                let mut rest_options = Vec::with_capacity(tool_options.len() + 1);
+                rest_options.push(ClearanceChoice::None);
+                rest_options.extend(tool_options.iter().cloned().map(ClearanceChoice::Tool));
+
+                let selected_rest = self
+                    .operation_form
+                    .rest_tool_index
+                    .and_then(|index| {
+                        tool_options
+                            .iter()
+                            .find(|opt| opt.index == index)
+                            .map(|opt| ClearanceChoice::Tool(opt.clone()))
+                    })
+                    .unwrap_or(ClearanceChoice::None);
+
+                let picker = pick_list(rest_options, Some(selected_rest), |choice| match choice {
+                    ClearanceChoice::None => Message::OperationRestToolChanged(None),
+                    ClearanceChoice::Tool(option) => {
+                        Message::OperationRestToolChanged(Some(option.index))
+                    }
+                });
+                strategy_section = strategy_section.push(
+                    column![
+                        text("Rest machining: only clear what this larger tool left (optional)")
+                            .size(12),
+                        picker,
+                    ]
+                    .spacing(4),
+                );
+            }
+
+            content = content.push(strategy_section);
+        }
+
+        if !matches!(
+            self.operation_form.kind,
+            OperationKindForm::VCarve | OperationKindForm::Drill
+        ) {
+            let direction = self.operation_form.cut_direction;
+            content = content.push(
+                column![
+                    text("Cut direction").size(12),
+                    row![
+                        cut_direction_button("Climb", CutDirection::Climb, direction),
+                        cut_direction_button("Conventional", CutDirection::Conventional, direction),
+                    ]
+                    .spacing(8),
+                ]
+                .spacing(4),
+            );
+
+            if self.operation_form.kind != OperationKindForm::Bore {
+                let entry_mode = self.operation_form.entry_mode;
+                let mut modes = row![
+                    entry_mode_button("Plunge", EntryMode::Plunge, entry_mode),
+                    entry_mode_button("Ramp", EntryMode::Ramp, entry_mode),
+                ]
+                .spacing(8);
+                if self.operation_form.kind == OperationKindForm::Pocket {
+                    modes = modes.push(entry_mode_button("Helix", EntryMode::Helix, entry_mode));
+                }
+
+                let mut entry_section = column![text("Entry").size(12), modes].spacing(4);
+                if entry_mode != EntryMode::Plunge {
+                    entry_section = entry_section.push(text_input_row(
+                        "Max entry angle (degrees)",
+                        &self.operation_form.entry_angle,
+                        Message::OperationEntryAngleChanged,
+                        None,
+                    ));
+                }
+                if entry_mode == EntryMode::Helix {
+                    entry_section = entry_section.push(text_input_row(
+                        &format!("Helix radius ({units})"),
+                        &self.operation_form.helix_radius,
+                        Message::OperationHelixRadiusChanged,
+                        None,
+                    ));
+                }
+                content = content.push(entry_section);
+            }
+        }
+
         if self.operation_form.kind == OperationKindForm::VCarve && !tool_options.is_empty() {
             let mut clearance_options = Vec::with_capacity(tool_options.len() + 1);
             clearance_options.push(ClearanceChoice::None);
@@ -1795,11 +2449,18 @@ impl App {
                 .push(column![text("Clearance tool (optional)").size(12), picker].spacing(4));
         }
 
+        content = content.push(feeds_section(
+            "Feed and speed overrides (blank uses the tool's)",
+            &self.operation_form.feeds,
+            units,
+            Message::OperationFeedChanged,
+            None,
+        ));
+
         if let Some(error) = &self.operation_form.error {
             let color = iced::Color::from_rgb8(0xE5, 0x54, 0x54);
             content = content.push(text(error).style(move |_theme| iced::widget::text::Style {
                 color: Some(color),
-                ..Default::default()
             }));
         }
 
@@ -1822,7 +2483,43 @@ impl App {
     fn vcarve_settings_modal(&self) -> Element<'_, Message> {
         let settings = &self.vcarve_debug_settings;
 
-        let content = column![
+        let mut content = column![].spacing(12);
+        if let Some(index) = self.vcarve_settings_operation {
+            let form = &self.vcarve_config_form;
+            content = content.push(
+                column![
+                    text(format!("Operation {} Carving", index + 1)).size(24),
+                    text_input_row(
+                        "Pruning angle (degrees)",
+                        &form.pruning_angle,
+                        Message::VCarvePruningAngleChanged,
+                        None,
+                    ),
+                    text_input_row(
+                        &format!("Sampling resolution ({})", form.units),
+                        &form.sampling_resolution,
+                        Message::VCarveSamplingResolutionChanged,
+                        None,
+                    ),
+                    text_input_row(
+                        &format!("Minimum feature size (optional, {})", form.units),
+                        &form.min_feature_size,
+                        Message::VCarveMinFeatureSizeChanged,
+                        None,
+                    ),
+                ]
+                .spacing(12),
+            );
+            if let Some(error) = &form.error {
+                let color = iced::Color::from_rgb8(0xE5, 0x54, 0x54);
+                content = content.push(text(error).style(move |_theme| iced::widget::text::Style {
+                    color: Some(color),
+                }));
+            }
+            content = content.push(button("Apply").on_press(Message::SaveVCarveSettings));
+        }
+
+        let content = content.push(column![
             text("V-Carve Debug Settings").size(24),
             text("Path Visualization").size(16),
             checkbox("Show Crease Paths (blue)", settings.show_crease_paths)
@@ -1849,7 +2546,7 @@ impl App {
                 .spacing(10)
                 .align_y(Alignment::Center),
         ]
-        .spacing(12);
+        .spacing(12));
 
         container(content)
             .padding(24)
@@ -1858,6 +2555,14 @@ impl App {
             .into()
     }
 
+    /// Units lengths are shown and typed in; millimetres with no project open.
+    fn project_units(&self) -> Units {
+        self.project
+            .as_ref()
+            .map(OpenProject::units)
+            .unwrap_or_default()
+    }
+
     fn save_tool_library(&self) {
         match ToolLibrary::default_library_path() {
             Ok(path) => {
@@ -1870,16 +2575,16 @@ impl App {
     }
 }
 
-fn stock_tab_view(stock: &StockSpec) -> Element<'static, Message> {
+fn stock_tab_view(stock: &StockSpec, units: Units) -> Element<'static, Message> {
     let card = container(
         column![
             text("Stock").size(20),
             row![
-                text(format!("Width: {} mm", format_dimension(stock.width))),
-                text(format!("Height: {} mm", format_dimension(stock.height))),
+                text(format!("Width: {} {units}", format_length(stock.width, units))),
+                text(format!("Height: {} {units}", format_length(stock.height, units))),
                 text(format!(
-                    "Thickness: {} mm",
-                    format_dimension(stock.thickness)
+                    "Thickness: {} {units}",
+                    format_length(stock.thickness, units)
                 )),
             ]
             .spacing(12)
@@ -1892,7 +2597,7 @@ fn stock_tab_view(stock: &StockSpec) -> Element<'static, Message> {
                     .filter(|s| !s.is_empty())
                     .unwrap_or("—")
             )),
-            text(format!("Origin: {}", format_origin_label(stock.origin))),
+            text(format!("Origin: {}", format_origin_label(stock.origin, units))),
             button("Edit Stock").on_press(Message::EditStock),
         ]
         .spacing(12),
@@ -1969,7 +2674,6 @@ fn tab_pill<'a>(
             } else {
                 iced::Color::from_rgb8(0x99, 0x99, 0x99)
             }),
-            ..Default::default()
         });
 
     let btn = button(label_text)
@@ -2001,14 +2705,60 @@ fn tab_pill<'a>(
     btn.into()
 }
 
+/// Feed, plunge, ramp and spindle inputs laid out two to a row.
+fn feeds_section<'a>(
+    title: &'static str,
+    feeds: &'a FeedsForm,
+    units: Units,
+    on_change: fn(FeedField, String) -> Message,
+    error: Option<&'a str>,
+) -> Element<'a, Message> {
+    let input = |label: String, value: &'a str, field: FeedField| {
+        column![
+            text(label).size(12),
+            iced::widget::text_input("default", value)
+                .padding(8)
+                .on_input(move |value| on_change(field, value)),
+        ]
+        .spacing(4)
+        .width(Length::Fill)
+    };
+
+    let mut section = column![
+        text(title).size(12),
+        row![
+            input(format!("Feed ({units}/min)"), &feeds.feed_rate, FeedField::Feed),
+            input(format!("Plunge ({units}/min)"), &feeds.plunge_rate, FeedField::Plunge),
+        ]
+        .spacing(8),
+        row![
+            input(format!("Ramp ({units}/min)"), &feeds.ramp_rate, FeedField::Ramp),
+            input("Spindle (RPM)".to_string(), &feeds.spindle_rpm, FeedField::Spindle),
+        ]
+        .spacing(8),
+    ]
+    .spacing(8);
+
+    if let Some(message) = error {
+        let color = iced::Color::from_rgb8(0xE5, 0x54, 0x54);
+        section = section.push(
+            text(message).style(move |_theme| iced::widget::text::Style {
+                color: Some(color),
+            }),
+        );
+    }
+
+    section.into()
+}
+
 fn text_input_row<'a>(
-    label: &'static str,
+    label: &str,
     value: &'a str,
     on_input: fn(String) -> Message,
     error: Option<&'a str>,
 ) -> Element<'a, Message> {
     let mut column = column![
-        text(label).size(12),
+        text(label.to_owned()).size(12),
         iced::widget::text_input(label, value)
             .padding(8)
             .on_input(on_input),
@@ -2020,7 +2770,6 @@ fn text_input_row<'a>(
         column = column.push(
             text(message).style(move |_theme| iced::widget::text::Style {
                 color: Some(color),
-                ..Default::default()
             }),
         );
     }
@@ -2051,7 +2800,6 @@ fn type_toggle<'a>(label: &'static str, kind: ToolKind, current: ToolKind) -> El
 
     let label_text = text(label).style(move |_theme| iced::widget::text::Style {
         color: Some(color),
-        ..Default::default()
     });
 
     button(label_text)
@@ -2074,7 +2822,6 @@ fn operation_type_button<'a>(
 
     let label_text = text(label).style(move |_theme| iced::widget::text::Style {
         color: Some(color),
-        ..Default::default()
     });
 
     button(label_text)
@@ -2083,6 +2830,116 @@ fn operation_type_button<'a>(
         .into()
 }
 
+fn entry_mode_button<'a>(
+    label: &'static str,
+    mode: EntryMode,
+    current: EntryMode,
+) -> Element<'a, Message> {
+    let active = mode == current;
+    let color = if active {
+        iced::Color::from_rgb8(0x2a, 0x64, 0xc5)
+    } else {
+        iced::Color::from_rgb8(0x55, 0x55, 0x55)
+    };
+
+    let label_text = text(label).style(move |_theme| iced::widget::text::Style {
+        color: Some(color),
+    });
+
+    button(label_text)
+        .padding([4, 8])
+        .on_press(Message::OperationEntryModeChanged(mode))
+        .into()
+}
+
+fn tab_mode_button<'a>(
+    label: &'static str,
+    mode: TabMode,
+    current: TabMode,
+) -> Element<'a, Message> {
+    let active = mode == current;
+    let color = if active {
+        iced::Color::from_rgb8(0x2a, 0x64, 0xc5)
+    } else {
+        iced::Color::from_rgb8(0x55, 0x55, 0x55)
+    };
+
+    let label_text = text(label).style(move |_theme| iced::widget::text::Style {
+        color: Some(color),
+    });
+
+    button(label_text)
+        .padding([4, 8])
+        .on_press(Message::OperationTabModeChanged(mode))
+        .into()
+}
+
+fn pocket_mode_button<'a>(
+    label: &'static str,
+    mode: PocketMode,
+    current: PocketMode,
+) -> Element<'a, Message> {
+    let active = mode == current;
+    let color = if active {
+        iced::Color::from_rgb8(0x2a, 0x64, 0xc5)
+    } else {
+        iced::Color::from_rgb8(0x55, 0x55, 0x55)
+    };
+
+    let label_text = text(label).style(move |_theme| iced::widget::text::Style {
+        color: Some(color),
+    });
+
+    button(label_text)
+        .padding([4, 8])
+        .on_press(Message::OperationPocketModeChanged(mode))
+        .into()
+}
+
+fn lead_mode_button<'a>(
+    label: &'static str,
+    mode: LeadMode,
+    current: LeadMode,
+) -> Element<'a, Message> {
+    let active = mode == current;
+    let color = if active {
+        iced::Color::from_rgb8(0x2a, 0x64, 0xc5)
+    } else {
+        iced::Color::from_rgb8(0x55, 0x55, 0x55)
+    };
+
+    let label_text = text(label).style(move |_theme| iced::widget::text::Style {
+        color: Some(color),
+    });
+
+    button(label_text)
+        .padding([4, 8])
+        .on_press(Message::OperationLeadModeChanged(mode))
+        .into()
+}
+
+fn cut_direction_button<'a>(
+    label: &'static str,
+    direction: CutDirection,
+    current: CutDirection,
+) -> Element<'a, Message> {
+    let active = direction == current;
+    let color = if active {
+        iced::Color::from_rgb8(0x2a, 0x64, 0xc5)
+    } else {
+        iced::Color::from_rgb8(0x55, 0x55, 0x55)
+    };
+
+    let label_text = text(label).style(move |_theme| iced::widget::text::Style {
+        color: Some(color),
+    });
+
+    button(label_text)
+        .padding([4, 8])
+        .on_press(Message::OperationCutDirectionChanged(direction))
+        .into()
+}
+
 fn cut_side_button<'a>(
     label: &'static str,
     side: CutSide,
@@ -2097,7 +2954,6 @@ fn cut_side_button<'a>(
 
     let label_text = text(label).style(move |_theme| iced::widget::text::Style {
         color: Some(color),
-        ..Default::default()
     });
 
     button(label_text)
@@ -2142,60 +2998,70 @@ impl fmt::Display for ClearanceChoice {
     }
 }
 
-fn open_project_dialog() -> impl std::future::Future<Output = Result<OpenProject, ProjectError>> {
-    async {
-        let mut dialog = AsyncFileDialog::new()
-            .set_title("Open a .rcproj file")
-            .add_filter("Rcarve Project", &["rcproj"]);
+async fn open_project_dialog() -> Result<OpenProject, ProjectError> {
+    let mut dialog = AsyncFileDialog::new()
+        .set_title("Open a .rcproj file")
+        .add_filter("Rcarve Project", &["rcproj"]);
 
-        if let Some(dir) = projects_directory() {
-            dialog = dialog.set_directory(dir);
-        }
+    if let Some(dir) = projects_directory() {
+        dialog = dialog.set_directory(dir);
+    }
 
-        let picked = dialog.pick_file().await.ok_or(ProjectError::DialogClosed)?;
-        let path = picked.path().to_path_buf();
+    let picked = dialog.pick_file().await.ok_or(ProjectError::DialogClosed)?;
+    let path = picked.path().to_path_buf();
 
-        load_project_from_path(path)
-    }
+    load_project_from_path(path)
 }
 
-fn new_project_dialog() -> impl std::future::Future<Output = Result<OpenProject, ProjectError>> {
-    async {
-        let mut dialog = AsyncFileDialog::new()
-            .set_title("Create a new .rcproj file")
-            .add_filter("Rcarve Project", &["rcproj"]);
-
-        if let Some(dir) = projects_directory() {
-            dialog = dialog.set_directory(dir);
-        }
+async fn new_project_dialog() -> Result<OpenProject, ProjectError> {
+    let mut dialog = AsyncFileDialog::new()
+        .set_title("Create a new .rcproj file")
+        .add_filter("Rcarve Project", &["rcproj"]);
 
-        let picked = dialog.save_file().await.ok_or(ProjectError::DialogClosed)?;
-        let mut path = picked.path().to_path_buf();
+    if let Some(dir) = projects_directory() {
+        dialog = dialog.set_directory(dir);
+    }
 
-        if path.extension().and_then(|s| s.to_str()) != Some("rcproj") {
-            path.set_extension("rcproj");
-        }
+    let picked = dialog.save_file().await.ok_or(ProjectError::DialogClosed)?;
+    let mut path = picked.path().to_path_buf();
 
-        let name = infer_project_name(&path);
-        create_new_project(path.clone(), name)?;
-        load_project_from_path(path)
+    if path.extension().and_then(|s| s.to_str()) != Some("rcproj") {
+        path.set_extension("rcproj");
     }
+
+    let name = infer_project_name(&path);
+    create_new_project(path.clone(), name)?;
+    load_project_from_path(path)
 }
 
-fn import_svg_dialog(
+async fn import_svg_dialog(
     project_path: PathBuf,
-) -> impl std::future::Future<Output = Result<OpenProject, ProjectError>> {
-    async move {
-        let picked = AsyncFileDialog::new()
-            .set_title("Import an SVG file")
-            .add_filter("Scalable Vector Graphics", &["svg"])
-            .pick_file()
-            .await
-            .ok_or(ProjectError::DialogClosed)?;
-
-        let svg_path = picked.path().to_path_buf();
-        import_svg_into_project(project_path, svg_path)
-    }
+) -> Result<OpenProject, ProjectError> {
+    let picked = AsyncFileDialog::new()
+        .set_title("Import an SVG file")
+        .add_filter("Scalable Vector Graphics", &["svg"])
+        .pick_file()
+        .await
+        .ok_or(ProjectError::DialogClosed)?;
+
+    let svg_path = picked.path().to_path_buf();
+    import_svg_into_project(project_path, svg_path)
+}
+
+async fn export_gcode_dialog(name: String, gcode: rcarve::GCode) -> Result<PathBuf, ProjectError> {
+    let picked = AsyncFileDialog::new()
+        .set_title("Export G-code")
+        .add_filter("G-code", &["nc", "gcode", "ngc", "tap"])
+        .set_file_name(format!("{name}.nc"))
+        .save_file()
+        .await
+        .ok_or(ProjectError::DialogClosed)?;
+
+    let path = picked.path().to_path_buf();
+    let mut contents = gcode.lines.join("\n");
+    contents.push('\n');
+    std::fs::write(&path, contents).map_err(|error| ProjectError::Io(error.to_string()))?;
+    Ok(path)
 }
 
 fn default_project_path() -> Option<PathBuf> {
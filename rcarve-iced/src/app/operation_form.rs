@@ -1,21 +1,95 @@
 use rcarve::ids::CurveId;
-use rcarve::{CutSide, Operation, OperationTarget};
+use super::tool_form::FeedsForm;
+use super::util::{convert_length, format_length};
+use rcarve::{
+    CutDirection, CutSide, EntryStrategy, InlayPlug, LeadKind, LeadSettings, Operation,
+    OperationTarget, PocketStrategy, TabPlacement, TabSettings, Units, VCarveConfig,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OperationKindForm {
     Pocket,
     Profile,
     VCarve,
+    Drill,
+    Bore,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabMode {
+    None,
+    Count,
+    Spacing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryMode {
+    Plunge,
+    Ramp,
+    Helix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeadMode {
+    None,
+    Arc,
+    Line,
+}
+
+/// Whether a V-carve is part of an inlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayMode {
+    /// A plain V-carve.
+    Off,
+    /// A new inlay: the form describes the pocket, and saving adds its plug too.
+    Pair,
+    /// The plug of an existing inlay.
+    Plug,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PocketMode {
+    Offset,
+    Raster,
+}
+
+/// Operation inputs; lengths and feeds are typed in the project's units.
 #[derive(Debug, Clone)]
 pub struct OperationForm {
     pub kind: OperationKindForm,
+    pub units: Units,
     pub depth: String,
     pub cut_side: CutSide,
+    pub cut_direction: CutDirection,
     pub tool_index: Option<usize>,
     pub clearance_tool_index: Option<usize>,
     pub vcarve_max_depth: String,
+    /// Engine settings of a V-carve, edited in the V-carve settings modal.
+    pub vcarve_config: VCarveConfig,
+    pub finish_pass_depth: String,
+    pub tab_mode: TabMode,
+    pub tab_amount: String,
+    pub tab_width: String,
+    pub tab_height: String,
+    pub tab_positions: String,
+    pub entry_mode: EntryMode,
+    pub entry_angle: String,
+    pub helix_radius: String,
+    pub lead_mode: LeadMode,
+    pub lead_length: String,
+    pub lead_overlap: String,
+    pub feeds: FeedsForm,
+    pub pocket_mode: PocketMode,
+    pub raster_angle: String,
+    pub raster_boundary_pass: bool,
+    pub rest_tool_index: Option<usize>,
+    pub peck_depth: String,
+    pub dwell: String,
+    pub pitch: String,
+    pub inlay: InlayMode,
+    pub plug_start_depth: String,
+    pub plug_flat_depth: String,
+    pub glue_gap: String,
     pub selection_snapshot: Vec<CurveId>,
     pub error: Option<String>,
 }
@@ -30,67 +104,255 @@ impl OperationForm {
     pub fn new() -> Self {
         Self {
             kind: OperationKindForm::Profile,
+            units: Units::Millimeters,
             depth: String::from("1.0"),
             cut_side: CutSide::Outside,
+            cut_direction: CutDirection::Climb,
             tool_index: None,
             clearance_tool_index: None,
             vcarve_max_depth: String::new(),
+            vcarve_config: VCarveConfig::default(),
+            finish_pass_depth: String::new(),
+            tab_mode: TabMode::None,
+            tab_amount: String::from("4"),
+            tab_width: String::from("6.0"),
+            tab_height: String::from("2.0"),
+            tab_positions: String::new(),
+            entry_mode: EntryMode::Plunge,
+            entry_angle: String::from("3.0"),
+            helix_radius: String::from("2.0"),
+            lead_mode: LeadMode::None,
+            lead_length: String::from("3.0"),
+            lead_overlap: String::from("1.0"),
+            feeds: FeedsForm::default(),
+            pocket_mode: PocketMode::Offset,
+            raster_angle: String::from("0"),
+            raster_boundary_pass: true,
+            rest_tool_index: None,
+            peck_depth: String::new(),
+            dwell: String::new(),
+            pitch: String::from("1.0"),
+            inlay: InlayMode::Off,
+            plug_start_depth: String::new(),
+            plug_flat_depth: String::new(),
+            glue_gap: String::new(),
             selection_snapshot: Vec::new(),
             error: None,
         }
     }
 
-    pub fn from_operation(operation: &Operation) -> Self {
+    /// A new operation typed in `units`, with the defaults converted to match.
+    pub fn with_units(units: Units) -> Self {
+        let mut form = Self::new();
+        form.set_units(units);
+        form
+    }
+
+    /// The form for `operation`, shown in `units`.
+    pub fn from_operation(operation: &Operation, units: Units) -> Self {
+        let mut form = Self::from_operation_mm(operation);
+        form.set_units(units);
+        form
+    }
+
+    fn from_operation_mm(operation: &Operation) -> Self {
         match operation {
             Operation::Profile {
                 target_depth,
                 cut_side,
                 tool_index,
                 targets,
-            } => Self {
-                kind: OperationKindForm::Profile,
-                depth: target_depth.to_string(),
-                cut_side: cut_side.clone(),
-                tool_index: Some(*tool_index),
-                clearance_tool_index: None,
-                vcarve_max_depth: String::new(),
-                selection_snapshot: curves_from_target(targets),
-                error: None,
-            },
+                finish_pass_depth,
+                tabs,
+                entry,
+                direction,
+                leads,
+                feeds,
+            } => {
+                let mut form = Self {
+                    kind: OperationKindForm::Profile,
+                    depth: target_depth.to_string(),
+                    cut_side: cut_side.clone(),
+                    cut_direction: *direction,
+                    tool_index: Some(*tool_index),
+                    clearance_tool_index: None,
+                    vcarve_max_depth: String::new(),
+                    finish_pass_depth: format_optional(*finish_pass_depth),
+                    feeds: FeedsForm::from_settings(feeds, Units::Millimeters),
+                    selection_snapshot: curves_from_target(targets),
+                    ..Self::new()
+                };
+                if let Some(tabs) = tabs {
+                    let (mode, amount) = match tabs.placement {
+                        TabPlacement::Count(count) => (TabMode::Count, count.to_string()),
+                        TabPlacement::Spacing(spacing) => (TabMode::Spacing, spacing.to_string()),
+                    };
+                    form.tab_mode = mode;
+                    form.tab_amount = amount;
+                    form.tab_width = tabs.width.to_string();
+                    form.tab_height = tabs.height.to_string();
+                    form.tab_positions = tabs
+                        .positions
+                        .iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                }
+                if let Some(leads) = leads {
+                    form.lead_mode = match leads.kind {
+                        LeadKind::Arc => LeadMode::Arc,
+                        LeadKind::Line => LeadMode::Line,
+                    };
+                    form.lead_length = leads.length.to_string();
+                    form.lead_overlap = leads.overlap.to_string();
+                }
+                form.set_entry(entry);
+                form
+            }
             Operation::Pocket {
                 target_depth,
                 tool_index,
                 target,
+                finish_pass_depth,
+                entry,
+                direction,
+                feeds,
+                strategy,
+                rest_tool_index,
+            } => {
+                let mut form = Self {
+                    kind: OperationKindForm::Pocket,
+                    depth: target_depth.to_string(),
+                    cut_side: CutSide::Inside,
+                    cut_direction: *direction,
+                    tool_index: Some(*tool_index),
+                    clearance_tool_index: None,
+                    vcarve_max_depth: String::new(),
+                    finish_pass_depth: format_optional(*finish_pass_depth),
+                    feeds: FeedsForm::from_settings(feeds, Units::Millimeters),
+                    rest_tool_index: *rest_tool_index,
+                    selection_snapshot: curves_from_target(target),
+                    ..Self::new()
+                };
+                if let PocketStrategy::Raster {
+                    angle_deg,
+                    boundary_pass,
+                } = strategy
+                {
+                    form.pocket_mode = PocketMode::Raster;
+                    form.raster_angle = angle_deg.to_string();
+                    form.raster_boundary_pass = *boundary_pass;
+                }
+                form.set_entry(entry);
+                form
+            }
+            Operation::VCarve {
+                config,
+                tool_index,
+                targets,
+                clearance_tool_index,
+                feeds,
+                inlay_plug,
+            } => {
+                let mut form = Self {
+                    kind: OperationKindForm::VCarve,
+                    depth: String::new(),
+                    cut_side: CutSide::OnLine,
+                    tool_index: Some(*tool_index),
+                    clearance_tool_index: *clearance_tool_index,
+                    vcarve_max_depth: format_optional(config.flat_depth),
+                    vcarve_config: *config,
+                    finish_pass_depth: String::new(),
+                    feeds: FeedsForm::from_settings(feeds, Units::Millimeters),
+                    selection_snapshot: curves_from_target(targets),
+                    ..Self::new()
+                };
+                if let Some(plug) = inlay_plug {
+                    form.inlay = InlayMode::Plug;
+                    form.vcarve_max_depth = String::new();
+                    form.plug_flat_depth = format_optional(config.flat_depth);
+                    form.plug_start_depth = plug.start_depth.to_string();
+                    form.glue_gap = format_optional(Some(plug.glue_gap).filter(|gap| *gap > 0.0));
+                }
+                form
+            }
+            Operation::Drill {
+                target_depth,
+                tool_index,
+                targets,
+                peck_depth,
+                dwell,
+                feeds,
             } => Self {
-                kind: OperationKindForm::Pocket,
+                kind: OperationKindForm::Drill,
                 depth: target_depth.to_string(),
-                cut_side: CutSide::Inside,
+                cut_side: CutSide::OnLine,
                 tool_index: Some(*tool_index),
-                clearance_tool_index: None,
-                vcarve_max_depth: String::new(),
-                selection_snapshot: curves_from_target(target),
-                error: None,
+                peck_depth: format_optional(*peck_depth),
+                dwell: format_optional(*dwell),
+                feeds: FeedsForm::from_settings(feeds, Units::Millimeters),
+                selection_snapshot: curves_from_target(targets),
+                ..Self::new()
             },
-            Operation::VCarve {
+            Operation::Bore {
                 target_depth,
                 tool_index,
                 targets,
-                clearance_tool_index,
+                cut_side,
+                pitch,
+                direction,
+                feeds,
             } => Self {
-                kind: OperationKindForm::VCarve,
-                depth: String::new(),
-                cut_side: CutSide::OnLine,
+                kind: OperationKindForm::Bore,
+                depth: target_depth.to_string(),
+                cut_side: cut_side.clone(),
+                cut_direction: *direction,
                 tool_index: Some(*tool_index),
-                clearance_tool_index: *clearance_tool_index,
-                vcarve_max_depth: target_depth
-                    .map(|depth| depth.to_string())
-                    .unwrap_or_default(),
+                pitch: pitch.to_string(),
+                feeds: FeedsForm::from_settings(feeds, Units::Millimeters),
                 selection_snapshot: curves_from_target(targets),
-                error: None,
+                ..Self::new()
             },
         }
     }
 
+    /// Switch the units the form is typed in, converting the lengths entered so far.
+    fn set_units(&mut self, units: Units) {
+        let from = self.units;
+        let mut lengths = vec![
+            &mut self.depth,
+            &mut self.vcarve_max_depth,
+            &mut self.finish_pass_depth,
+            &mut self.tab_width,
+            &mut self.tab_height,
+            &mut self.helix_radius,
+            &mut self.lead_length,
+            &mut self.lead_overlap,
+            &mut self.peck_depth,
+            &mut self.pitch,
+            &mut self.plug_start_depth,
+            &mut self.plug_flat_depth,
+            &mut self.glue_gap,
+        ];
+        if self.tab_mode == TabMode::Spacing {
+            lengths.push(&mut self.tab_amount);
+        }
+        for length in lengths {
+            *length = convert_length(length, from, units);
+        }
+        self.feeds.convert(from, units);
+        self.units = units;
+    }
+
+    /// A positive length typed in the form's units, in millimetres.
+    fn length(&self, value: &str, label: &str) -> Result<f64, String> {
+        parse_positive(value, label).map(|length| self.units.to_mm(length))
+    }
+
+    fn optional_length(&self, value: &str, label: &str) -> Result<Option<f64>, String> {
+        parse_optional_positive(value, label).map(|length| length.map(|l| self.units.to_mm(l)))
+    }
+
     pub fn update_selection(&mut self, curves: &[CurveId]) {
         self.selection_snapshot = curves.to_vec();
     }
@@ -110,11 +372,49 @@ impl OperationForm {
         self.selection_snapshot = selected_curves.to_vec();
 
         let depth_value = if self.kind == OperationKindForm::VCarve {
-            parse_optional_positive(&self.vcarve_max_depth, "Max depth")?
+            match self.inlay {
+                InlayMode::Off => self.optional_length(&self.vcarve_max_depth, "Max depth")?,
+                InlayMode::Pair => {
+                    let pocket_depth = self.length(&self.vcarve_max_depth, "Pocket depth")?;
+                    let (plug, _) = self.inlay_plug()?;
+                    if plug.start_depth >= pocket_depth {
+                        let error =
+                            "The plug start depth must be shallower than the pocket.".to_string();
+                        self.error = Some(error.clone());
+                        return Err(error);
+                    }
+                    Some(pocket_depth)
+                }
+                InlayMode::Plug => Some(self.inlay_plug()?.1),
+            }
         } else {
-            Some(parse_positive(&self.depth, "Depth")?)
+            Some(self.length(&self.depth, "Depth")?)
         };
 
+        let fixed_entry = matches!(
+            self.kind,
+            OperationKindForm::VCarve | OperationKindForm::Drill | OperationKindForm::Bore
+        );
+        let finish_pass_depth = if fixed_entry {
+            None
+        } else {
+            self.optional_length(&self.finish_pass_depth, "Finishing pass")?
+        };
+
+        let (tabs, leads) = if self.kind == OperationKindForm::Profile {
+            (self.parse_tabs()?, self.parse_leads()?)
+        } else {
+            (None, None)
+        };
+
+        let entry = if fixed_entry {
+            EntryStrategy::Plunge
+        } else {
+            self.parse_entry()?
+        };
+
+        let feeds = self.feeds.parse(self.units)?;
+
         let tool_index = match self.tool_index {
             Some(index) if index < tool_count => index,
             Some(_) => {
@@ -129,6 +429,12 @@ impl OperationForm {
             }
         };
 
+        if self.kind == OperationKindForm::Pocket && self.rest_tool_index == Some(tool_index) {
+            let error = "Rest machining needs a larger tool than the pocket tool.".to_string();
+            self.error = Some(error.clone());
+            return Err(error);
+        }
+
         let target = OperationTarget::Curves(selected_curves.to_vec());
 
         let operation = match self.kind {
@@ -137,24 +443,199 @@ impl OperationForm {
                 cut_side: self.cut_side.clone(),
                 tool_index,
                 targets: target,
+                finish_pass_depth,
+                tabs,
+                entry,
+                direction: self.cut_direction,
+                leads,
+                feeds,
             },
             OperationKindForm::Pocket => Operation::Pocket {
                 target_depth: depth_value.expect("pocket depth set"),
                 tool_index,
                 target,
+                finish_pass_depth,
+                entry,
+                direction: self.cut_direction,
+                feeds,
+                strategy: self.parse_pocket_strategy()?,
+                rest_tool_index: self.rest_tool_index.filter(|index| *index < tool_count),
             },
             OperationKindForm::VCarve => Operation::VCarve {
-                target_depth: depth_value,
+                config: VCarveConfig {
+                    flat_depth: depth_value,
+                    ..self.vcarve_config
+                },
                 tool_index,
                 targets: target,
                 clearance_tool_index: self
                     .clearance_tool_index
                     .filter(|index| *index < tool_count),
+                feeds,
+                inlay_plug: match self.inlay {
+                    InlayMode::Plug => Some(self.inlay_plug()?.0),
+                    InlayMode::Off | InlayMode::Pair => None,
+                },
+            },
+            OperationKindForm::Drill => Operation::Drill {
+                target_depth: depth_value.expect("drill depth set"),
+                tool_index,
+                targets: target,
+                peck_depth: self.optional_length(&self.peck_depth, "Peck depth")?,
+                dwell: parse_optional_positive(&self.dwell, "Dwell")?,
+                feeds,
+            },
+            OperationKindForm::Bore => Operation::Bore {
+                target_depth: depth_value.expect("bore depth set"),
+                tool_index,
+                targets: target,
+                cut_side: self.cut_side.clone(),
+                pitch: self.length(&self.pitch, "Helix pitch")?,
+                direction: self.cut_direction,
+                feeds,
             },
         };
 
         Ok(operation)
     }
+
+    /// The plug settings and plug flat depth of an inlay.
+    pub fn inlay_plug(&mut self) -> Result<(InlayPlug, f64), String> {
+        let start_depth = self.length(&self.plug_start_depth, "Plug start depth")?;
+        let flat_depth = self.length(&self.plug_flat_depth, "Plug flat depth")?;
+        let glue_gap = self.optional_length(&self.glue_gap, "Glue gap")?.unwrap_or(0.0);
+        if flat_depth <= start_depth {
+            let error = "The plug flat depth must be deeper than its start depth.".to_string();
+            self.error = Some(error.clone());
+            return Err(error);
+        }
+        Ok((
+            InlayPlug {
+                start_depth,
+                glue_gap,
+            },
+            flat_depth,
+        ))
+    }
+
+    fn set_entry(&mut self, entry: &EntryStrategy) {
+        match entry {
+            EntryStrategy::Plunge => self.entry_mode = EntryMode::Plunge,
+            EntryStrategy::Ramp { max_angle_deg } => {
+                self.entry_mode = EntryMode::Ramp;
+                self.entry_angle = max_angle_deg.to_string();
+            }
+            EntryStrategy::Helix {
+                radius,
+                max_angle_deg,
+            } => {
+                self.entry_mode = EntryMode::Helix;
+                self.entry_angle = max_angle_deg.to_string();
+                self.helix_radius = radius.to_string();
+            }
+        }
+    }
+
+    fn parse_entry(&self) -> Result<EntryStrategy, String> {
+        let parse_angle = || {
+            let angle = parse_positive(&self.entry_angle, "Entry angle")?;
+            if angle >= 90.0 {
+                return Err("Entry angle must be less than 90 degrees".to_string());
+            }
+            Ok(angle)
+        };
+        match self.entry_mode {
+            EntryMode::Plunge => Ok(EntryStrategy::Plunge),
+            EntryMode::Ramp => Ok(EntryStrategy::Ramp {
+                max_angle_deg: parse_angle()?,
+            }),
+            EntryMode::Helix if self.kind == OperationKindForm::Pocket => Ok(EntryStrategy::Helix {
+                radius: self.length(&self.helix_radius, "Helix radius")?,
+                max_angle_deg: parse_angle()?,
+            }),
+            EntryMode::Helix => Err("Helical entry is only available for pockets".to_string()),
+        }
+    }
+
+    fn parse_pocket_strategy(&self) -> Result<PocketStrategy, String> {
+        match self.pocket_mode {
+            PocketMode::Offset => Ok(PocketStrategy::Offset),
+            PocketMode::Raster => {
+                let angle_deg: f64 = self
+                    .raster_angle
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Raster angle must be a number".to_string())?;
+                Ok(PocketStrategy::Raster {
+                    angle_deg,
+                    boundary_pass: self.raster_boundary_pass,
+                })
+            }
+        }
+    }
+
+    fn parse_leads(&self) -> Result<Option<LeadSettings>, String> {
+        let kind = match self.lead_mode {
+            LeadMode::None => return Ok(None),
+            LeadMode::Arc => LeadKind::Arc,
+            LeadMode::Line => LeadKind::Line,
+        };
+        let length_label = match kind {
+            LeadKind::Arc => "Lead radius",
+            LeadKind::Line => "Lead length",
+        };
+        let overlap: f64 = self
+            .lead_overlap
+            .trim()
+            .parse()
+            .map_err(|_| "Lead overlap must be a number".to_string())?;
+        if overlap < 0.0 {
+            return Err("Lead overlap cannot be negative".to_string());
+        }
+        Ok(Some(LeadSettings {
+            kind,
+            length: self.length(&self.lead_length, length_label)?,
+            overlap: self.units.to_mm(overlap),
+        }))
+    }
+
+    fn parse_tabs(&self) -> Result<Option<TabSettings>, String> {
+        let placement = match self.tab_mode {
+            TabMode::None => return Ok(None),
+            TabMode::Count => {
+                let count: usize = self
+                    .tab_amount
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Tab count must be a whole number".to_string())?;
+                if count == 0 {
+                    return Err("Tab count must be greater than zero".to_string());
+                }
+                TabPlacement::Count(count)
+            }
+            TabMode::Spacing => {
+                TabPlacement::Spacing(self.length(&self.tab_amount, "Tab spacing")?)
+            }
+        };
+
+        let positions = self
+            .tab_positions
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| match value.parse::<f64>() {
+                Ok(t) if (0.0..=1.0).contains(&t) => Ok(t),
+                _ => Err("Tab positions must be numbers between 0 and 1".to_string()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(TabSettings {
+            placement,
+            width: self.length(&self.tab_width, "Tab width")?,
+            height: self.length(&self.tab_height, "Tab height")?,
+            positions,
+        }))
+    }
 }
 
 fn curves_from_target(target: &OperationTarget) -> Vec<CurveId> {
@@ -164,6 +645,62 @@ fn curves_from_target(target: &OperationTarget) -> Vec<CurveId> {
     }
 }
 
+fn format_optional(value: Option<f64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// The V-carve engine settings of one operation, as edited in the V-carve settings modal.
+#[derive(Debug, Clone, Default)]
+pub struct VCarveConfigForm {
+    pub pruning_angle: String,
+    pub sampling_resolution: String,
+    pub min_feature_size: String,
+    pub units: Units,
+    pub error: Option<String>,
+}
+
+impl VCarveConfigForm {
+    pub fn from_config(config: &VCarveConfig, units: Units) -> Self {
+        Self {
+            pruning_angle: config.pruning_angle_threshold_deg.to_string(),
+            sampling_resolution: format_length(config.sampling_resolution, units),
+            min_feature_size: Some(config.min_feature_size)
+                .filter(|size| *size > 0.0)
+                .map(|size| format_length(size, units))
+                .unwrap_or_default(),
+            units,
+            error: None,
+        }
+    }
+
+    /// `config` with the edited settings; its flat depth is kept.
+    pub fn apply(&mut self, config: &VCarveConfig) -> Result<VCarveConfig, String> {
+        let result = self.parse(config);
+        self.error = result.as_ref().err().cloned();
+        result
+    }
+
+    fn parse(&self, config: &VCarveConfig) -> Result<VCarveConfig, String> {
+        let pruning_angle: f64 = self
+            .pruning_angle
+            .trim()
+            .parse()
+            .map_err(|_| "Pruning angle must be a number".to_string())?;
+        if !(0.0..90.0).contains(&pruning_angle) {
+            return Err("Pruning angle must be between 0 and 90 degrees".to_string());
+        }
+        let units = self.units;
+        Ok(VCarveConfig {
+            pruning_angle_threshold_deg: pruning_angle,
+            sampling_resolution: units
+                .to_mm(parse_positive(&self.sampling_resolution, "Sampling resolution")?),
+            min_feature_size: parse_optional_positive(&self.min_feature_size, "Minimum feature size")?
+                .map_or(0.0, |size| units.to_mm(size)),
+            ..*config
+        })
+    }
+}
+
 fn parse_positive(value: &str, label: &str) -> Result<f64, String> {
     let parsed: f64 = value
         .trim()
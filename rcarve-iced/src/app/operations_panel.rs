@@ -1,7 +1,9 @@
 use iced::border::Border;
-use iced::widget::{button, column, container, mouse_area, row, scrollable, text};
+use iced::widget::{button, checkbox, column, container, mouse_area, row, scrollable, text};
 use iced::{Alignment, Color, Element, Length};
-use rcarve::{OperationKind, OperationSummary, ToolLibrary, ToolpathStatus};
+use rcarve::{
+    Board, JobEstimate, MachiningEstimate, OperationKind, OperationSummary, ToolLibrary, ToolpathStatus,
+};
 use std::collections::HashSet;
 
 use super::{Message, canvas_view::toolpath_color};
@@ -18,8 +20,19 @@ pub fn operations_view(
     visible_paths: &HashSet<usize>,
     is_generating: bool,
     show_debug_polygons: bool,
+    inside_out: bool,
+    estimate: JobEstimate,
 ) -> Element<'static, Message> {
-    let header = operations_header(is_generating, show_debug_polygons);
+    let has_plug = entries
+        .iter()
+        .any(|(summary, _)| summary.kind == OperationKind::InlayPlug);
+    let header = operations_header(
+        is_generating,
+        show_debug_polygons,
+        inside_out,
+        has_plug,
+        &estimate,
+    );
 
     let body: Element<'static, Message> = if entries.is_empty() {
         container(
@@ -47,6 +60,7 @@ pub fn operations_view(
                         status,
                         tools,
                         visible_paths.contains(&summary.index),
+                        estimate.operations.get(summary.index).copied().flatten(),
                     )
                 })
                 .collect::<Vec<_>>(),
@@ -88,7 +102,13 @@ pub fn operations_view(
     .into()
 }
 
-fn operations_header(is_generating: bool, show_debug_polygons: bool) -> Element<'static, Message> {
+fn operations_header(
+    is_generating: bool,
+    show_debug_polygons: bool,
+    inside_out: bool,
+    has_plug: bool,
+    estimate: &JobEstimate,
+) -> Element<'static, Message> {
     let mut generate_button = button(if is_generating {
         "Generating..."
     } else {
@@ -101,6 +121,23 @@ fn operations_header(is_generating: bool, show_debug_polygons: bool) -> Element<
         generate_button = generate_button.on_press(Message::GenerateToolpaths);
     }
 
+    // An inlay plug is cut from a second board, so it gets a program of its own.
+    let mut export_buttons = row![
+        button("Export G-code")
+            .on_press(Message::ExportGcode(Board::Stock))
+            .padding([8, 16])
+            .style(|_theme, _state| secondary_button_style()),
+    ]
+    .spacing(8);
+    if has_plug {
+        export_buttons = export_buttons.push(
+            button("Export Plug G-code")
+                .on_press(Message::ExportGcode(Board::InlayPlug))
+                .padding([8, 16])
+                .style(|_theme, _state| secondary_button_style()),
+        );
+    }
+
     column![
         text("Operations")
             .size(24)
@@ -114,6 +151,7 @@ fn operations_header(is_generating: bool, show_debug_polygons: bool) -> Element<
                 .padding([8, 16])
                 .style(|_theme, _state| primary_button_style()),
             generate_button,
+            export_buttons,
         ]
         .spacing(8),
         row![
@@ -126,26 +164,68 @@ fn operations_header(is_generating: bool, show_debug_polygons: bool) -> Element<
             .padding([6, 14])
             .style(|_theme, _state| secondary_button_style()),
             button("V-Carve Debug")
-                .on_press(Message::OpenVCarveSettings)
+                .on_press(Message::OpenVCarveSettings(None))
                 .padding([6, 14])
                 .style(|_theme, _state| secondary_button_style()),
         ]
         .spacing(8),
+        checkbox("Cut features inside a profile before the profile", inside_out)
+            .on_toggle(Message::ToggleInsideOutOrder)
+            .text_size(12)
+            .style(|theme, status| iced::widget::checkbox::Style {
+                text_color: Some(MUTED_TEXT),
+                ..iced::widget::checkbox::primary(theme, status)
+            }),
+        text(job_estimate_label(estimate))
+            .size(12)
+            .style(|_| text_style(MUTED_TEXT)),
     ]
     .spacing(12)
     .into()
 }
 
+fn job_estimate_label(estimate: &JobEstimate) -> String {
+    if estimate.operations.iter().all(Option::is_none) {
+        return "Generate toolpaths to estimate the machining time.".to_string();
+    }
+    let total = &estimate.total;
+    let mut label = format!(
+        "Estimated job: {} · {} tool change{} · {} plunges",
+        format_duration(total.seconds),
+        total.tool_changes,
+        if total.tool_changes == 1 { "" } else { "s" },
+        total.plunges,
+    );
+    if let Some(cost) = estimate.cost {
+        label.push_str(&format!(" · cost {cost:.2}"));
+    }
+    label
+}
+
+/// Whole seconds under a minute, then minutes, then hours and minutes.
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
 fn operation_card(
     summary: &OperationSummary,
     status: &ToolpathStatus,
     tools: &ToolLibrary,
     is_visible: bool,
+    estimate: Option<MachiningEstimate>,
 ) -> Element<'static, Message> {
     let kind_label = match summary.kind {
         OperationKind::Profile => "Profile",
         OperationKind::Pocket => "Pocket",
         OperationKind::VCarve => "V-Carve",
+        OperationKind::InlayPlug => "Inlay plug",
+        OperationKind::Drill => "Drill",
+        OperationKind::Bore => "Bore",
     };
 
     let tool_label = tools
@@ -182,6 +262,18 @@ fn operation_card(
         details = details.push(info_row("Clearance", label));
     }
 
+    if let Some(estimate) = estimate {
+        details = details.push(info_row(
+            "Time",
+            format!(
+                "{} · {:.0}mm cut · {:.0}mm rapid",
+                format_duration(estimate.seconds),
+                estimate.cut_length,
+                estimate.rapid_length
+            ),
+        ));
+    }
+
     let visibility_control: Element<'static, Message> = match status {
         ToolpathStatus::Ready { .. } => button(if is_visible { "Hide" } else { "Show" })
             .padding([5, 12])
@@ -194,7 +286,7 @@ fn operation_card(
             .into(),
     };
 
-    let actions = row![
+    let mut actions = row![
         button("Edit")
             .padding([5, 12])
             .style(|_theme, _state| secondary_button_style())
@@ -209,6 +301,14 @@ fn operation_card(
             .on_press(Message::ClearToolpath(summary.index)),
     ]
     .spacing(6);
+    if matches!(summary.kind, OperationKind::VCarve | OperationKind::InlayPlug) {
+        actions = actions.push(
+            button("Carving")
+                .padding([5, 12])
+                .style(|_theme, _state| secondary_button_style())
+                .on_press(Message::OpenVCarveSettings(Some(summary.index))),
+        );
+    }
 
     let swatch = color_swatch(toolpath_color(summary.index));
 
@@ -334,7 +434,6 @@ fn card_style() -> iced::widget::container::Style {
 fn text_style(color: Color) -> iced::widget::text::Style {
     iced::widget::text::Style {
         color: Some(color),
-        ..Default::default()
     }
 }
 
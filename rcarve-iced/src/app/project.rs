@@ -1,4 +1,4 @@
-use rcarve::{CurveId, Project as RcarveProject, ShapeId, StockSpec, SvgImport};
+use rcarve::{CurveId, Project as RcarveProject, ShapeId, StockSpec, SvgImport, Units};
 use std::path::{Path, PathBuf};
 use ulid::Ulid;
 
@@ -33,6 +33,10 @@ impl OpenProject {
         &self.data.stock
     }
 
+    pub fn units(&self) -> Units {
+        self.data.units
+    }
+
     pub fn save(&mut self) -> Result<(), String> {
         self.data
             .save_to_path(&self.path)
@@ -1,59 +1,69 @@
-use iced::widget::{button, column, container, row, text, text_input};
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
 use iced::{Alignment, Color, Element, Length};
-use rcarve::StockSpec;
+use rcarve::{StockSpec, Units};
 
 use super::Message;
-use super::util::{format_dimension, parse_dimension, parse_origin};
+use super::util::{convert_length, format_length, parse_dimension, parse_origin};
 
-#[derive(Debug, Clone)]
+/// Stock inputs, typed in the project's units, which the form also edits.
+#[derive(Debug, Clone, Default)]
 pub struct StockForm {
     pub width: String,
     pub height: String,
     pub thickness: String,
     pub material: String,
     pub origin: String,
+    pub units: Units,
     pub error: Option<String>,
 }
 
-impl Default for StockForm {
-    fn default() -> Self {
-        Self {
-            width: String::new(),
-            height: String::new(),
-            thickness: String::new(),
-            material: String::new(),
-            origin: String::new(),
-            error: None,
-        }
-    }
-}
 
 impl StockForm {
-    pub fn from_stock(stock: &StockSpec) -> Self {
+    pub fn from_stock(stock: &StockSpec, units: Units) -> Self {
         Self {
-            width: format_dimension(stock.width),
-            height: format_dimension(stock.height),
-            thickness: format_dimension(stock.thickness),
+            width: format_length(stock.width, units),
+            height: format_length(stock.height, units),
+            thickness: format_length(stock.thickness, units),
             material: stock.material.clone().unwrap_or_default(),
             origin: stock
                 .origin
                 .map(|(x, y, z)| {
                     format!(
                         "{},{},{}",
-                        format_dimension(x),
-                        format_dimension(y),
-                        format_dimension(z)
+                        format_length(x, units),
+                        format_length(y, units),
+                        format_length(z, units)
                     )
                 })
                 .unwrap_or_default(),
+            units,
             error: None,
         }
     }
 
+    /// Switch the units the form is typed in, converting what has been entered so far.
+    pub fn set_units(&mut self, units: Units) {
+        for length in [&mut self.width, &mut self.height, &mut self.thickness] {
+            *length = convert_length(length, self.units, units);
+        }
+        if let Ok(Some((x, y, z))) = parse_origin(&self.origin) {
+            let from = self.units;
+            self.origin = format!(
+                "{},{},{}",
+                format_length(from.to_mm(x), units),
+                format_length(from.to_mm(y), units),
+                format_length(from.to_mm(z), units)
+            );
+        }
+        self.units = units;
+    }
+
+    /// The stock in millimetres.
     pub fn parse(&self) -> Result<StockSpec, String> {
-        let width = parse_dimension(&self.width, "Width")?;
-        let height = parse_dimension(&self.height, "Height")?;
-        let thickness = parse_dimension(&self.thickness, "Thickness")?;
+        let units = self.units;
+        let width = units.to_mm(parse_dimension(&self.width, "Width")?);
+        let height = units.to_mm(parse_dimension(&self.height, "Height")?);
+        let thickness = units.to_mm(parse_dimension(&self.thickness, "Thickness")?);
 
         let material = {
             let trimmed = self.material.trim();
@@ -64,7 +74,8 @@ impl StockForm {
             }
         };
 
-        let origin = parse_origin(&self.origin)?;
+        let origin = parse_origin(&self.origin)?
+            .map(|(x, y, z)| (units.to_mm(x), units.to_mm(y), units.to_mm(z)));
 
         Ok(StockSpec {
             width,
@@ -77,8 +88,18 @@ impl StockForm {
 }
 
 pub fn modal(form: &StockForm) -> Element<'_, Message> {
+    let units = column![
+        text("Units").size(12),
+        pick_list(
+            [Units::Millimeters, Units::Inches],
+            Some(form.units),
+            Message::StockUnitsChanged
+        ),
+    ]
+    .spacing(4);
+
     let width = column![
-        text("Width (mm)").size(12),
+        text(format!("Width ({})", form.units)).size(12),
         text_input("Width", &form.width)
             .on_input(Message::StockWidthChanged)
             .padding(8),
@@ -86,7 +107,7 @@ pub fn modal(form: &StockForm) -> Element<'_, Message> {
     .spacing(4);
 
     let height = column![
-        text("Height (mm)").size(12),
+        text(format!("Height ({})", form.units)).size(12),
         text_input("Height", &form.height)
             .on_input(Message::StockHeightChanged)
             .padding(8),
@@ -94,7 +115,7 @@ pub fn modal(form: &StockForm) -> Element<'_, Message> {
     .spacing(4);
 
     let thickness = column![
-        text("Thickness (mm)").size(12),
+        text(format!("Thickness ({})", form.units)).size(12),
         text_input("Thickness", &form.thickness)
             .on_input(Message::StockThicknessChanged)
             .padding(8),
@@ -119,7 +140,7 @@ pub fn modal(form: &StockForm) -> Element<'_, Message> {
 
     let mut content = column![
         text("Edit Stock").size(24),
-        column![width, height, thickness, material, origin].spacing(12),
+        column![units, width, height, thickness, material, origin].spacing(12),
     ]
     .spacing(16);
 
@@ -127,7 +148,6 @@ pub fn modal(form: &StockForm) -> Element<'_, Message> {
         let color = Color::from_rgb8(0xE5, 0x54, 0x54);
         content = content.push(text(error).style(move |_theme| iced::widget::text::Style {
             color: Some(color),
-            ..Default::default()
         }));
     }
 
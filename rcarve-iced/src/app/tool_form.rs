@@ -1,6 +1,8 @@
-use rcarve::{Tool, ToolType};
+use rcarve::{FeedSettings, Tool, ToolType, Units};
 use std::fmt;
 
+use super::util::{convert_length, format_length};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToolKind {
     Endmill,
@@ -28,19 +30,99 @@ impl fmt::Display for ToolKind {
     }
 }
 
+/// One of the feed and speed inputs shared by the tool and operation forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedField {
+    Feed,
+    Plunge,
+    Ramp,
+    Spindle,
+}
+
+/// Feed and speed inputs; empty fields are left unset. Rates are per minute in the
+/// form's units.
+#[derive(Debug, Clone, Default)]
+pub struct FeedsForm {
+    pub feed_rate: String,
+    pub plunge_rate: String,
+    pub ramp_rate: String,
+    pub spindle_rpm: String,
+}
+
+impl FeedsForm {
+    pub fn from_settings(feeds: &FeedSettings, units: Units) -> Self {
+        let format = |value: Option<f64>| {
+            value
+                .map(|rate| format_length(rate, units))
+                .unwrap_or_default()
+        };
+        Self {
+            feed_rate: format(feeds.feed_rate),
+            plunge_rate: format(feeds.plunge_rate),
+            ramp_rate: format(feeds.ramp_rate),
+            spindle_rpm: feeds.spindle_rpm.map(|v| v.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// Re-express the typed rates in other units.
+    pub fn convert(&mut self, from: Units, to: Units) {
+        for rate in [&mut self.feed_rate, &mut self.plunge_rate, &mut self.ramp_rate] {
+            *rate = convert_length(rate, from, to);
+        }
+    }
+
+    pub fn set(&mut self, field: FeedField, value: String) {
+        match field {
+            FeedField::Feed => self.feed_rate = value,
+            FeedField::Plunge => self.plunge_rate = value,
+            FeedField::Ramp => self.ramp_rate = value,
+            FeedField::Spindle => self.spindle_rpm = value,
+        }
+    }
+
+    pub fn parse(&self, units: Units) -> Result<FeedSettings, String> {
+        let parse = |input: &str, label: &str| -> Result<Option<f64>, String> {
+            let input = input.trim();
+            if input.is_empty() {
+                return Ok(None);
+            }
+            match input.parse::<f64>() {
+                Ok(value) if value > 0.0 => Ok(Some(value)),
+                Ok(_) => Err(format!("{label} must be greater than zero")),
+                Err(_) => Err(format!("{label} must be a number")),
+            }
+        };
+        let rate = |input: &str, label: &str| -> Result<Option<f64>, String> {
+            Ok(parse(input, label)?.map(|rate| units.to_mm(rate)))
+        };
+        Ok(FeedSettings {
+            feed_rate: rate(&self.feed_rate, "Feed rate")?,
+            plunge_rate: rate(&self.plunge_rate, "Plunge rate")?,
+            ramp_rate: rate(&self.ramp_rate, "Ramp rate")?,
+            spindle_rpm: parse(&self.spindle_rpm, "Spindle speed")?,
+        })
+    }
+}
+
+/// Tool inputs; sizes and feeds are typed in the form's units.
 #[derive(Debug, Clone)]
 pub struct ToolForm {
     pub name: String,
     pub diameter: String,
     pub stepover: String,
     pub pass_depth: String,
+    pub flute_length: String,
     pub vbit_angle: String,
     pub kind: ToolKind,
+    pub units: Units,
+    pub feeds: FeedsForm,
     pub name_error: Option<String>,
     pub diameter_error: Option<String>,
     pub stepover_error: Option<String>,
     pub pass_depth_error: Option<String>,
+    pub flute_length_error: Option<String>,
     pub vbit_angle_error: Option<String>,
+    pub feeds_error: Option<String>,
 }
 
 impl Default for ToolForm {
@@ -56,29 +138,43 @@ impl ToolForm {
             diameter: String::new(),
             stepover: "0.4".to_string(),
             pass_depth: String::new(),
+            flute_length: String::new(),
             vbit_angle: "60".to_string(),
             kind: ToolKind::Endmill,
+            units: Units::default(),
+            feeds: FeedsForm::default(),
             name_error: None,
             diameter_error: None,
             stepover_error: None,
             pass_depth_error: None,
+            flute_length_error: None,
             vbit_angle_error: None,
+            feeds_error: None,
         }
     }
 
     pub fn from_tool(tool: &Tool) -> Self {
+        let units = tool.units;
         let mut form = Self {
             name: tool.name.clone(),
-            diameter: format!("{}", tool.diameter),
+            diameter: format_length(tool.diameter, units),
             stepover: format!("{}", tool.stepover),
-            pass_depth: format!("{}", tool.pass_depth),
+            pass_depth: format_length(tool.pass_depth, units),
+            flute_length: tool
+                .flute_length
+                .map(|v| format_length(v, units))
+                .unwrap_or_default(),
             vbit_angle: "60".to_string(),
             kind: ToolKind::from_tool_type(&tool.tool_type),
+            units,
+            feeds: FeedsForm::from_settings(&tool.feeds, units),
             name_error: None,
             diameter_error: None,
             stepover_error: None,
             pass_depth_error: None,
+            flute_length_error: None,
             vbit_angle_error: None,
+            feeds_error: None,
         };
 
         if let ToolType::VBit { angle_degrees } = &tool.tool_type {
@@ -98,7 +194,7 @@ impl ToolForm {
         }
 
         let diameter = match self.parse_positive(&self.diameter, "Diameter") {
-            Ok(value) => value,
+            Ok(value) => self.units.to_mm(value),
             Err(err) => {
                 self.diameter_error = Some(err.clone());
                 errors.push(err);
@@ -116,7 +212,7 @@ impl ToolForm {
         };
 
         let pass_depth = match self.parse_positive(&self.pass_depth, "Pass depth") {
-            Ok(value) => value,
+            Ok(value) => self.units.to_mm(value),
             Err(err) => {
                 self.pass_depth_error = Some(err.clone());
                 errors.push(err);
@@ -124,6 +220,19 @@ impl ToolForm {
             }
         };
 
+        let flute_length = if self.flute_length.trim().is_empty() {
+            None
+        } else {
+            match self.parse_positive(&self.flute_length, "Flute length") {
+                Ok(value) => Some(self.units.to_mm(value)),
+                Err(err) => {
+                    self.flute_length_error = Some(err.clone());
+                    errors.push(err);
+                    None
+                }
+            }
+        };
+
         let tool_type = match self.kind {
             ToolKind::Endmill => ToolType::Endmill { diameter },
             ToolKind::Ballnose => ToolType::Ballnose { diameter },
@@ -139,6 +248,15 @@ impl ToolForm {
             },
         };
 
+        let feeds = match self.feeds.parse(self.units) {
+            Ok(feeds) => feeds,
+            Err(err) => {
+                self.feeds_error = Some(err.clone());
+                errors.push(err);
+                FeedSettings::default()
+            }
+        };
+
         if !errors.is_empty() {
             return Err(errors.join("\n"));
         }
@@ -149,6 +267,9 @@ impl ToolForm {
             stepover,
             pass_depth,
             tool_type,
+            feeds,
+            flute_length,
+            units: self.units,
         })
     }
 
@@ -159,6 +280,19 @@ impl ToolForm {
         }
     }
 
+    /// Switch the units the form is typed in, converting what has been entered so far.
+    pub fn set_units(&mut self, units: Units) {
+        for length in [
+            &mut self.diameter,
+            &mut self.pass_depth,
+            &mut self.flute_length,
+        ] {
+            *length = convert_length(length, self.units, units);
+        }
+        self.feeds.convert(self.units, units);
+        self.units = units;
+    }
+
     fn parse_positive(&self, input: &str, label: &str) -> Result<f64, String> {
         let value: f64 = input
             .trim()
@@ -186,6 +320,8 @@ impl ToolForm {
         self.diameter_error = None;
         self.stepover_error = None;
         self.pass_depth_error = None;
+        self.flute_length_error = None;
         self.vbit_angle_error = None;
+        self.feeds_error = None;
     }
 }
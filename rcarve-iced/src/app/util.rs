@@ -1,10 +1,28 @@
 use iced::widget::{center, container, mouse_area, opaque, stack};
 use iced::{Color, Element};
 
+use rcarve::Units;
+
 use super::Message;
 
-pub fn format_dimension(value: f64) -> String {
-    let formatted = format!("{value:.3}");
+/// A length stored in millimetres, shown in `units`.
+pub fn format_length(mm: f64, units: Units) -> String {
+    let places = match units {
+        Units::Millimeters => 3,
+        Units::Inches => 4,
+    };
+    trim_decimals(format!("{:.*}", places, units.from_mm(mm)))
+}
+
+/// Re-express a typed length in other units; anything that is not a number is kept as typed.
+pub fn convert_length(input: &str, from: Units, to: Units) -> String {
+    match input.trim().parse::<f64>() {
+        Ok(value) if from != to => format_length(from.to_mm(value), to),
+        _ => input.to_string(),
+    }
+}
+
+fn trim_decimals(formatted: String) -> String {
     let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
 
     if trimmed.is_empty() {
@@ -28,7 +46,7 @@ pub fn parse_origin(input: &str) -> Result<Option<(f64, f64, f64)>, String> {
     }
 
     let parts: Vec<_> = trimmed
-        .split(|c| c == ',' || c == ' ')
+        .split([',', ' '])
         .filter(|s| !s.is_empty())
         .collect();
 
@@ -48,14 +66,14 @@ pub fn parse_origin(input: &str) -> Result<Option<(f64, f64, f64)>, String> {
     Ok(Some((x, y, z)))
 }
 
-pub fn format_origin_label(origin: Option<(f64, f64, f64)>) -> String {
+pub fn format_origin_label(origin: Option<(f64, f64, f64)>, units: Units) -> String {
     origin
         .map(|(x, y, z)| {
             format!(
                 "({}, {}, {})",
-                format_dimension(x),
-                format_dimension(y),
-                format_dimension(z)
+                format_length(x, units),
+                format_length(y, units),
+                format_length(z, units)
             )
         })
         .unwrap_or_else(|| "—".to_string())
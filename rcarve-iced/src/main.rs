@@ -1,4 +1,3 @@
-use iced;
 
 mod app;
 